@@ -1,14 +1,17 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use hue::api::{
-    GroupedLightUpdate, LightUpdate, ResourceLink, RoomUpdate, Scene, SceneUpdate,
-    ZigbeeDeviceDiscoveryUpdate,
+    DeviceSoftwareUpdateUpdate, GroupedLightUpdate, LightUpdate, ResourceLink, RoomUpdate, Scene,
+    SceneUpdate, ZigbeeDeviceDiscoveryUpdate,
 };
 use hue::stream::HueStreamLightsV2;
 
 use crate::Client;
 use crate::config::Z2mServer;
+use crate::device::{ZclAttrRead, ZclAttrResult, ZclAttrWrite};
 use crate::error::BifrostResult;
 
 #[allow(clippy::large_enum_variant)]
@@ -25,15 +28,98 @@ pub enum BackendRequest {
 
     Delete(ResourceLink),
 
+    DeviceRename(ResourceLink, String),
+
     EntertainmentStart(Uuid),
     EntertainmentFrame(HueStreamLightsV2),
     EntertainmentStop(),
 
     ZigbeeDeviceDiscovery(ResourceLink, ZigbeeDeviceDiscoveryUpdate),
+
+    DeviceSoftwareUpdate(ResourceLink, DeviceSoftwareUpdateUpdate),
+    DeviceSoftwareCheck(ResourceLink),
+
+    ZclAttrRead(ResourceLink, ZclAttrRead),
+    ZclAttrWrite(ResourceLink, ZclAttrWrite),
+
+    BackendInfo(String),
+}
+
+/// Result of a [`BackendRequest`], for requests that produce one
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum BackendResponse {
+    ZclAttributes(BTreeMap<String, ZclAttrResult>),
+    BackendInfo(Box<BackendInfo>),
+}
+
+/// Zigbee network information and health, as reported by a backend
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BackendInfo {
+    /// Backend (zigbee2mqtt) version
+    pub version: Option<String>,
+    pub commit: Option<String>,
+    pub coordinator: Option<CoordinatorInfo>,
+    pub network: Option<NetworkInfo>,
+    pub health: Option<HealthInfo>,
+    /// Link metrics, by device name
+    pub devices: BTreeMap<String, DeviceLinkInfo>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CoordinatorInfo {
+    #[serde(rename = "type")]
+    pub coordinator_type: String,
+    pub ieee_address: String,
+    pub firmware: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkInfo {
+    pub channel: u8,
+    pub pan_id: u16,
+    pub extended_pan_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HealthInfo {
+    pub response_time: Option<i64>,
+    pub mqtt_connected: Option<bool>,
+    pub uptime_sec: Option<f64>,
+    pub memory_used_mb: Option<f64>,
+    pub load_average: Option<Vec<f64>>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DeviceLinkInfo {
+    pub linkquality: Option<u8>,
+    pub messages: Option<i64>,
+    pub messages_per_sec: Option<f64>,
+    pub leave_count: Option<i64>,
+    pub network_address_changes: Option<i64>,
 }
 
 impl Client {
+    pub async fn backend_list(&self) -> BifrostResult<BTreeMap<String, Z2mServer>> {
+        self.get("backend").await
+    }
+
+    pub async fn get_backend(&self, name: &str) -> BifrostResult<Z2mServer> {
+        self.get(&format!("backend/z2m/{name}")).await
+    }
+
+    pub async fn backend_info(&self, name: &str) -> BifrostResult<BackendInfo> {
+        self.get(&format!("backend/{name}/info")).await
+    }
+
     pub async fn post_backend(&self, name: &str, backend: Z2mServer) -> BifrostResult<()> {
         self.post(&format!("backend/z2m/{name}"), backend).await
     }
+
+    pub async fn put_backend(&self, name: &str, backend: Z2mServer) -> BifrostResult<()> {
+        self.put(&format!("backend/z2m/{name}"), backend).await
+    }
+
+    pub async fn delete_backend(&self, name: &str) -> BifrostResult<()> {
+        self.delete(&format!("backend/z2m/{name}")).await
+    }
 }
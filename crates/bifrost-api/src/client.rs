@@ -59,4 +59,8 @@ impl Client {
     ) -> BifrostResult<O> {
         self.request(scope, Method::PUT, Some(data)).await
     }
+
+    pub async fn delete<T: DeserializeOwned>(&self, scope: &str) -> BifrostResult<T> {
+        self.request(scope, Method::DELETE, None::<()>).await
+    }
 }
@@ -2,7 +2,7 @@ use std::net::Ipv4Addr;
 use std::{collections::BTreeMap, num::NonZeroU32};
 
 use camino::Utf8PathBuf;
-use hue::api::RoomArchetype;
+use hue::api::{DeviceArchetype, GamutType, LightFunction, RoomArchetype};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
@@ -30,6 +30,8 @@ pub struct BridgeConfig {
 pub struct BifrostConfig {
     pub state_file: Utf8PathBuf,
     pub cert_file: Utf8PathBuf,
+    pub recording_dir: Option<Utf8PathBuf>,
+    pub device_db_file: Option<Utf8PathBuf>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
@@ -41,9 +43,16 @@ pub struct Z2mConfig {
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Z2mServer {
     pub url: Url,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub group_prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub disable_tls_verify: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub streaming_fps: Option<NonZeroU32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streaming_fallback_fps: Option<NonZeroU32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rename_devices: Option<bool>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default, Eq, PartialEq)]
@@ -52,6 +61,18 @@ pub struct RoomConfig {
     pub icon: Option<RoomArchetype>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct DeviceConfig {
+    pub name: Option<String>,
+    pub archetype: Option<DeviceArchetype>,
+    pub function: Option<LightFunction>,
+    pub mirek_min: Option<u32>,
+    pub mirek_max: Option<u32>,
+    pub gamut: Option<GamutType>,
+    pub entertainment: Option<bool>,
+    pub hidden: Option<bool>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AppConfig {
     pub bridge: BridgeConfig,
@@ -59,6 +80,25 @@ pub struct AppConfig {
     pub bifrost: BifrostConfig,
     #[serde(default)]
     pub rooms: BTreeMap<String, RoomConfig>,
+    #[serde(default)]
+    pub devices: BTreeMap<String, DeviceConfig>,
+}
+
+impl AppConfig {
+    /// Find the overrides for a device, by friendly name or ieee address
+    #[must_use]
+    pub fn device(&self, name: &str, ieee_address: &str) -> Option<&DeviceConfig> {
+        self.devices
+            .get(name)
+            .or_else(|| self.devices.get(ieee_address))
+    }
+
+    #[must_use]
+    pub fn is_hidden(&self, name: &str, ieee_address: &str) -> bool {
+        self.device(name, ieee_address)
+            .and_then(|dev| dev.hidden)
+            .unwrap_or_default()
+    }
 }
 
 impl Z2mServer {
@@ -82,25 +122,78 @@ impl Z2mServer {
         // manually by the user, we do nothing.
         if !url.query_pairs().any(|(key, _)| key == "token") {
             url.query_pairs_mut()
-                .append_pair("token", "your-secret-token");
+                .append_pair("token", Self::DEFAULT_TOKEN);
         }
 
         url
     }
 
+    /// Placeholder for secret tokens, in urls shown to the user
+    pub const REDACTED: &str = "<<REDACTED>>";
+
+    /// The z2m default token, which is safe to show
+    const DEFAULT_TOKEN: &str = "your-secret-token";
+
+    fn url_token(url: &Url) -> Option<String> {
+        url.query_pairs()
+            .find(|(key, _)| key == "token")
+            .map(|(_, value)| value.into_owned())
+    }
+
+    fn with_url_token(url: &Url, token: &str) -> Url {
+        let mut url = url.clone();
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(key, value)| {
+                let value = if key == "token" {
+                    token.to_string()
+                } else {
+                    value.into_owned()
+                };
+                (key.into_owned(), value)
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+        url
+    }
+
+    /// Copy of this server configuration that is safe to show, with any
+    /// secret access token replaced by [`Self::REDACTED`]
+    #[must_use]
+    pub fn redacted(&self) -> Self {
+        let mut res = self.clone();
+        if Self::url_token(&self.url).is_some_and(|token| token != Self::DEFAULT_TOKEN) {
+            res.url = Self::with_url_token(&self.url, Self::REDACTED);
+        }
+        res
+    }
+
+    /// Put back the access token from `old`, if it was replaced by
+    /// [`Self::REDACTED`]. This allows a redacted configuration to be
+    /// submitted unchanged.
+    #[must_use]
+    pub fn unredacted(mut self, old: &Self) -> Self {
+        if Self::url_token(&self.url).as_deref() == Some(Self::REDACTED) {
+            if let Some(token) = Self::url_token(&old.url) {
+                self.url = Self::with_url_token(&self.url, &token);
+            }
+        }
+        self
+    }
+
     #[must_use]
     #[allow(clippy::option_if_let_else)]
     fn sanitize_url(url: &str) -> String {
         match url.find("token=") {
             Some(offset) => {
                 let token = &url[offset + "token=".len()..];
-                if token == "your-secret-token" {
+                if token == Self::DEFAULT_TOKEN {
                     // this is the standard "blank" token, it's safe to show
                     url.to_string()
                 } else {
                     // this is an actual secret token, blank it out with a
                     // standard-length placeholder.
-                    format!("{}token={}", &url[..offset], "<<REDACTED>>")
+                    format!("{}token={}", &url[..offset], Self::REDACTED)
                 }
             }
             None => url.to_string(),
@@ -118,3 +211,151 @@ impl Client {
         self.get("config").await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use url::Url;
+
+    use crate::config::{AppConfig, Z2mServer};
+
+    fn server(url: &str) -> Z2mServer {
+        Z2mServer {
+            url: Url::parse(url).unwrap(),
+            group_prefix: None,
+            disable_tls_verify: None,
+            streaming_fps: None,
+            streaming_fallback_fps: None,
+            rename_devices: None,
+        }
+    }
+
+    #[test]
+    fn serialize_skips_unset_options() {
+        let mut srv = server("ws://host:8080/api");
+        assert_eq!(
+            serde_json::to_value(&srv).unwrap(),
+            json!({"url": "ws://host:8080/api"})
+        );
+
+        srv.disable_tls_verify = Some(true);
+        assert_eq!(
+            serde_json::to_value(&srv).unwrap(),
+            json!({"url": "ws://host:8080/api", "disable_tls_verify": true})
+        );
+    }
+
+    #[test]
+    fn redact_token() {
+        let srv = server("ws://host:8080/api?foo=bar&token=secret");
+        assert_eq!(
+            srv.redacted().url.as_str(),
+            "ws://host:8080/api?foo=bar&token=%3C%3CREDACTED%3E%3E"
+        );
+    }
+
+    #[test]
+    fn redact_keeps_default_token() {
+        let srv = server("ws://host:8080/api?token=your-secret-token");
+        assert_eq!(srv.redacted(), srv);
+    }
+
+    #[test]
+    fn redact_without_token() {
+        let srv = server("ws://host:8080");
+        assert_eq!(srv.redacted(), srv);
+    }
+
+    #[test]
+    fn unredact_restores_token() {
+        let old = server("ws://host:8080/api?token=secret");
+        let new = server("ws://other:8080/api?token=secret").redacted();
+        assert_eq!(
+            new.unredacted(&old).url.as_str(),
+            "ws://other:8080/api?token=secret"
+        );
+    }
+
+    #[test]
+    fn unredact_keeps_new_token() {
+        let old = server("ws://host:8080/api?token=secret");
+        let new = server("ws://host:8080/api?token=changed");
+        assert_eq!(new.clone().unredacted(&old), new);
+    }
+
+    fn config(devices: &serde_json::Value) -> AppConfig {
+        serde_json::from_value(json!({
+            "bridge": {
+                "name": "Bifrost",
+                "mac": "00:11:22:33:44:55",
+                "ipaddress": "10.0.0.2",
+                "http_port": 80,
+                "https_port": 443,
+                "entm_port": 2100,
+                "netmask": "255.255.255.0",
+                "gateway": "10.0.0.1",
+                "timezone": "UTC",
+            },
+            "z2m": {},
+            "bifrost": {
+                "state_file": "state.yaml",
+                "cert_file": "cert.pem",
+            },
+            "devices": devices,
+        }))
+        .unwrap()
+    }
+
+    const IEEE: &str = "0x0017880100112233";
+
+    #[test]
+    fn device_by_name() {
+        let conf = config(&json!({"kitchen": {"name": "Kitchen"}}));
+
+        let dev = conf.device("kitchen", IEEE).unwrap();
+        assert_eq!(dev.name.as_deref(), Some("Kitchen"));
+        assert!(conf.device("hallway", "0x0017880100445566").is_none());
+    }
+
+    #[test]
+    fn device_by_ieee_address() {
+        let conf = config(&json!({IEEE: {"name": "Kitchen"}}));
+
+        let dev = conf.device("kitchen", IEEE).unwrap();
+        assert_eq!(dev.name.as_deref(), Some("Kitchen"));
+    }
+
+    #[test]
+    fn device_prefers_name() {
+        let conf = config(&json!({
+            "kitchen": {"name": "By name"},
+            IEEE: {"name": "By address"},
+        }));
+
+        let dev = conf.device("kitchen", IEEE).unwrap();
+        assert_eq!(dev.name.as_deref(), Some("By name"));
+    }
+
+    #[test]
+    fn device_without_overrides() {
+        let conf = config(&json!({}));
+
+        assert!(conf.device("kitchen", IEEE).is_none());
+        assert!(!conf.is_hidden("kitchen", IEEE));
+    }
+
+    #[test]
+    fn is_hidden() {
+        let conf = config(&json!({
+            "kitchen": {"hidden": true},
+            "hallway": {"hidden": false},
+            "bedroom": {"name": "Bedroom"},
+            IEEE: {"hidden": true},
+        }));
+
+        assert!(conf.is_hidden("kitchen", "0x0017880100445566"));
+        assert!(!conf.is_hidden("hallway", "0x0017880100445566"));
+        assert!(!conf.is_hidden("bedroom", "0x0017880100445566"));
+        assert!(conf.is_hidden("garage", IEEE));
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use zcl::attr::{ZclAttrValue, ZclDataType};
+
+use crate::Client;
+use crate::error::BifrostResult;
+
+/// Read zcl attributes from a device
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ZclAttrRead {
+    pub cluster: u16,
+    pub attributes: Vec<u16>,
+    /// Data types of (some of) the requested attributes. Results for these
+    /// are decoded as [`ZclAttrResult::Value`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub types: BTreeMap<u16, ZclDataType>,
+    /// Manufacturer code, for manufacturer-specific attributes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mfc: Option<u16>,
+}
+
+/// Result of reading a single zcl attribute
+///
+/// Backends might only report decoded values, without their zcl data type, so
+/// values are only typed when the data type was given in the [`ZclAttrRead`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZclAttrResult {
+    /// Value decoded with the requested data type
+    Value(ZclAttrValue),
+    /// Value as reported by the backend
+    Raw(serde_json::Value),
+}
+
+/// Write zcl attributes on a device
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ZclAttrWrite {
+    pub cluster: u16,
+    pub attributes: BTreeMap<u16, ZclAttrValue>,
+    /// Manufacturer code, for manufacturer-specific attributes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mfc: Option<u16>,
+}
+
+impl Client {
+    /// Read zcl attributes from a device. The result is keyed by attribute
+    /// name (as known by the backend), or by attribute id otherwise.
+    pub async fn device_zcl_read(
+        &self,
+        device: Uuid,
+        req: ZclAttrRead,
+    ) -> BifrostResult<BTreeMap<String, ZclAttrResult>> {
+        self.post(&format!("device/{device}/zcl/read"), req).await
+    }
+
+    /// Ask the backend to check for software updates for a device. The
+    /// result shows up in the `device_software_update` resource of the device.
+    pub async fn device_software_check(&self, device: Uuid) -> BifrostResult<()> {
+        self.post(&format!("device/{device}/software/check"), ())
+            .await
+    }
+
+    pub async fn device_zcl_write(&self, device: Uuid, req: ZclAttrWrite) -> BifrostResult<()> {
+        self.post(&format!("device/{device}/zcl/write"), req).await
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Client;
+use crate::error::BifrostResult;
+
+/// Effects rendered by the built-in entertainment effect generator
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntertainmentEffect {
+    /// Slow blue/purple waves, moving across the area
+    ColorWave,
+    /// Flickering red and orange flames
+    Fire,
+    /// Warm, gently flickering candle light
+    Candle,
+    /// Rainbow colors cycling across all channels (and gradient segments)
+    Rainbow,
+    /// Random saturated colors, changing on every beat
+    Party,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntertainmentEffectRequest {
+    pub effect: EntertainmentEffect,
+    /// Speed multiplier for the effect (default: 1.0)
+    pub speed: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntertainmentReplay {
+    /// Name of recording file, in the configured recording directory
+    pub name: String,
+}
+
+impl Client {
+    pub async fn entertainment_replay(&self, name: &str) -> BifrostResult<Uuid> {
+        let req = EntertainmentReplay {
+            name: name.to_string(),
+        };
+        self.post("entertainment/replay", req).await
+    }
+
+    pub async fn entertainment_effect_start(
+        &self,
+        area: Uuid,
+        effect: EntertainmentEffect,
+        speed: Option<f64>,
+    ) -> BifrostResult<()> {
+        let req = EntertainmentEffectRequest { effect, speed };
+        self.post(&format!("entertainment/effect/{area}"), req)
+            .await
+    }
+
+    pub async fn entertainment_effect_stop(&self, area: Uuid) -> BifrostResult<()> {
+        self.delete(&format!("entertainment/effect/{area}")).await
+    }
+}
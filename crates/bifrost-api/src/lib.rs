@@ -1,5 +1,7 @@
 pub mod backend;
 pub mod config;
+pub mod device;
+pub mod entertainment;
 pub mod error;
 pub mod service;
 pub mod websocket;
@@ -10,4 +12,5 @@ pub use client::*;
 pub mod export {
     pub extern crate hue;
     pub extern crate svc;
+    pub extern crate zcl;
 }
@@ -5,6 +5,7 @@ use crate::backend::BackendRequest;
 use crate::config::AppConfig;
 use crate::service::Service;
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Update {
     AppConfig(AppConfig),
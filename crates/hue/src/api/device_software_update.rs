@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::api::ResourceLink;
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceSoftwareUpdateState {
+    #[default]
+    NoUpdate,
+    UpdatePending,
+    ReadyToInstall,
+    Installing,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeviceSoftwareUpdate {
+    pub owner: ResourceLink,
+    pub state: DeviceSoftwareUpdateState,
+    #[serde(default)]
+    pub problems: Vec<Value>,
+    /// Install progress, in percent (Bifrost extension, only present while
+    /// installing)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<u8>,
+}
+
+impl DeviceSoftwareUpdate {
+    #[must_use]
+    pub const fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            state: DeviceSoftwareUpdateState::NoUpdate,
+            problems: vec![],
+            progress: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeviceSoftwareUpdateUpdate {
+    /// Start installing the available software update
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install: Option<bool>,
+}
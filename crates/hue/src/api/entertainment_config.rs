@@ -78,6 +78,41 @@ impl Position {
     pub const fn new(x: f64, y: f64, z: f64) -> Self {
         Self { x, y, z }
     }
+
+    #[must_use]
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        Self {
+            x: (other.x - self.x).mul_add(t, self.x),
+            y: (other.y - self.y).mul_add(t, self.y),
+            z: (other.z - self.z).mul_add(t, self.z),
+        }
+    }
+
+    /// Spread `count` points evenly along the path through `positions`
+    ///
+    /// This is used to place the segments of a gradient light along the
+    /// positions configured for it.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn spread(positions: &[Self], count: usize) -> Vec<Self> {
+        match positions {
+            [] => vec![],
+            [pos] => vec![pos.clone(); count],
+            _ => (0..count)
+                .map(|index| {
+                    let t = if count > 1 {
+                        index as f64 / (count - 1) as f64
+                    } else {
+                        0.0
+                    };
+                    let along = t * (positions.len() - 1) as f64;
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    let idx = (along.floor() as usize).min(positions.len() - 2);
+                    positions[idx].lerp(&positions[idx + 1], along - idx as f64)
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -129,7 +164,7 @@ pub struct EntertainmentConfigurationNew {
     pub locations: EntertainmentConfigurationLocationsNew,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum EntertainmentConfigurationStreamProxyMode {
     Auto,
@@ -227,3 +262,61 @@ pub struct EntertainmentConfigurationServiceLocationsNew {
     pub positions: Vec<Position>,
     pub service: ResourceLink,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::Position;
+    use crate::{compare, compare_float};
+
+    macro_rules! compare_pos {
+        ($pos:expr, $x:expr, $y:expr, $z:expr) => {{
+            let pos = &$pos;
+            compare!(pos.x, $x);
+            compare!(pos.y, $y);
+            compare!(pos.z, $z);
+        }};
+    }
+
+    #[test]
+    fn spread_empty() {
+        assert!(Position::spread(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn spread_single() {
+        let pos = Position::new(0.5, -0.5, 0.0);
+        let res = Position::spread(&[pos], 3);
+
+        assert_eq!(res.len(), 3);
+        for pos in res {
+            compare_pos!(pos, 0.5, -0.5, 0.0);
+        }
+    }
+
+    #[test]
+    fn spread_line() {
+        let a = Position::new(-1.0, 0.0, 0.0);
+        let b = Position::new(1.0, 0.0, 0.0);
+        let res = Position::spread(&[a, b], 5);
+
+        assert_eq!(res.len(), 5);
+        compare_pos!(res[0], -1.0, 0.0, 0.0);
+        compare_pos!(res[1], -0.5, 0.0, 0.0);
+        compare_pos!(res[2], 0.0, 0.0, 0.0);
+        compare_pos!(res[3], 0.5, 0.0, 0.0);
+        compare_pos!(res[4], 1.0, 0.0, 0.0);
+    }
+
+    #[test]
+    fn spread_path() {
+        let a = Position::new(0.0, 0.0, 0.0);
+        let b = Position::new(1.0, 0.0, 0.0);
+        let c = Position::new(1.0, 1.0, 0.0);
+        let res = Position::spread(&[a, b, c], 3);
+
+        assert_eq!(res.len(), 3);
+        compare_pos!(res[0], 0.0, 0.0, 0.0);
+        compare_pos!(res[1], 1.0, 0.0, 0.0);
+        compare_pos!(res[2], 1.0, 1.0, 0.0);
+    }
+}
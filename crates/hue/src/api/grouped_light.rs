@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::api::{ColorTemperatureUpdate, ColorUpdate, DimmingUpdate, On, ResourceLink, Stub};
+use crate::api::{
+    ColorTemperatureUpdate, ColorUpdate, DimmingUpdate, Light, On, ResourceLink, Stub,
+};
 use crate::legacy_api::ApiLightStateUpdate;
 use crate::xy::XY;
 
@@ -10,9 +12,9 @@ pub struct GroupedLight {
     pub alert: Value,
     pub dimming: Option<DimmingUpdate>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub color: Option<Stub>,
+    pub color: Option<GroupedLightColor>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub color_temperature: Option<Stub>,
+    pub color_temperature: Option<ColorTemperatureUpdate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub color_temperature_delta: Option<Stub>,
     #[serde(default)]
@@ -30,8 +32,8 @@ impl GroupedLight {
         Self {
             alert: Value::Null,
             dimming: None,
-            color: Some(Stub),
-            color_temperature: Some(Stub),
+            color: Some(GroupedLightColor { xy: None }),
+            color_temperature: Some(ColorTemperatureUpdate { mirek: None }),
             color_temperature_delta: Some(Stub),
             dimming_delta: Stub,
             dynamics: Stub,
@@ -45,6 +47,56 @@ impl GroupedLight {
     pub fn as_brightness_opt(&self) -> Option<f64> {
         self.dimming.as_ref().map(|br| br.brightness)
     }
+
+    /// Update the state of this group from the lights in it, the way a hue
+    /// bridge does: the group is on if any light is on, and brightness,
+    /// color and color temperature are averaged over the lights that are on
+    /// (or over all lights, if none are on).
+    pub fn aggregate<'a>(&mut self, lights: impl IntoIterator<Item = &'a Light>) {
+        let lights: Vec<&Light> = lights.into_iter().collect();
+        let lights_on: Vec<&Light> = lights.iter().copied().filter(|l| l.on.on).collect();
+
+        let active = if lights_on.is_empty() {
+            &lights
+        } else {
+            &lights_on
+        };
+
+        self.on = (!lights.is_empty()).then(|| On::new(!lights_on.is_empty()));
+
+        self.dimming = average(active.iter().filter_map(|l| l.dimming))
+            .map(|brightness| DimmingUpdate::new(brightness.clamp(0.0, 100.0)));
+
+        let xy = active.iter().filter_map(|l| l.color.as_ref().map(|c| c.xy));
+        let (x, y): (Vec<f64>, Vec<f64>) = xy.map(|xy| (xy.x, xy.y)).unzip();
+        self.color = (!lights.iter().all(|l| l.color.is_none())).then(|| GroupedLightColor {
+            xy: average(x).zip(average(y)).map(|(x, y)| XY::new(x, y)),
+        });
+
+        let mirek = active
+            .iter()
+            .filter_map(|l| l.color_temperature.as_ref())
+            .filter(|ct| ct.mirek_valid)
+            .filter_map(|ct| ct.mirek);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let mirek = average(mirek).map(|m| m.round() as u16);
+        self.color_temperature = (!lights.iter().all(|l| l.color_temperature.is_none()))
+            .then_some(ColorTemperatureUpdate { mirek });
+    }
+}
+
+fn average(values: impl IntoIterator<Item = impl Into<f64>>) -> Option<f64> {
+    let (sum, count) = values
+        .into_iter()
+        .fold((0.0, 0_u32), |(sum, count), v| (sum + v.into(), count + 1));
+
+    (count > 0).then(|| sum / f64::from(count))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct GroupedLightColor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xy: Option<XY>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -147,3 +199,99 @@ impl From<&ApiLightStateUpdate> for GroupedLightUpdate {
             )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{
+        ColorTemperature, DeviceArchetype, Dimming, GamutType, GroupedLight, Light, LightColor,
+        LightMetadata, MirekSchema, On, RType,
+    };
+    use crate::compare_float;
+    use crate::xy::XY;
+
+    fn light(on: bool, brightness: f64) -> Light {
+        let link = RType::Device.deterministic(brightness.to_bits());
+        let mut light = Light::new(
+            link,
+            LightMetadata::new(DeviceArchetype::default(), "light"),
+        );
+        light.on = On::new(on);
+        light.dimming = Some(Dimming {
+            brightness,
+            min_dim_level: None,
+        });
+        light
+    }
+
+    fn glight() -> GroupedLight {
+        GroupedLight::new(RType::Room.deterministic(0))
+    }
+
+    #[test]
+    fn aggregate_empty() {
+        let mut glight = glight();
+        glight.aggregate([]);
+        assert_eq!(glight.on, None);
+        assert_eq!(glight.dimming, None);
+        assert_eq!(glight.color, None);
+        assert_eq!(glight.color_temperature, None);
+    }
+
+    #[test]
+    fn aggregate_any_on() {
+        let lights = [light(false, 10.0), light(true, 50.0), light(true, 100.0)];
+        let mut glight = glight();
+        glight.aggregate(&lights);
+        assert_eq!(glight.on, Some(On::new(true)));
+
+        // brightness is averaged over the lights that are on
+        compare_float!(glight.as_brightness_opt().unwrap(), 75.0, 1e-9);
+    }
+
+    #[test]
+    fn aggregate_all_off() {
+        let lights = [light(false, 20.0), light(false, 40.0)];
+        let mut glight = glight();
+        glight.aggregate(&lights);
+        assert_eq!(glight.on, Some(On::new(false)));
+        compare_float!(glight.as_brightness_opt().unwrap(), 30.0, 1e-9);
+    }
+
+    #[test]
+    fn aggregate_color() {
+        let mut a = light(true, 50.0);
+        a.color = Some(LightColor {
+            gamut: None,
+            gamut_type: GamutType::C,
+            xy: XY::new(0.2, 0.4),
+        });
+        a.color_temperature = Some(ColorTemperature {
+            mirek: Some(200),
+            mirek_schema: MirekSchema::DEFAULT,
+            mirek_valid: true,
+        });
+
+        let mut b = a.clone();
+        b.color = Some(LightColor {
+            gamut: None,
+            gamut_type: GamutType::C,
+            xy: XY::new(0.4, 0.2),
+        });
+        b.color_temperature = Some(ColorTemperature {
+            mirek: Some(301),
+            mirek_schema: MirekSchema::DEFAULT,
+            mirek_valid: true,
+        });
+
+        // dimmable-only lights do not count towards color
+        let c = light(true, 50.0);
+
+        let mut glight = glight();
+        glight.aggregate([&a, &b, &c]);
+
+        let xy = glight.color.unwrap().xy.unwrap();
+        compare_float!(xy.x, 0.3, 1e-9);
+        compare_float!(xy.y, 0.3, 1e-9);
+        assert_eq!(glight.color_temperature.unwrap().mirek, Some(251));
+    }
+}
@@ -1,11 +1,14 @@
 use std::collections::BTreeSet;
 use std::ops::{AddAssign, Sub};
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::api::device::DeviceIdentifyUpdate;
 use crate::api::{DeviceArchetype, Identify, Metadata, MetadataUpdate, ResourceLink, Stub};
+use crate::date_format;
 use crate::hs::HS;
 use crate::legacy_api::ApiLightStateUpdate;
 use crate::xy::XY;
@@ -124,19 +127,7 @@ impl Light {
             }),
             metadata,
             owner,
-            powerup: Some(LightPowerup {
-                preset: LightPowerupPreset::Safety,
-                configured: true,
-                on: LightPowerupOn::On {
-                    on: On { on: true },
-                },
-                dimming: LightPowerupDimming::Dimming {
-                    dimming: DimmingUpdate { brightness: 100.0 },
-                },
-                color: LightPowerupColor::ColorTemperature {
-                    color_temperature: ColorTemperatureUpdate::new(366),
-                },
-            }),
+            powerup: Some(LightPowerup::new(LightPowerupPreset::Safety)),
             signaling: Some(LightSignaling {
                 signal_values: vec![
                     LightSignal::NoSignal,
@@ -144,7 +135,7 @@ impl Light {
                     LightSignal::OnOffColor,
                     LightSignal::Alternating,
                 ],
-                status: Value::Null,
+                status: None,
             }),
         }
     }
@@ -166,6 +157,11 @@ impl Light {
         self.color.as_ref().map(|col| col.xy)
     }
 
+    #[must_use]
+    pub fn as_gamut_opt(&self) -> Option<ColorGamut> {
+        self.color.as_ref().and_then(|col| col.gamut)
+    }
+
     #[must_use]
     pub fn as_gradient_opt(&self) -> Option<LightGradientUpdate> {
         self.gradient.as_ref().map(|grad| LightGradientUpdate {
@@ -224,6 +220,12 @@ impl AddAssign<&LightUpdate> for Light {
                 grad.points.clone_from(&grupd.points);
             }
         }
+
+        if let Some(pu) = &upd.powerup {
+            *self
+                .powerup
+                .get_or_insert_with(|| LightPowerup::new(LightPowerupPreset::Safety)) += pu;
+        }
     }
 }
 
@@ -331,7 +333,7 @@ pub struct LightGradientUpdate {
     pub points: Vec<LightGradientPoint>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum LightPowerupPreset {
     Safety,
@@ -353,6 +355,115 @@ pub struct LightPowerup {
     pub color: LightPowerupColor,
 }
 
+impl LightPowerup {
+    /// Color temperature used by the "safety" preset (2700K)
+    pub const SAFETY_MIREK: u16 = 366;
+
+    /// The settings for a powerup preset. The "custom" preset has no
+    /// settings of its own, so it starts out as "safety".
+    #[must_use]
+    pub const fn new(preset: LightPowerupPreset) -> Self {
+        let (on, dimming, color) = match preset {
+            LightPowerupPreset::Safety | LightPowerupPreset::Custom => (
+                LightPowerupOn::On { on: On::new(true) },
+                LightPowerupDimming::Dimming {
+                    dimming: DimmingUpdate::new(100.0),
+                },
+                LightPowerupColor::ColorTemperature {
+                    color_temperature: ColorTemperatureUpdate::new(Self::SAFETY_MIREK),
+                },
+            ),
+            LightPowerupPreset::Powerfail => (
+                LightPowerupOn::Previous,
+                LightPowerupDimming::Previous,
+                LightPowerupColor::Previous,
+            ),
+            LightPowerupPreset::LastOnState => (
+                LightPowerupOn::On { on: On::new(true) },
+                LightPowerupDimming::Previous,
+                LightPowerupColor::Previous,
+            ),
+        };
+
+        Self {
+            preset,
+            configured: true,
+            on,
+            dimming,
+            color,
+        }
+    }
+
+    /// Find the preset matching the current settings, or "custom" if none do
+    #[must_use]
+    pub fn detect_preset(&self) -> LightPowerupPreset {
+        [
+            LightPowerupPreset::Safety,
+            LightPowerupPreset::Powerfail,
+            LightPowerupPreset::LastOnState,
+        ]
+        .into_iter()
+        .find(|preset| {
+            let pu = Self::new(*preset);
+            pu.on == self.on && pu.dimming == self.dimming && pu.color == self.color
+        })
+        .unwrap_or(LightPowerupPreset::Custom)
+    }
+}
+
+impl AddAssign<&LightPowerupUpdate> for LightPowerup {
+    fn add_assign(&mut self, upd: &LightPowerupUpdate) {
+        match upd.preset {
+            Some(LightPowerupPreset::Custom) | None => {
+                let before = self.clone();
+
+                if !upd.on.is_none() {
+                    self.on = upd.on.clone();
+                }
+                if !upd.dimming.is_none() {
+                    self.dimming = upd.dimming.clone();
+                }
+                if !upd.color.is_none() {
+                    self.color = upd.color.clone();
+                }
+
+                // only guess the preset if the settings actually changed, so
+                // a "custom" setting identical to a preset stays "custom"
+                if let Some(preset) = upd.preset {
+                    self.preset = preset;
+                } else if *self != before {
+                    self.preset = self.detect_preset();
+                }
+                self.configured = true;
+            }
+            Some(preset) => *self = Self::new(preset),
+        }
+    }
+}
+
+/// Update to the power-on behavior of a light
+///
+/// The preset is required by the hue api, but updates reported by backends
+/// only contain the settings, so the preset is detected from those.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LightPowerupUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<LightPowerupPreset>,
+    #[serde(default, skip_serializing_if = "LightPowerupOn::is_none")]
+    pub on: LightPowerupOn,
+    #[serde(default, skip_serializing_if = "LightPowerupDimming::is_none")]
+    pub dimming: LightPowerupDimming,
+    #[serde(default, skip_serializing_if = "LightPowerupColor::is_none")]
+    pub color: LightPowerupColor,
+}
+
+impl LightPowerupUpdate {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.preset.is_none() && self.on.is_none() && self.dimming.is_none() && self.color.is_none()
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(tag = "mode", rename_all = "snake_case")]
 pub enum LightPowerupOn {
@@ -416,12 +527,46 @@ impl LightPowerupDimming {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct LightSignaling {
     pub signal_values: Vec<LightSignal>,
-    #[serde(default)]
-    #[serde(skip_serializing_if = "Value::is_null")]
-    pub status: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<LightSignalingStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LightSignalingStatus {
+    pub signal: LightSignal,
+    #[serde(with = "date_format::utc")]
+    pub estimated_end: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub colors: Vec<ColorUpdate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LightSignalingUpdate {
+    pub signal: LightSignal,
+    /// Duration of the signal, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub colors: Vec<ColorUpdate>,
+}
+
+impl LightSignalingUpdate {
+    /// Longest signal duration accepted by the hue api
+    pub const MAX_DURATION: u32 = 65_534_000;
+
+    const DEFAULT_DURATION: u32 = 2000;
+
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(u64::from(
+            self.duration
+                .unwrap_or(Self::DEFAULT_DURATION)
+                .min(Self::MAX_DURATION),
+        ))
+    }
 }
 
 #[derive(Copy, Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
@@ -528,13 +673,18 @@ pub struct LightEffects {
 
 impl LightEffects {
     #[must_use]
-    pub fn all() -> Self {
+    pub fn new(effect_values: Vec<LightEffect>) -> Self {
         Self {
-            status_values: Vec::from(LightEffect::ALL),
+            status_values: effect_values.clone(),
             status: LightEffect::NoEffect,
-            effect_values: Vec::from(LightEffect::ALL),
+            effect_values,
         }
     }
+
+    #[must_use]
+    pub fn all() -> Self {
+        Self::new(Vec::from(LightEffect::ALL))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -545,18 +695,23 @@ pub struct LightEffectsV2 {
 
 impl LightEffectsV2 {
     #[must_use]
-    pub fn all() -> Self {
+    pub fn new(effect_values: Vec<LightEffect>) -> Self {
         Self {
             action: LightEffectValues {
-                effect_values: Vec::from(LightEffect::ALL),
+                effect_values: effect_values.clone(),
             },
             status: LightEffectStatus {
                 effect: LightEffect::NoEffect,
-                effect_values: Vec::from(LightEffect::ALL),
+                effect_values,
                 parameters: None,
             },
         }
     }
+
+    #[must_use]
+    pub fn all() -> Self {
+        Self::new(Vec::from(LightEffect::ALL))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -657,13 +812,15 @@ pub struct LightUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub owner: Option<ResourceLink>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub powerup: Option<Value>,
+    pub powerup: Option<LightPowerupUpdate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dynamics: Option<LightDynamicsUpdate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub identify: Option<DeviceIdentifyUpdate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timed_effects: Option<LightTimedEffectsUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signaling: Option<LightSignalingUpdate>,
 }
 
 impl LightUpdate {
@@ -726,6 +883,42 @@ impl LightUpdate {
     pub fn with_dynamics(self, dynamics: Option<LightDynamicsUpdate>) -> Self {
         Self { dynamics, ..self }
     }
+
+    #[must_use]
+    pub fn with_powerup(self, powerup: Option<LightPowerupUpdate>) -> Self {
+        Self { powerup, ..self }
+    }
+
+    #[must_use]
+    pub fn with_signaling(self, signaling: Option<LightSignalingUpdate>) -> Self {
+        Self { signaling, ..self }
+    }
+
+    /// Project every xy color in this update onto the nearest point inside
+    /// the given gamut.
+    #[must_use]
+    pub fn with_gamut_clamp(mut self, gamut: &ColorGamut) -> Self {
+        if let Some(col) = &mut self.color {
+            col.xy = gamut.clamp(col.xy);
+        }
+
+        if let Some(grad) = &mut self.gradient {
+            for point in &mut grad.points {
+                point.color.xy = gamut.clamp(point.color.xy);
+            }
+        }
+
+        if let Some(LightEffectsV2Update {
+            action: Some(act), ..
+        }) = &mut self.effects_v2
+        {
+            if let Some(col) = &mut act.parameters.color {
+                col.xy = gamut.clamp(col.xy);
+            }
+        }
+
+        self
+    }
 }
 
 impl From<&ApiLightStateUpdate> for LightUpdate {
@@ -802,7 +995,7 @@ impl ColorTemperatureUpdate {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ColorGamut {
     pub red: XY,
     pub green: XY,
@@ -810,6 +1003,21 @@ pub struct ColorGamut {
 }
 
 impl ColorGamut {
+    pub const GAMUT_A: Self = Self {
+        red: XY { x: 0.704, y: 0.296 },
+        green: XY {
+            x: 0.2151,
+            y: 0.7106,
+        },
+        blue: XY { x: 0.138, y: 0.080 },
+    };
+
+    pub const GAMUT_B: Self = Self {
+        red: XY { x: 0.675, y: 0.322 },
+        green: XY { x: 0.409, y: 0.518 },
+        blue: XY { x: 0.167, y: 0.040 },
+    };
+
     pub const GAMUT_C: Self = Self {
         red: XY {
             x: 0.6915,
@@ -841,7 +1049,65 @@ impl ColorGamut {
     };
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+impl ColorGamut {
+    /// Cross product of (a - origin) and (b - origin). The sign tells which
+    /// side of the line origin->a the point b is on.
+    #[allow(clippy::suboptimal_flops)]
+    fn cross(origin: XY, a: XY, b: XY) -> f64 {
+        (a.x - origin.x) * (b.y - origin.y) - (a.y - origin.y) * (b.x - origin.x)
+    }
+
+    /// Find the point on the line segment a->b that is closest to p
+    fn closest_on_segment(a: XY, b: XY, p: XY) -> XY {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let len2 = dx.mul_add(dx, dy * dy);
+        if len2 < f64::EPSILON {
+            return a;
+        }
+
+        let t = ((p.x - a.x).mul_add(dx, (p.y - a.y) * dy) / len2).clamp(0.0, 1.0);
+
+        XY::new(t.mul_add(dx, a.x), t.mul_add(dy, a.y))
+    }
+
+    /// Returns true if the xy point is inside (or on the edge of) this gamut
+    #[must_use]
+    pub fn contains(&self, xy: XY) -> bool {
+        let d1 = Self::cross(self.red, self.green, xy);
+        let d2 = Self::cross(self.green, self.blue, xy);
+        let d3 = Self::cross(self.blue, self.red, xy);
+
+        let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+        let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+
+        !(has_neg && has_pos)
+    }
+
+    /// Project the xy point onto the nearest point inside this gamut.
+    ///
+    /// Points already inside the gamut are returned unchanged.
+    #[must_use]
+    pub fn clamp(&self, xy: XY) -> XY {
+        if self.contains(xy) {
+            return xy;
+        }
+
+        [
+            Self::closest_on_segment(self.red, self.green, xy),
+            Self::closest_on_segment(self.green, self.blue, xy),
+            Self::closest_on_segment(self.blue, self.red, xy),
+        ]
+        .into_iter()
+        .min_by(|a, b| {
+            let da = (a.x - xy.x).hypot(a.y - xy.y);
+            let db = (b.x - xy.x).hypot(b.y - xy.y);
+            da.total_cmp(&db)
+        })
+        .unwrap_or(xy)
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum GamutType {
     A,
     B,
@@ -850,6 +1116,18 @@ pub enum GamutType {
     Other,
 }
 
+impl GamutType {
+    #[must_use]
+    pub const fn gamut(&self) -> Option<ColorGamut> {
+        match self {
+            Self::A => Some(ColorGamut::GAMUT_A),
+            Self::B => Some(ColorGamut::GAMUT_B),
+            Self::C => Some(ColorGamut::GAMUT_C),
+            Self::Other => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct LightColor {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -867,6 +1145,21 @@ impl LightColor {
             xy,
         }
     }
+
+    #[must_use]
+    pub const fn with_gamut(self, gamut_type: GamutType, gamut: Option<ColorGamut>) -> Self {
+        Self {
+            gamut,
+            gamut_type,
+            ..self
+        }
+    }
+
+    /// Clamp an xy color to the gamut of this light (if known)
+    #[must_use]
+    pub fn clamp(&self, xy: XY) -> XY {
+        self.gamut.map_or(xy, |gamut| gamut.clamp(xy))
+    }
 }
 
 #[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -907,3 +1200,195 @@ impl From<Dimming> for f64 {
         value.brightness
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{
+        ColorGamut, ColorTemperatureUpdate, ColorUpdate, DimmingUpdate, GamutType, LightColor,
+        LightPowerup, LightPowerupColor, LightPowerupDimming, LightPowerupOn, LightPowerupPreset,
+        LightPowerupUpdate, LightSignal, LightSignalingUpdate, LightUpdate, On,
+    };
+    use crate::xy::XY;
+    use crate::{compare, compare_float, compare_xy};
+
+    #[test]
+    fn gamut_contains_primaries() {
+        let gamut = ColorGamut::GAMUT_C;
+        assert!(gamut.contains(gamut.red));
+        assert!(gamut.contains(gamut.green));
+        assert!(gamut.contains(gamut.blue));
+    }
+
+    #[test]
+    fn gamut_contains_center() {
+        for gamut in [
+            ColorGamut::GAMUT_A,
+            ColorGamut::GAMUT_B,
+            ColorGamut::GAMUT_C,
+        ] {
+            assert!(gamut.contains(XY::new(0.4, 0.35)));
+        }
+    }
+
+    #[test]
+    fn gamut_b_excludes_d65() {
+        // gamut b is famously narrow on the blue-green edge
+        assert!(!ColorGamut::GAMUT_B.contains(XY::D65_WHITE_POINT));
+        assert!(ColorGamut::GAMUT_C.contains(XY::D65_WHITE_POINT));
+    }
+
+    #[test]
+    fn gamut_clamp_inside() {
+        let xy = XY::new(0.4, 0.4);
+        compare_xy!(ColorGamut::GAMUT_C.clamp(xy), xy);
+    }
+
+    #[test]
+    fn gamut_clamp_corner() {
+        // a point far beyond the red corner projects onto the corner itself
+        let gamut = ColorGamut::GAMUT_B;
+        compare_xy!(gamut.clamp(XY::new(0.9, 0.3)), gamut.red);
+    }
+
+    #[test]
+    fn gamut_clamp_edge() {
+        // gamut c green is at (0.17, 0.70), red at (0.6915, 0.3083), so the
+        // red-green edge is a straight line. A point just outside should land
+        // on it, closer to white than the original point.
+        let gamut = ColorGamut::GAMUT_C;
+        let xy = XY::new(0.5, 0.55);
+        let res = gamut.clamp(xy);
+
+        assert!(!gamut.contains(xy));
+        assert!(gamut.contains(XY::new(res.x - 1e-6, res.y - 1e-6)));
+        assert!(res.x < xy.x);
+        assert!(res.y < xy.y);
+    }
+
+    #[test]
+    fn gamut_type_lookup() {
+        assert_eq!(GamutType::A.gamut(), Some(ColorGamut::GAMUT_A));
+        assert_eq!(GamutType::B.gamut(), Some(ColorGamut::GAMUT_B));
+        assert_eq!(GamutType::C.gamut(), Some(ColorGamut::GAMUT_C));
+        assert_eq!(GamutType::Other.gamut(), None);
+    }
+
+    #[test]
+    fn light_color_clamp_unknown_gamut() {
+        let xy = XY::new(0.9, 0.1);
+        compare_xy!(LightColor::new(XY::D65_WHITE_POINT).clamp(xy), xy);
+    }
+
+    #[test]
+    fn light_update_gamut_clamp() {
+        let gamut = ColorGamut::GAMUT_A;
+        let upd = LightUpdate::new()
+            .with_color_xy(XY::new(0.1, 0.9))
+            .with_gamut_clamp(&gamut);
+
+        let ColorUpdate { xy } = upd.color.unwrap();
+        assert!(gamut.contains(xy));
+        compare_xy!(xy, gamut.green);
+    }
+
+    #[test]
+    fn powerup_presets_detected() {
+        for preset in [
+            LightPowerupPreset::Safety,
+            LightPowerupPreset::Powerfail,
+            LightPowerupPreset::LastOnState,
+        ] {
+            assert_eq!(LightPowerup::new(preset).detect_preset(), preset);
+        }
+    }
+
+    #[test]
+    fn powerup_update_preset() {
+        let mut pu = LightPowerup::new(LightPowerupPreset::Safety);
+        pu += &LightPowerupUpdate {
+            preset: Some(LightPowerupPreset::Powerfail),
+            ..LightPowerupUpdate::default()
+        };
+        assert_eq!(pu, LightPowerup::new(LightPowerupPreset::Powerfail));
+    }
+
+    #[test]
+    fn powerup_update_custom() {
+        let mut pu = LightPowerup::new(LightPowerupPreset::Safety);
+        pu += &LightPowerupUpdate {
+            preset: Some(LightPowerupPreset::Custom),
+            on: LightPowerupOn::On { on: On::new(false) },
+            ..LightPowerupUpdate::default()
+        };
+
+        assert_eq!(pu.preset, LightPowerupPreset::Custom);
+        assert_eq!(pu.on, LightPowerupOn::On { on: On::new(false) });
+        assert_eq!(
+            pu.color,
+            LightPowerupColor::ColorTemperature {
+                color_temperature: ColorTemperatureUpdate::new(LightPowerup::SAFETY_MIREK)
+            }
+        );
+    }
+
+    #[test]
+    fn powerup_update_detects_preset() {
+        // backends report settings without a preset
+        let mut pu = LightPowerup::new(LightPowerupPreset::Safety);
+        pu += &LightPowerupUpdate {
+            preset: None,
+            on: LightPowerupOn::Previous,
+            dimming: LightPowerupDimming::Previous,
+            color: LightPowerupColor::Previous,
+        };
+        assert_eq!(pu.preset, LightPowerupPreset::Powerfail);
+
+        pu += &LightPowerupUpdate {
+            dimming: LightPowerupDimming::Dimming {
+                dimming: DimmingUpdate::new(50.0),
+            },
+            ..LightPowerupUpdate::default()
+        };
+        assert_eq!(pu.preset, LightPowerupPreset::Custom);
+    }
+
+    #[test]
+    fn powerup_update_deserialize() {
+        let upd: LightPowerupUpdate = serde_json::from_str(
+            r#"{"preset":"custom","on":{"mode":"on","on":{"on":true}},"dimming":{"mode":"previous"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(upd.preset, Some(LightPowerupPreset::Custom));
+        assert_eq!(upd.on, LightPowerupOn::On { on: On::new(true) });
+        assert_eq!(upd.dimming, LightPowerupDimming::Previous);
+        assert!(upd.color.is_none());
+    }
+
+    #[test]
+    fn signaling_update_deserialize() {
+        let upd: LightSignalingUpdate = serde_json::from_str(
+            r#"{"signal":"alternating","duration":5000,"colors":[{"xy":{"x":0.1,"y":0.2}},{"xy":{"x":0.3,"y":0.4}}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(upd.signal, LightSignal::Alternating);
+        assert_eq!(upd.duration().as_millis(), 5000);
+        assert_eq!(upd.colors.len(), 2);
+        compare_xy!(upd.colors[1].xy, XY::new(0.3, 0.4));
+    }
+
+    #[test]
+    fn signaling_update_duration_limit() {
+        let upd = LightSignalingUpdate {
+            signal: LightSignal::OnOff,
+            duration: Some(u32::MAX),
+            colors: vec![],
+        };
+
+        assert_eq!(
+            upd.duration().as_millis(),
+            u128::from(LightSignalingUpdate::MAX_DURATION)
+        );
+    }
+}
@@ -1,5 +1,6 @@
 mod behavior;
 mod device;
+mod device_software_update;
 mod entertainment;
 mod entertainment_config;
 mod grouped_light;
@@ -18,6 +19,9 @@ pub use behavior::{
     WakeupStyle,
 };
 pub use device::{Device, DeviceArchetype, DeviceProductData, DeviceUpdate, Identify};
+pub use device_software_update::{
+    DeviceSoftwareUpdate, DeviceSoftwareUpdateState, DeviceSoftwareUpdateUpdate,
+};
 pub use entertainment::{Entertainment, EntertainmentSegment, EntertainmentSegments};
 pub use entertainment_config::{
     EntertainmentConfiguration, EntertainmentConfigurationAction,
@@ -30,7 +34,7 @@ pub use entertainment_config::{
     EntertainmentConfigurationStreamProxyMode, EntertainmentConfigurationStreamProxyUpdate,
     EntertainmentConfigurationType, EntertainmentConfigurationUpdate, Position,
 };
-pub use grouped_light::{GroupedLight, GroupedLightUpdate};
+pub use grouped_light::{GroupedLight, GroupedLightColor, GroupedLightUpdate};
 pub use light::{
     ColorGamut, ColorTemperature, ColorTemperatureUpdate, ColorUpdate, Delta, Dimming,
     DimmingUpdate, GamutType, Light, LightAlert, LightColor, LightDynamics, LightDynamicsStatus,
@@ -38,8 +42,9 @@ pub use light::{
     LightEffectValues, LightEffects, LightEffectsV2, LightEffectsV2Update, LightFunction,
     LightGradient, LightGradientMode, LightGradientPoint, LightGradientUpdate, LightMetadata,
     LightMode, LightPowerup, LightPowerupColor, LightPowerupDimming, LightPowerupOn,
-    LightPowerupPreset, LightProductData, LightSignal, LightSignaling, LightTimedEffect,
-    LightTimedEffects, LightTimedEffectsUpdate, LightUpdate, MirekSchema, On,
+    LightPowerupPreset, LightPowerupUpdate, LightProductData, LightSignal, LightSignaling,
+    LightSignalingStatus, LightSignalingUpdate, LightTimedEffect, LightTimedEffects,
+    LightTimedEffectsUpdate, LightUpdate, MirekSchema, On,
 };
 pub use resource::{RType, ResourceLink, ResourceRecord};
 pub use room::{Room, RoomArchetype, RoomMetadata, RoomMetadataUpdate, RoomUpdate};
@@ -50,11 +55,10 @@ pub use scene::{
 use serde::ser::SerializeMap;
 pub use stream::HueStreamKey;
 pub use stubs::{
-    Bridge, BridgeHome, Button, ButtonData, ButtonMetadata, ButtonReport, DevicePower,
-    DeviceSoftwareUpdate, DollarRef, GeofenceClient, Geolocation, GroupedLightLevel, GroupedMotion,
-    Homekit, LightLevel, Matter, Metadata, MetadataUpdate, Motion, PrivateGroup, PublicImage,
-    RelativeRotary, SmartScene, Taurus, Temperature, TimeZone, ZigbeeConnectivity,
-    ZigbeeConnectivityStatus, Zone,
+    Bridge, BridgeHome, Button, ButtonData, ButtonMetadata, ButtonReport, DevicePower, DollarRef,
+    GeofenceClient, Geolocation, GroupedLightLevel, GroupedMotion, Homekit, LightLevel, Matter,
+    Metadata, MetadataUpdate, Motion, PrivateGroup, PublicImage, RelativeRotary, SmartScene,
+    Taurus, Temperature, TimeZone, ZigbeeConnectivity, ZigbeeConnectivityStatus, Zone,
 };
 pub use update::Update;
 pub use zigbee_device_discovery::{
@@ -84,6 +88,7 @@ impl Serialize for Stub {
     }
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Resource {
@@ -2,7 +2,7 @@ use std::collections::BTreeSet;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Value, json};
 
 use crate::api::{DeviceArchetype, LightFunction, ResourceLink, SceneMetadata};
 use crate::{best_guess_timezone, date_format};
@@ -63,13 +63,6 @@ pub struct DevicePower {
     pub power_state: Value,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DeviceSoftwareUpdate {
-    pub owner: ResourceLink,
-    pub state: Value,
-    pub problems: Vec<Value>,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GeofenceClient {
     pub name: String,
@@ -175,11 +168,13 @@ pub struct Taurus {
     pub owner: ResourceLink,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ZigbeeConnectivityStatus {
     Connected,
+    Disconnected,
     ConnectivityIssue,
+    UnidirectionalIncoming,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -193,6 +188,27 @@ pub struct ZigbeeConnectivity {
     pub status: ZigbeeConnectivityStatus,
 }
 
+impl ZigbeeConnectivity {
+    #[must_use]
+    pub fn channel_value(channel: u8) -> Value {
+        json!({
+            "status": "set",
+            "value": format!("channel_{channel}"),
+        })
+    }
+
+    #[must_use]
+    pub fn channel_number(&self) -> Option<u8> {
+        self.channel
+            .as_ref()?
+            .get("value")?
+            .as_str()?
+            .strip_prefix("channel_")?
+            .parse()
+            .ok()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Zone {
     pub metadata: Metadata,
@@ -247,3 +263,54 @@ pub struct MetadataUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub function: Option<LightFunction>,
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::api::{RType, ZigbeeConnectivity, ZigbeeConnectivityStatus};
+
+    fn zbc(channel: Option<serde_json::Value>) -> ZigbeeConnectivity {
+        ZigbeeConnectivity {
+            channel,
+            extended_pan_id: None,
+            mac_address: String::from("00:17:88:01:00:11:22:33"),
+            owner: RType::Device.deterministic("device"),
+            status: ZigbeeConnectivityStatus::Connected,
+        }
+    }
+
+    #[test]
+    fn channel_value() {
+        assert_eq!(
+            ZigbeeConnectivity::channel_value(15),
+            json!({"status": "set", "value": "channel_15"})
+        );
+    }
+
+    #[test]
+    fn channel_number_roundtrip() {
+        for channel in [11, 15, 20, 25, 26] {
+            let zbc = zbc(Some(ZigbeeConnectivity::channel_value(channel)));
+            assert_eq!(zbc.channel_number(), Some(channel));
+        }
+    }
+
+    #[test]
+    fn channel_number_missing() {
+        assert_eq!(zbc(None).channel_number(), None);
+        assert_eq!(zbc(Some(json!({"status": "set"}))).channel_number(), None);
+    }
+
+    #[test]
+    fn channel_number_invalid() {
+        for value in [
+            json!({"status": "set", "value": "not_set"}),
+            json!({"status": "set", "value": "channel_"}),
+            json!({"status": "set", "value": "channel_300"}),
+            json!({"status": "set", "value": 15}),
+        ] {
+            assert_eq!(zbc(Some(value)).channel_number(), None);
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::BTreeMap;
-use std::sync::LazyLock;
+use std::sync::{LazyLock, PoisonError, RwLock};
 
-use crate::api::{DeviceArchetype, DeviceProductData};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{DeviceArchetype, DeviceProductData, GamutType};
 
 // This file contains discovered product data from multiple sources,
 // including data samples from the community, and various open source or public
@@ -13,56 +15,161 @@ use crate::api::{DeviceArchetype, DeviceProductData};
 // provide more realistic API data, even when certain information is not
 // available from the backend (zigbee2mqtt).
 
-#[derive(Debug, Clone)]
-pub struct SimpleProductData<'a> {
-    pub manufacturer_name: &'a str,
-    pub product_name: &'a str,
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SimpleProductData {
+    pub manufacturer_name: String,
+    pub product_name: String,
+    #[serde(default)]
     pub product_archetype: DeviceArchetype,
-    pub hardware_platform_type: Option<&'a str>,
+    #[serde(default)]
+    pub hardware_platform_type: Option<String>,
+    #[serde(default)]
+    pub gamut_type: Option<GamutType>,
+    #[serde(default)]
+    pub capabilities: DeviceCapabilities,
+}
+
+/// Capability flags, for features that cannot be detected from the backend.
+///
+/// Unset flags mean "use the detected (or default) behavior".
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeviceCapabilities {
+    /// Device can be used in entertainment areas
+    #[serde(default)]
+    pub entertainment: Option<bool>,
+    /// Device supports the Hue dynamic effects (candle, fire, prism, etc)
+    #[serde(default)]
+    pub effects: Option<bool>,
 }
 
-impl<'a> SimpleProductData<'a> {
+impl SimpleProductData {
     /// helper function to construct signify devices
     #[must_use]
-    pub const fn signify(
-        product_name: &'a str,
+    pub fn signify(
+        product_name: &str,
         product_archetype: DeviceArchetype,
-        hardware_platform_type: &'a str,
+        hardware_platform_type: &str,
     ) -> Self {
         Self {
-            manufacturer_name: DeviceProductData::SIGNIFY_MANUFACTURER_NAME,
-            product_name,
+            manufacturer_name: DeviceProductData::SIGNIFY_MANUFACTURER_NAME.to_string(),
+            product_name: product_name.to_string(),
             product_archetype,
-            hardware_platform_type: Some(hardware_platform_type),
+            hardware_platform_type: Some(hardware_platform_type.to_string()),
+            gamut_type: None,
+            capabilities: DeviceCapabilities::default(),
+        }
+    }
+
+    /// helper function to construct third-party devices
+    #[must_use]
+    pub fn third_party(
+        manufacturer_name: &str,
+        product_name: &str,
+        product_archetype: DeviceArchetype,
+    ) -> Self {
+        Self {
+            manufacturer_name: manufacturer_name.to_string(),
+            product_name: product_name.to_string(),
+            product_archetype,
+            hardware_platform_type: None,
+            gamut_type: None,
+            capabilities: DeviceCapabilities::default(),
+        }
+    }
+
+    /// helper function to mark color-capable devices with their gamut
+    #[must_use]
+    pub const fn with_gamut(mut self, gamut_type: GamutType) -> Self {
+        self.gamut_type = Some(gamut_type);
+        self
+    }
+}
+
+/// A single device database entry, as found in user-supplied device database
+/// files.
+///
+/// Entries are keyed by model id (as reported by the device), and optionally
+/// the vendor, to tell apart different products that share a model id.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeviceDbEntry {
+    pub model_id: String,
+    #[serde(default)]
+    pub vendor: Option<String>,
+    #[serde(flatten)]
+    pub product: SimpleProductData,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DeviceDb {
+    entries: BTreeMap<String, Vec<(Option<String>, SimpleProductData)>>,
+}
+
+impl DeviceDb {
+    #[must_use]
+    pub fn builtin() -> Self {
+        let mut db = Self::default();
+        for (model_id, product) in make_product_data() {
+            db.insert(model_id, None, product);
+        }
+        for (vendor, model_id, product) in make_third_party_data() {
+            db.insert(model_id, Some(vendor), product);
         }
+        db
+    }
+
+    /// Add an entry to the database. Entries added later take precedence over
+    /// existing entries with the same key.
+    pub fn insert(&mut self, model_id: &str, vendor: Option<&str>, product: SimpleProductData) {
+        self.entries
+            .entry(model_id.to_string())
+            .or_default()
+            .insert(0, (vendor.map(ToString::to_string), product));
+    }
+
+    /// Look up a model id, preferring entries for any of the given vendors,
+    /// and falling back to entries without a vendor.
+    #[must_use]
+    pub fn lookup(&self, vendors: &[&str], model_id: &str) -> Option<&SimpleProductData> {
+        let entries = self.entries.get(model_id)?;
+
+        entries
+            .iter()
+            .find(|(vendor, _)| {
+                vendor
+                    .as_deref()
+                    .is_some_and(|v| vendors.iter().any(|x| x.eq_ignore_ascii_case(v)))
+            })
+            .or_else(|| entries.iter().find(|(vendor, _)| vendor.is_none()))
+            .map(|(_, product)| product)
     }
 }
 
-static PRODUCT_DATA: LazyLock<BTreeMap<&str, SimpleProductData>> = LazyLock::new(make_product_data);
+static DEVICE_DB: LazyLock<RwLock<DeviceDb>> = LazyLock::new(|| RwLock::new(DeviceDb::builtin()));
 
 #[cfg_attr(coverage_nightly, coverage(off))]
-fn make_product_data() -> BTreeMap<&'static str, SimpleProductData<'static>> {
+fn make_product_data() -> BTreeMap<&'static str, SimpleProductData> {
     // use shorter alias for better formatting
     #[allow(clippy::enum_glob_use)]
     use DeviceArchetype::*;
+    use GamutType::C;
     use SimpleProductData as SPD;
 
     maplit::btreemap! {
-        "915005987201" => SPD::signify("Signe gradient floor", HueSigne, "100b-118"),
-        "929003053301_01" => SPD::signify("Hue Ensis up", PendantLong, "100b-11f"),
-        "929003053301_02" => SPD::signify("Hue Ensis down", PendantLong, "100b-11f"),
-        "LCA001" => SPD::signify("Hue color lamp", SultanBulb, "100b-112"),
-        "LCD007" => SPD::signify("Hue color downlight", RecessedCeiling, "100b-114"),
-        "LCE002" => SPD::signify("Hue color candle", CandleBulb, "100b-114"),
-        "LCG002" => SPD::signify("Hue color spot", SpotBulb, "100b-114"),
-        "LCT014" => SPD::signify("Hue color lamp", SultanBulb, "100b-10c"),
-        "LCT015" => SPD::signify("Hue color lamp", SultanBulb, "100b-10c"),
-        "LCT016" => SPD::signify("Hue color lamp", SultanBulb, "100b-10c"),
-        "LCX001" => SPD::signify("Hue play gradient lightstrip", HueLightstripTv, "100b-118"),
-        "LCX005" => SPD::signify("Hue play gradient lightstrip", HueLightstripPc, "100b-118"),
-        "LLC020" => SPD::signify("Hue go", HueGo, "100b-108"),
+        "915005987201" => SPD::signify("Signe gradient floor", HueSigne, "100b-118").with_gamut(C),
+        "929003053301_01" => SPD::signify("Hue Ensis up", PendantLong, "100b-11f").with_gamut(C),
+        "929003053301_02" => SPD::signify("Hue Ensis down", PendantLong, "100b-11f").with_gamut(C),
+        "LCA001" => SPD::signify("Hue color lamp", SultanBulb, "100b-112").with_gamut(C),
+        "LCD007" => SPD::signify("Hue color downlight", RecessedCeiling, "100b-114").with_gamut(C),
+        "LCE002" => SPD::signify("Hue color candle", CandleBulb, "100b-114").with_gamut(C),
+        "LCG002" => SPD::signify("Hue color spot", SpotBulb, "100b-114").with_gamut(C),
+        "LCT014" => SPD::signify("Hue color lamp", SultanBulb, "100b-10c").with_gamut(C),
+        "LCT015" => SPD::signify("Hue color lamp", SultanBulb, "100b-10c").with_gamut(C),
+        "LCT016" => SPD::signify("Hue color lamp", SultanBulb, "100b-10c").with_gamut(C),
+        "LCX001" => SPD::signify("Hue play gradient lightstrip", HueLightstripTv, "100b-118").with_gamut(C),
+        "LCX005" => SPD::signify("Hue play gradient lightstrip", HueLightstripPc, "100b-118").with_gamut(C),
+        "LLC020" => SPD::signify("Hue go", HueGo, "100b-108").with_gamut(C),
         "LOM001" => SPD::signify("Hue Smart plug", Plug, "100b-115"),
-        "LST002" => SPD::signify("Hue lightstrip plus", HueLightstrip, "100b-10f"),
+        "LST002" => SPD::signify("Hue lightstrip plus", HueLightstrip, "100b-10f").with_gamut(C),
         "LTO001" => SPD::signify("Hue filament bulb", VintageBulb, "100b-114"),
         "LTW015" => SPD::signify("Hue ambiance lamp", SultanBulb, "100b-10c"),
         "LWA003" => SPD::signify("Hue white lamp", SultanBulb, "100b-114"),
@@ -76,17 +183,137 @@ fn make_product_data() -> BTreeMap<&'static str, SimpleProductData<'static>> {
         "SML003" => SPD::signify("Hue motion sensor", UnknownArchetype, "100b-11b"),
 
         "Z3-1BRL" => SPD {
-            manufacturer_name: "Lutron",
-            product_name: "Lutron Aurora",
-            product_archetype: UnknownArchetype,
-            hardware_platform_type: Some("1144-0"),
+            hardware_platform_type: Some("1144-0".to_string()),
+            ..SPD::third_party("Lutron", "Lutron Aurora", UnknownArchetype)
         },
     }
 }
 
+/// Common third-party devices, as (vendor, model id, product data).
+///
+/// The vendor is matched against both the manufacturer name reported by the
+/// device, and the vendor name used by zigbee2mqtt.
+#[cfg_attr(coverage_nightly, coverage(off))]
+fn make_third_party_data() -> Vec<(&'static str, &'static str, SimpleProductData)> {
+    // use shorter alias for better formatting
+    #[allow(clippy::enum_glob_use)]
+    use DeviceArchetype::*;
+
+    const IKEA: &str = "IKEA of Sweden";
+    const INNR: &str = "innr";
+    const GLEDOPTO: &str = "GLEDOPTO";
+
+    // (vendor, model id, product name, archetype)
+    let devices = [
+        (
+            IKEA,
+            "TRADFRI bulb E27 CWS opal 600lm",
+            "TRADFRI bulb E27 CWS",
+            ClassicBulb,
+        ),
+        (
+            IKEA,
+            "TRADFRI bulb E27 CWS 806lm",
+            "TRADFRI bulb E27 CWS",
+            ClassicBulb,
+        ),
+        (
+            IKEA,
+            "TRADFRI bulb E14 CWS 470lm",
+            "TRADFRI bulb E14 CWS",
+            CandleBulb,
+        ),
+        (
+            IKEA,
+            "TRADFRI bulb GU10 CWS 345lm",
+            "TRADFRI bulb GU10 CWS",
+            SpotBulb,
+        ),
+        (
+            IKEA,
+            "TRADFRI bulb E27 WS opal 980lm",
+            "TRADFRI bulb E27 WS",
+            ClassicBulb,
+        ),
+        (
+            IKEA,
+            "TRADFRI bulb E27 WS globe 1055lm",
+            "TRADFRI bulb E27 WS globe",
+            SultanBulb,
+        ),
+        (
+            IKEA,
+            "TRADFRI bulb E14 WS opal 400lm",
+            "TRADFRI bulb E14 WS",
+            CandleBulb,
+        ),
+        (
+            IKEA,
+            "TRADFRI bulb GU10 WS 400lm",
+            "TRADFRI bulb GU10 WS",
+            SpotBulb,
+        ),
+        (
+            IKEA,
+            "TRADFRI bulb E27 W opal 1000lm",
+            "TRADFRI bulb E27 W",
+            ClassicBulb,
+        ),
+        (
+            IKEA,
+            "TRADFRI control outlet",
+            "TRADFRI control outlet",
+            Plug,
+        ),
+        (INNR, "RB 285 C", "Smart bulb colour E27", ClassicBulb),
+        (INNR, "RB 250 C", "Smart candle colour E14", CandleBulb),
+        (INNR, "RS 230 C", "Smart spot colour GU10", SpotBulb),
+        (INNR, "RB 265", "Smart bulb white E27", ClassicBulb),
+        (INNR, "RB 278 T", "Smart bulb comfort E27", ClassicBulb),
+        (INNR, "FL 130 C", "Flex light colour", HueLightstrip),
+        (INNR, "SP 120", "Smart plug", Plug),
+        (GLEDOPTO, "GL-C-007", "LED controller RGBW", HueLightstrip),
+        (
+            GLEDOPTO,
+            "GL-C-008",
+            "LED controller RGB+CCT",
+            HueLightstrip,
+        ),
+        (GLEDOPTO, "GL-S-007Z", "Smart RGBW GU10", SpotBulb),
+        (GLEDOPTO, "GL-B-008Z", "Smart 12W E27 RGB+CCT", ClassicBulb),
+    ];
+
+    devices
+        .into_iter()
+        .map(|(vendor, model_id, name, archetype)| {
+            let product = SimpleProductData::third_party(vendor, name, archetype);
+            (vendor, model_id, product)
+        })
+        .collect()
+}
+
+/// Add user-supplied entries to the device database. These take precedence
+/// over the built-in entries.
+pub fn register(entries: impl IntoIterator<Item = DeviceDbEntry>) {
+    let mut db = DEVICE_DB.write().unwrap_or_else(PoisonError::into_inner);
+    for entry in entries {
+        db.insert(&entry.model_id, entry.vendor.as_deref(), entry.product);
+    }
+}
+
+#[must_use]
+pub fn product_data(model_id: &str) -> Option<SimpleProductData> {
+    vendor_product_data(&[], model_id)
+}
+
+/// Look up product data by model id and vendor name(s)
 #[must_use]
-pub fn product_data(model_id: &str) -> Option<SimpleProductData<'static>> {
-    PRODUCT_DATA.get(model_id).cloned()
+pub fn vendor_product_data(vendors: &[&str], model_id: &str) -> Option<SimpleProductData> {
+    DEVICE_DB
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .lookup(vendors, model_id)
+        .cloned()
 }
 
 #[must_use]
@@ -95,14 +322,36 @@ pub fn product_archetype(model_id: &str) -> Option<DeviceArchetype> {
 }
 
 #[must_use]
-pub fn hardware_platform_type(model_id: &str) -> Option<&'static str> {
+pub fn hardware_platform_type(model_id: &str) -> Option<String> {
     product_data(model_id).and_then(|pd| pd.hardware_platform_type)
 }
 
+#[must_use]
+pub fn gamut_type(model_id: &str) -> Option<GamutType> {
+    product_data(model_id)
+        .and_then(|pd| pd.gamut_type)
+        .or_else(|| legacy_gamut_type(model_id))
+}
+
+/// Gamut types for older Philips Hue models, that are not (yet) in the product
+/// database. Source: "Hue API: Supported lights" developer documentation.
+fn legacy_gamut_type(model_id: &str) -> Option<GamutType> {
+    match model_id {
+        "LLC001" | "LLC005" | "LLC006" | "LLC007" | "LLC010" | "LLC011" | "LLC012" | "LLC013"
+        | "LLC014" | "LST001" => Some(GamutType::A),
+        "LCT001" | "LCT002" | "LCT003" | "LCT007" | "LLM001" => Some(GamutType::B),
+        "LCT010" | "LCT011" | "LCT012" => Some(GamutType::C),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::api::DeviceArchetype;
-    use crate::devicedb::{hardware_platform_type, product_archetype, product_data};
+    use crate::api::{DeviceArchetype, GamutType};
+    use crate::devicedb::{
+        DeviceDb, DeviceDbEntry, SimpleProductData, gamut_type, hardware_platform_type,
+        product_archetype, product_data,
+    };
 
     #[test]
     fn lookup_spf() {
@@ -121,4 +370,65 @@ mod tests {
     fn lookup_platform_type() {
         assert_eq!(hardware_platform_type("LCX001").unwrap(), "100b-118",);
     }
+
+    #[test]
+    fn lookup_gamut_type() {
+        assert_eq!(gamut_type("LCX001"), Some(GamutType::C));
+    }
+
+    #[test]
+    fn lookup_gamut_type_legacy() {
+        assert_eq!(gamut_type("LCT001"), Some(GamutType::B));
+        assert_eq!(gamut_type("LST001"), Some(GamutType::A));
+    }
+
+    #[test]
+    fn lookup_gamut_type_white() {
+        assert_eq!(gamut_type("LWB014"), None);
+    }
+
+    #[test]
+    fn lookup_third_party_requires_vendor() {
+        let db = DeviceDb::builtin();
+        assert!(db.lookup(&[], "RB 285 C").is_none());
+        assert_eq!(
+            db.lookup(&["Innr"], "RB 285 C").unwrap().product_archetype,
+            DeviceArchetype::ClassicBulb
+        );
+    }
+
+    #[test]
+    fn lookup_vendor_falls_back() {
+        let db = DeviceDb::builtin();
+        let pd = db.lookup(&["Philips"], "LCX001").unwrap();
+        assert_eq!(pd.product_archetype, DeviceArchetype::HueLightstripTv);
+    }
+
+    #[test]
+    fn insert_takes_precedence() {
+        let mut db = DeviceDb::builtin();
+        let spd = SimpleProductData::third_party("Signify", "Custom", DeviceArchetype::HueGo);
+        db.insert("LCX001", None, spd.clone());
+        assert_eq!(db.lookup(&[], "LCX001"), Some(&spd));
+    }
+
+    #[test]
+    fn parse_entry() {
+        let entry: DeviceDbEntry = serde_json::from_value(serde_json::json!({
+            "model_id": "ABC123",
+            "vendor": "Acme",
+            "manufacturer_name": "Acme Inc",
+            "product_name": "Acme bulb",
+            "product_archetype": "candle_bulb",
+            "gamut_type": "C",
+            "capabilities": {"entertainment": false},
+        }))
+        .unwrap();
+
+        assert_eq!(entry.vendor.as_deref(), Some("Acme"));
+        assert_eq!(entry.product.product_archetype, DeviceArchetype::CandleBulb);
+        assert_eq!(entry.product.gamut_type, Some(GamutType::C));
+        assert_eq!(entry.product.capabilities.entertainment, Some(false));
+        assert_eq!(entry.product.capabilities.effects, None);
+    }
 }
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use uuid::Uuid;
 
-use crate::api::{ColorGamut, DeviceProductData};
+use crate::api::{ColorGamut, DeviceProductData, GamutType};
 use crate::date_format;
 use crate::hs::RawHS;
 use crate::{api, best_guess_timezone};
@@ -420,8 +420,8 @@ impl ApiGroup {
                 hue: None,
                 sat: None,
                 effect: None,
-                xy: None,
-                ct: None,
+                xy: glight.color.and_then(|col| col.xy).map(|xy| [xy.x, xy.y]),
+                ct: glight.color_temperature.and_then(|ct| ct.mirek),
                 alert: ApiAlert::None,
                 colormode: None,
             },
@@ -442,6 +442,20 @@ pub struct ApiGroupState {
     pub any_on: bool,
 }
 
+impl ApiGroupState {
+    #[must_use]
+    pub fn from_lights<'a>(lights: impl IntoIterator<Item = &'a api::Light>) -> Self {
+        let (count, on) = lights.into_iter().fold((0, 0), |(count, on), light| {
+            (count + 1, on + usize::from(light.on.on))
+        });
+
+        Self {
+            all_on: count > 0 && on == count,
+            any_on: on > 0,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum LightColorMode {
@@ -551,13 +565,52 @@ impl ApiLight {
     #[must_use]
     pub fn from_dev_and_light(uuid: &Uuid, dev: &api::Device, light: &api::Light) -> Self {
         let colormode = if light.color.is_some() {
-            LightColorMode::Xy
+            Some(LightColorMode::Xy)
+        } else if light.color_temperature.is_some() {
+            Some(LightColorMode::Ct)
         } else {
-            LightColorMode::Ct
+            None
+        };
+
+        let light_type = match (&light.color, &light.color_temperature, &light.dimming) {
+            (Some(_), Some(_), _) => "Extended color light",
+            (Some(_), None, _) => "Color light",
+            (None, Some(_), _) => "Color temperature light",
+            (None, None, Some(_)) => "Dimmable light",
+            (None, None, None) => "On/Off light",
         };
 
         let product_data = dev.product_data.clone();
 
+        let mut control = json!({
+            "maxlumen": 800,
+        });
+
+        if let Some(dim) = &light.dimming {
+            // v1 reports the minimum dim level in units of 0.001%
+            let level = dim.min_dim_level.unwrap_or(0.01) * 1000.0;
+            control["mindimlevel"] = json!(level.round() as u32);
+        }
+
+        if let Some(col) = &light.color {
+            let gamut = col.gamut.unwrap_or(ColorGamut::GAMUT_C);
+            control["colorgamut"] = json!([
+                [gamut.red.x, gamut.red.y],
+                [gamut.green.x, gamut.green.y],
+                [gamut.blue.x, gamut.blue.y],
+            ]);
+            if let Some(gamut_type) = legacy_gamut_type(col.gamut_type) {
+                control["colorgamuttype"] = json!(gamut_type);
+            }
+        }
+
+        if let Some(ct) = &light.color_temperature {
+            control["ct"] = json!({
+                "min": ct.mirek_schema.mirek_minimum,
+                "max": ct.mirek_schema.mirek_maximum,
+            });
+        }
+
         Self {
             state: ApiLightState {
                 on: light.on.on,
@@ -570,7 +623,7 @@ impl ApiLight {
                 xy: light.color.clone().map(|col| col.xy.into()),
                 ct: light.color_temperature.clone().and_then(|ct| ct.mirek),
                 alert: "select".into(),
-                colormode: Some(colormode),
+                colormode,
                 mode: "homeautomation".to_string(),
                 reachable: true,
             },
@@ -582,21 +635,8 @@ impl ApiLight {
             productid: product_data.hardware_platform_type,
 
             capabilities: json!({
-                "certified": true,
-                "control": {
-                    "colorgamut": [
-                        [ColorGamut::GAMUT_C.red.x,   ColorGamut::GAMUT_C.red.y  ],
-                        [ColorGamut::GAMUT_C.green.x, ColorGamut::GAMUT_C.green.y],
-                        [ColorGamut::GAMUT_C.blue.x,  ColorGamut::GAMUT_C.blue.y ],
-                    ],
-                    "colorgamuttype": "C",
-                    "ct": {
-                        "max": 500,
-                        "min": 153
-                    },
-                    "maxlumen": 800,
-                    "mindimlevel": 10
-                },
+                "certified": product_data.certified,
+                "control": control,
                 "streaming": {
                     "proxy": true,
                     "renderer": true
@@ -611,7 +651,7 @@ impl ApiLight {
                     "configured": true
                 }
             }),
-            light_type: "Extended color light".to_string(),
+            light_type: light_type.to_string(),
 
             /* FIXME: Should have form "00:11:22:33:44:55:66:77-0b" */
             uniqueid: uuid.as_simple().to_string(),
@@ -622,6 +662,12 @@ impl ApiLight {
             swconfigid: None,
         }
     }
+
+    #[must_use]
+    pub const fn with_reachable(mut self, reachable: bool) -> Self {
+        self.state.reachable = reachable;
+        self
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -910,6 +956,16 @@ impl Capabilities {
     }
 }
 
+/// The v1 api only knows the hue gamut types, so other gamuts are left out
+const fn legacy_gamut_type(gamut_type: GamutType) -> Option<&'static str> {
+    match gamut_type {
+        GamutType::A => Some("A"),
+        GamutType::B => Some("B"),
+        GamutType::C => Some("C"),
+        GamutType::Other => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "mac")]
@@ -927,4 +983,15 @@ mod tests {
 
         assert_eq!(res, b"\"01:02:03:aa:bb:cc\"");
     }
+
+    #[test]
+    fn legacy_gamut_type() {
+        use crate::api::GamutType;
+        use crate::legacy_api::legacy_gamut_type;
+
+        assert_eq!(legacy_gamut_type(GamutType::A), Some("A"));
+        assert_eq!(legacy_gamut_type(GamutType::B), Some("B"));
+        assert_eq!(legacy_gamut_type(GamutType::C), Some("C"));
+        assert_eq!(legacy_gamut_type(GamutType::Other), None);
+    }
 }
@@ -49,6 +49,22 @@ impl HueStreamHeader {
 
         Ok(hdr)
     }
+
+    #[must_use]
+    pub fn new(version: HueStreamVersion, color_mode: HueStreamColorMode) -> Self {
+        let mut magic = [0; 9];
+        magic.copy_from_slice(Self::MAGIC);
+
+        Self {
+            magic,
+            version,
+            x0: 0,
+            seqnr: 0,
+            x1: 0,
+            color_mode,
+            x2: 0,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -88,6 +104,29 @@ impl HueStreamPacketV2 {
             HueStreamLightsV2::Xy(_) => HueStreamColorMode::Xy,
         }
     }
+
+    /// Encode packet in the wire format understood by [`HueStreamPacket::parse`]
+    pub fn pack(&self) -> HueResult<Vec<u8>> {
+        let hdr = HueStreamHeader::new(HueStreamVersion::V2, self.color_mode());
+
+        let mut res = hdr.pack()?.to_vec();
+        res.extend_from_slice(self.area.hyphenated().to_string().as_bytes());
+
+        match &self.lights {
+            HueStreamLightsV2::Rgb(rgb) => {
+                for light in rgb {
+                    res.extend_from_slice(&light.pack()?);
+                }
+            }
+            HueStreamLightsV2::Xy(xy) => {
+                for light in xy {
+                    res.extend_from_slice(&light.pack()?);
+                }
+            }
+        }
+
+        Ok(res)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -246,9 +285,11 @@ impl Xy16 {
 #[cfg(test)]
 mod tests {
     use crate::error::HueError;
+    use uuid::uuid;
+
     use crate::stream::{
         HueStreamColorMode, HueStreamHeader, HueStreamLightsV1, HueStreamLightsV2, HueStreamPacket,
-        Rgb16, Xy16,
+        HueStreamPacketV2, Rgb16, Xy16, Xy16V2,
     };
     use crate::xy::XY;
     use crate::{compare, compare_float, compare_xy};
@@ -480,4 +521,37 @@ mod tests {
 
         assert_eq!(res.color_mode(), HueStreamColorMode::Xy);
     }
+
+    #[test]
+    fn pack_packet_v2_roundtrip() {
+        let area = uuid!("01234567-89ab-cdef-0123-456789abcdef");
+        let lights = HueStreamLightsV2::Xy(vec![Xy16V2 {
+            channel: 0x11,
+            xy: Xy16 {
+                x: 0xA0A1,
+                y: 0xB0B1,
+                b: 0xC0C1,
+            },
+        }]);
+        let pkt = HueStreamPacketV2 { area, lights };
+
+        let data = pkt.pack().unwrap();
+
+        let HueStreamPacket::V2(res) = HueStreamPacket::parse(&data).unwrap() else {
+            panic!();
+        };
+
+        assert_eq!(res.area, area);
+        assert_eq!(res.color_mode(), HueStreamColorMode::Xy);
+
+        let HueStreamLightsV2::Xy(xy) = res.lights else {
+            panic!();
+        };
+
+        assert_eq!(xy.len(), 1);
+        assert_eq!(xy[0].channel, 0x11);
+        assert_eq!(xy[0].xy.x, 0xA0A1);
+        assert_eq!(xy[0].xy.y, 0xB0B1);
+        assert_eq!(xy[0].xy.b, 0xC0C1);
+    }
 }
@@ -98,7 +98,7 @@ bitflags! {
     }
 }
 
-#[derive(PackedStruct)]
+#[derive(PackedStruct, Debug)]
 #[packed_struct(endian = "lsb", bit_numbering = "msb0")]
 pub struct GradientUpdateHeader {
     /// First 4 bits of first byte: number of gradient light points
@@ -118,6 +118,7 @@ pub struct GradientUpdateHeader {
     pub resv2: u16,
 }
 
+#[derive(Debug)]
 pub struct GradientColors {
     pub header: GradientUpdateHeader,
     pub points: Vec<XY>,
@@ -146,7 +147,7 @@ impl GradientParams {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Debug)]
 pub struct HueZigbeeUpdate {
     pub onoff: Option<u8>,
     pub brightness: Option<u8>,
@@ -1,8 +1,7 @@
 use chrono::Duration;
-use packed_struct::prelude::*;
 
 use crate::error::{HueError, HueResult};
-use crate::zigbee::{HueEntFrame, HueEntFrameLightRecord, HueEntSegmentConfig, HueEntStop};
+use crate::zigbee::{HueEntFrame, HueEntFrameLightRecord, HueEntStop};
 
 pub struct EntertainmentZigbeeStream {
     smoothing: u16,
@@ -115,31 +114,18 @@ impl EntertainmentZigbeeStream {
         Ok(())
     }
 
-    pub fn segment_mapping(&mut self, map: &[u16]) -> HueResult<ZigbeeMessage> {
-        let msg = HueEntSegmentConfig::new(map);
-
-        Ok(ZigbeeMessage::new(
-            Self::CLUSTER,
-            Self::CMD_SEGMENT_MAP,
-            msg.pack()?,
-        ))
-    }
-
-    pub fn reset(&mut self) -> HueResult<ZigbeeMessage> {
-        let ent = HueEntStop {
+    /// Stop message for the current stream
+    #[must_use]
+    pub const fn stop(&self) -> HueEntStop {
+        HueEntStop {
             x0: 0,
             x1: 1,
             counter: self.counter,
-        };
-
-        Ok(ZigbeeMessage::new(
-            Self::CLUSTER,
-            Self::CMD_RESET,
-            ent.pack_to_vec()?,
-        ))
+        }
     }
 
-    pub fn frame(&mut self, blks: Vec<HueEntFrameLightRecord>) -> HueResult<ZigbeeMessage> {
+    /// Next frame of the stream (advances the frame counter)
+    pub const fn next_frame(&mut self, blks: Vec<HueEntFrameLightRecord>) -> HueEntFrame {
         let ent = HueEntFrame {
             counter: self.counter,
             smoothing: self.smoothing,
@@ -148,11 +134,7 @@ impl EntertainmentZigbeeStream {
 
         self.counter += 1;
 
-        Ok(ZigbeeMessage::new(
-            Self::CLUSTER,
-            Self::CMD_FRAME,
-            ent.pack()?,
-        ))
+        ent
     }
 }
 
@@ -211,55 +193,30 @@ mod tests {
     }
 
     #[test]
-    fn ezs_reset() {
-        let mut ezs = EZS::new(0x1122);
+    fn ezs_stop() {
+        let ezs = EZS::new(0x1122);
 
-        let rst = ezs.reset().unwrap();
-        assert_eq!(rst.cluster, EZS::CLUSTER);
-        assert_eq!(rst.command, EZS::CMD_RESET);
-        assert_eq!(rst.data, [0x00, 0x01, 0x22, 0x11, 0x00, 0x00]);
+        let stop = ezs.stop();
+        assert_eq!(stop.counter, 0x1122);
 
         // counter should be the same
         assert_eq!(ezs.counter(), 0x1122);
     }
 
-    #[allow(clippy::bool_assert_comparison, clippy::cast_possible_truncation)]
     #[test]
-    fn ezs_frame() {
+    fn ezs_next_frame() {
         let mut ezs = EZS::new(0x1122);
+        ezs.set_smoothing(0x3344);
 
-        let rst = ezs.frame(vec![]).unwrap();
-        assert_eq!(rst.cluster, EZS::CLUSTER);
-        assert_eq!(rst.command, EZS::CMD_FRAME);
-        assert_eq!(
-            rst.data,
-            [
-                0x22,
-                0x11,
-                0x00,
-                0x00,
-                ezs.smoothing as u8,
-                (ezs.smoothing >> 8) as u8
-            ]
-        );
+        let frame = ezs.next_frame(vec![]);
+        assert_eq!(frame.counter, 0x1122);
+        assert_eq!(frame.smoothing, 0x3344);
+        assert!(frame.blks.is_empty());
 
         // counter should be incremented
         assert_eq!(ezs.counter(), 0x1123);
     }
 
-    #[test]
-    fn ezs_segment_mapping() {
-        let mut ezs = EZS::new(0x1122);
-
-        let rst = ezs.segment_mapping(&[0xA0A1, 0xB0B1]).unwrap();
-        assert_eq!(rst.cluster, EZS::CLUSTER);
-        assert_eq!(rst.command, EZS::CMD_SEGMENT_MAP);
-        assert_eq!(rst.data, [0x00, 0x02, 0xA1, 0xA0, 0xB1, 0xB0]);
-
-        // counter should be the same
-        assert_eq!(ezs.counter(), 0x1122);
-    }
-
     #[test]
     fn duration_zero() {
         let zero_dur = Duration::seconds(0);
@@ -63,6 +63,7 @@ impl ServiceEvent {
 pub enum SvmRequest {
     Stop(RpcRequest<ServiceId, SvcResult<Uuid>>),
     Start(RpcRequest<ServiceId, SvcResult<Uuid>>),
+    Remove(RpcRequest<ServiceId, SvcResult<Uuid>>),
     Status(RpcRequest<ServiceId, SvcResult<ServiceState>>),
     List(RpcRequest<(), Vec<(Uuid, ServiceName)>>),
     Resolve(RpcRequest<ServiceId, SvcResult<Uuid>>),
@@ -148,6 +149,12 @@ impl SvmClient {
         self.rpc(SvmRequest::Stop, id.service_id()).await?
     }
 
+    /// Remove a service from the service manager, aborting it if it is still
+    /// running. Stop the service first, for a graceful shutdown.
+    pub async fn remove(&mut self, id: impl IntoServiceId) -> SvcResult<Uuid> {
+        self.rpc(SvmRequest::Remove, id.service_id()).await?
+    }
+
     pub async fn resolve(&mut self, id: impl IntoServiceId) -> SvcResult<Uuid> {
         self.rpc(SvmRequest::Resolve, id.service_id()).await?
     }
@@ -223,6 +230,7 @@ impl Debug for SvmRequest {
         match self {
             Self::Stop(arg0) => f.debug_tuple("Stop").field(arg0).finish(),
             Self::Start(arg0) => f.debug_tuple("Start").field(arg0).finish(),
+            Self::Remove(arg0) => f.debug_tuple("Remove").field(arg0).finish(),
             Self::Status(arg0) => f.debug_tuple("Status").field(arg0).finish(),
             Self::List(arg0) => f.debug_tuple("List").field(arg0).finish(),
             Self::Register(_arg0) => f.debug_tuple("Register").field(&"<service>").finish(),
@@ -437,10 +445,18 @@ impl ServiceManager {
     }
 
     fn handle_service_event(&mut self, event: ServiceEvent) {
+        // events can still arrive from services that have just been removed
+        let Some(svc) = self.svcs.get_mut(&event.id) else {
+            return;
+        };
+        log::trace!(
+            "[{}] [{}] Service is now {:?}",
+            svc.name,
+            event.id,
+            event.state
+        );
+        svc.state = event.state;
         self.notify_subscribers(event);
-        let name = &self.svcs[&event.id].name;
-        log::trace!("[{name}] [{}] Service is now {:?}", event.id, event.state);
-        self.svcs.get_mut(&event.id).unwrap().state = event.state;
     }
 
     async fn handle_svm_request(&mut self, upd: SvmRequest) -> SvcResult<()> {
@@ -449,6 +465,13 @@ impl ServiceManager {
 
             SvmRequest::Stop(rpc) => rpc.respond(|id| self.stop(&id)),
 
+            SvmRequest::Remove(rpc) => rpc.respond(|id| {
+                let uuid = self.resolve(&id)?;
+                log::debug!("Removing service: {uuid} {}", self.svcs[&uuid].name);
+                self.abort(&id)?;
+                Ok(uuid)
+            }),
+
             SvmRequest::Status(rpc) => rpc.respond(|id| Ok(self.get(&id)?.state)),
 
             SvmRequest::List(rpc) => rpc.respond(|()| {
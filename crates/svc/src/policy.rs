@@ -11,10 +11,21 @@ pub enum Retry {
     Forever,
 }
 
+/// Exponential backoff, applied on top of the base delay of a [`Policy`].
+///
+/// The delay for retry `n` is `delay * factor^n`, capped at `max`.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub factor: u32,
+    pub max: Duration,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Policy {
     pub retry: Retry,
     pub delay: Option<Duration>,
+    pub backoff: Option<Backoff>,
+    pub jitter: Option<Duration>,
 }
 
 impl Default for Policy {
@@ -29,9 +40,21 @@ impl Policy {
         Self {
             retry: Retry::No,
             delay: None,
+            backoff: None,
+            jitter: None,
         }
     }
 
+    /// Policy that retries forever, with exponential backoff between `delay`
+    /// and `max`.
+    #[must_use]
+    pub const fn forever(delay: Duration, max: Duration) -> Self {
+        Self::new()
+            .with_retry(Retry::Forever)
+            .with_delay(delay)
+            .with_backoff(2, max)
+    }
+
     #[must_use]
     pub const fn with_retry(self, retry: Retry) -> Self {
         Self { retry, ..self }
@@ -53,10 +76,81 @@ impl Policy {
         }
     }
 
+    #[must_use]
+    pub const fn with_backoff(self, factor: u32, max: Duration) -> Self {
+        Self {
+            backoff: Some(Backoff { factor, max }),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub const fn without_backoff(self) -> Self {
+        Self {
+            backoff: None,
+            ..self
+        }
+    }
+
+    /// Add a random delay (between zero and `jitter`) to every retry.
+    #[must_use]
+    pub const fn with_jitter(self, jitter: Duration) -> Self {
+        Self {
+            jitter: Some(jitter),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub const fn without_jitter(self) -> Self {
+        Self {
+            jitter: None,
+            ..self
+        }
+    }
+
+    /// Calculate the delay before attempt number `retry`, not including jitter.
+    #[must_use]
+    pub fn delay_for(&self, retry: u32) -> Option<Duration> {
+        let delay = self.delay?;
+
+        let Some(backoff) = self.backoff else {
+            return Some(delay);
+        };
+
+        let scaled = backoff
+            .factor
+            .checked_pow(retry)
+            .and_then(|mult| delay.checked_mul(mult))
+            .unwrap_or(backoff.max);
+
+        Some(scaled.min(backoff.max))
+    }
+
     #[cfg(feature = "manager")]
     pub async fn sleep(&self) {
-        if let Some(dur) = self.delay {
-            sleep(dur).await;
+        self.sleep_for(0).await;
+    }
+
+    /// Calculate the delay before attempt number `retry`, including a random
+    /// amount of jitter (if configured).
+    #[cfg(feature = "manager")]
+    #[must_use]
+    pub fn delay_with_jitter(&self, retry: u32) -> Duration {
+        let jitter = self
+            .jitter
+            .map(|jitter| jitter.mul_f64(rand::random::<f64>()))
+            .unwrap_or_default();
+
+        self.delay_for(retry).unwrap_or_default() + jitter
+    }
+
+    #[cfg(feature = "manager")]
+    pub async fn sleep_for(&self, retry: u32) {
+        let delay = self.delay_with_jitter(retry);
+
+        if !delay.is_zero() {
+            sleep(delay).await;
         }
     }
 
@@ -69,3 +163,73 @@ impl Policy {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::policy::{Policy, Retry};
+
+    const SEC: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn delay_none() {
+        assert_eq!(Policy::new().delay_for(0), None);
+        assert_eq!(Policy::new().delay_for(5), None);
+    }
+
+    #[test]
+    fn delay_fixed() {
+        let policy = Policy::new().with_delay(SEC);
+        assert_eq!(policy.delay_for(0), Some(SEC));
+        assert_eq!(policy.delay_for(10), Some(SEC));
+    }
+
+    #[test]
+    fn backoff_growth() {
+        let policy = Policy::new().with_delay(SEC).with_backoff(2, SEC * 60);
+        assert_eq!(policy.delay_for(0), Some(SEC));
+        assert_eq!(policy.delay_for(1), Some(SEC * 2));
+        assert_eq!(policy.delay_for(2), Some(SEC * 4));
+        assert_eq!(policy.delay_for(5), Some(SEC * 32));
+    }
+
+    #[test]
+    fn backoff_cap() {
+        let policy = Policy::forever(SEC, SEC * 60);
+        assert_eq!(policy.delay_for(6), Some(SEC * 60));
+        assert_eq!(policy.delay_for(20), Some(SEC * 60));
+        // overflowing multipliers are capped too
+        assert_eq!(policy.delay_for(u32::MAX), Some(SEC * 60));
+    }
+
+    #[test]
+    fn jitter_bounds() {
+        let policy = Policy::new().with_delay(SEC).with_jitter(SEC / 2);
+        for _ in 0..100 {
+            let delay = policy.delay_with_jitter(0);
+            assert!(delay >= SEC);
+            assert!(delay <= SEC + SEC / 2);
+        }
+    }
+
+    #[test]
+    fn jitter_without_delay() {
+        let policy = Policy::new().with_jitter(SEC);
+        for _ in 0..100 {
+            assert!(policy.delay_with_jitter(3) <= SEC);
+        }
+    }
+
+    #[test]
+    fn should_retry() {
+        assert!(!Policy::new().with_retry(Retry::No).should_retry(0));
+        assert!(Policy::new().with_retry(Retry::Limit(2)).should_retry(1));
+        assert!(!Policy::new().with_retry(Retry::Limit(2)).should_retry(2));
+        assert!(
+            Policy::new()
+                .with_retry(Retry::Forever)
+                .should_retry(u32::MAX)
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, watch};
 use tokio::time::sleep;
 use uuid::Uuid;
@@ -13,11 +13,17 @@ use crate::traits::{Service, ServiceRunner, ServiceState, StopResult};
 struct State {
     id: Uuid,
     retry: u32,
+    run_retry: u32,
+    running_since: Option<Instant>,
     state: ServiceState,
     tx: mpsc::UnboundedSender<ServiceEvent>,
 }
 
 impl State {
+    /// A service that kept running for this long is considered healthy again,
+    /// so its next failure starts over from the first retry
+    const STABLE_RUN_TIME: Duration = Duration::from_secs(60);
+
     pub const fn new(
         id: Uuid,
         state: ServiceState,
@@ -26,6 +32,8 @@ impl State {
         Self {
             id,
             retry: 0,
+            run_retry: 0,
+            running_since: None,
             state,
             tx,
         }
@@ -34,6 +42,11 @@ impl State {
     pub fn set(&mut self, next: ServiceState) -> Result<(), RunSvcError> {
         self.state = next;
         self.retry = 0;
+        // failed runs are counted across restarts, until the service is stopped
+        if matches!(next, ServiceState::Stopped | ServiceState::Failed) {
+            self.run_retry = 0;
+        }
+        self.running_since = (next == ServiceState::Running).then(Instant::now);
         Ok(self.tx.send(ServiceEvent::new(self.id, self.state))?)
     }
 
@@ -46,6 +59,18 @@ impl State {
         self.retry += 1;
         res
     }
+
+    pub fn run_retry(&mut self) -> u32 {
+        if self
+            .running_since
+            .is_some_and(|since| since.elapsed() >= Self::STABLE_RUN_TIME)
+        {
+            self.run_retry = 0;
+        }
+        let res = self.run_retry;
+        self.run_retry += 1;
+        res
+    }
 }
 
 pub struct StandardService<S: Service> {
@@ -59,14 +84,21 @@ pub struct StandardService<S: Service> {
 
 impl<S: Service> StandardService<S> {
     pub fn new(name: impl AsRef<str>, svc: S) -> Self {
+        let start_policy = svc.start_policy().unwrap_or_else(|| {
+            Policy::new()
+                .with_delay(Duration::from_secs(3))
+                .with_retry(Retry::Forever)
+        });
+        let run_policy = svc
+            .run_policy()
+            .unwrap_or_else(|| Policy::new().with_delay(Duration::from_secs(1)));
+
         Self {
             name: name.as_ref().to_string(),
             svc,
             configure_policy: Policy::new(),
-            start_policy: Policy::new()
-                .with_delay(Duration::from_secs(1))
-                .with_retry(Retry::Limit(3)),
-            run_policy: Policy::new().with_delay(Duration::from_secs(1)),
+            start_policy,
+            run_policy,
             stop_policy: Policy::new(),
         }
     }
@@ -155,20 +187,32 @@ impl<S: Service> ServiceRunner for StandardService<S> {
                     }
                 }
 
-                ServiceState::Starting => match svc.start().await {
-                    Ok(()) => {
-                        log::debug!(target:target, "Started");
-                        state.set(ServiceState::Running)?;
+                ServiceState::Starting => {
+                    if *rx.borrow() == ServiceState::Stopped {
+                        state.set(ServiceState::Stopped)?;
+                        continue;
                     }
-                    Err(err) => {
-                        log::error!(target:target, "Failed to start service: {err}");
-                        if *rx.borrow() == ServiceState::Stopped {
-                            state.set(ServiceState::Stopped)?;
-                        } else {
-                            sleep(Duration::from_secs(3)).await;
+
+                    match svc.start().await {
+                        Ok(()) => {
+                            log::debug!(target:target, "Started");
+                            state.set(ServiceState::Running)?;
+                        }
+                        Err(err) => {
+                            log::error!(target:target, "Failed to start service: {err}");
+                            let retry = state.retry();
+                            if self.start_policy.should_retry(retry) {
+                                // wait for the next attempt, unless asked to change state
+                                tokio::select! {
+                                    () = self.start_policy.sleep_for(retry) => {}
+                                    _ = rx.changed() => {}
+                                }
+                            } else {
+                                state.set(ServiceState::Failed)?;
+                            }
                         }
                     }
-                },
+                }
 
                 ServiceState::Running => {
                     tokio::select! {
@@ -178,9 +222,17 @@ impl<S: Service> ServiceRunner for StandardService<S> {
                                 state.set(ServiceState::Stopping)?;
                             }
                             Err(err) => {
-                                self.run_policy.sleep().await;
-                                if self.run_policy.should_retry(state.retry()) {
-                                    log::warn!(target:target, "Service failed to start, retrying..");
+                                let retry = state.run_retry();
+                                if self.run_policy.should_retry(retry) {
+                                    log::warn!(target:target, "Service failed: {err}, restarting..");
+                                    if let Err(err) = svc.stop().await {
+                                        log::error!(target:target, "Failed to stop failing service: {err}");
+                                    }
+                                    tokio::select! {
+                                        () = self.run_policy.sleep_for(retry) => {}
+                                        _ = rx.changed() => {}
+                                    }
+                                    state.set(ServiceState::Starting)?;
                                 } else {
                                     log::error!(target:target, "Failed to run service: {err}");
                                     match svc.stop().await {
@@ -258,3 +310,40 @@ impl<S: Service> ServiceRunner for StandardService<S> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use crate::runservice::State;
+    use crate::traits::ServiceState;
+
+    #[test]
+    fn run_retry_counts_quick_failures() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut state = State::new(Uuid::nil(), ServiceState::Starting, tx);
+
+        for n in 0..3 {
+            state.set(ServiceState::Running).unwrap();
+            assert_eq!(state.run_retry(), n);
+            state.set(ServiceState::Starting).unwrap();
+        }
+    }
+
+    #[test]
+    fn run_retry_resets_after_stable_run() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut state = State::new(Uuid::nil(), ServiceState::Starting, tx);
+
+        state.set(ServiceState::Running).unwrap();
+        assert_eq!(state.run_retry(), 0);
+        assert_eq!(state.run_retry(), 1);
+
+        state.set(ServiceState::Running).unwrap();
+        state.running_since = Instant::now().checked_sub(State::STABLE_RUN_TIME);
+        assert_eq!(state.run_retry(), 0);
+    }
+}
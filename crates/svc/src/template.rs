@@ -3,6 +3,7 @@ use async_trait::async_trait;
 #[cfg(feature = "manager")]
 use crate::error::RunSvcError;
 use crate::error::SvcError;
+use crate::policy::Policy;
 use crate::traits::{BoxDynService, Service, StopResult};
 
 #[cfg(feature = "manager")]
@@ -58,6 +59,14 @@ impl<S: Service> Service for ErrorAdapter<S> {
             .await
             .map_err(|err| RunSvcError::ServiceError(Box::new(err)))
     }
+
+    fn start_policy(&self) -> Option<Policy> {
+        self.svc.start_policy()
+    }
+
+    fn run_policy(&self) -> Option<Policy> {
+        self.svc.run_policy()
+    }
 }
 
 impl<F> ServiceTemplate for F
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use crate::error::RunSvcError;
 #[cfg(feature = "manager")]
 use crate::manager::ServiceEvent;
+use crate::policy::Policy;
 #[cfg(feature = "manager")]
 use crate::template::ErrorAdapter;
 #[cfg(feature = "manager")]
@@ -99,6 +100,18 @@ pub trait Service: Send {
         Ok(StopResult::NotSupported)
     }
 
+    /// Policy for retrying a failed [`Service::start`], if this service needs
+    /// something other than the default.
+    fn start_policy(&self) -> Option<Policy> {
+        None
+    }
+
+    /// Policy for restarting the service after [`Service::run`] fails, if this
+    /// service needs something other than the default.
+    fn run_policy(&self) -> Option<Policy> {
+        None
+    }
+
     #[cfg(feature = "manager")]
     fn boxed(self) -> BoxDynService
     where
@@ -134,6 +147,14 @@ impl Service for BoxDynService {
     fn signal_stop<'a: 'b, 'b>(&'a mut self) -> BoxFuture<'b, Result<StopResult, Self::Error>> {
         (**self).signal_stop()
     }
+
+    fn start_policy(&self) -> Option<Policy> {
+        (**self).start_policy()
+    }
+
+    fn run_policy(&self) -> Option<Policy> {
+        (**self).run_policy()
+    }
 }
 
 #[cfg(feature = "manager")]
@@ -7,6 +7,8 @@ use std::fmt::Display;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
+use hue::devicedb::{SimpleProductData, vendor_product_data};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct RawMessage {
@@ -48,13 +50,19 @@ pub enum Message {
     BridgeOptions(Value),
 
     #[serde(rename = "bridge/response/touchlink/scan")]
-    BridgeTouchlinkScan(Value),
+    BridgeTouchlinkScan(Response<TouchlinkScan>),
+
+    #[serde(rename = "bridge/response/touchlink/identify")]
+    BridgeTouchlinkIdentify(Response<Value>),
+
+    #[serde(rename = "bridge/response/touchlink/factory_reset")]
+    BridgeTouchlinkFactoryReset(Response<Value>),
 
     #[serde(rename = "bridge/response/permit_join")]
-    BridgePermitJoin(Value),
+    BridgePermitJoin(Response<PermitJoinResponse>),
 
     #[serde(rename = "bridge/response/networkmap")]
-    BridgeNetworkmap(Value),
+    BridgeNetworkmap(Response<NetworkMap>),
 
     #[serde(rename = "bridge/config")]
     BridgeConfig(Value),
@@ -80,6 +88,9 @@ pub enum Message {
     #[serde(rename = "bridge/response/device/remove")]
     BridgeDeviceRemove(Response<DeviceRemoveResponse>),
 
+    #[serde(rename = "bridge/response/device/rename")]
+    BridgeDeviceRename(Response<DeviceRename>),
+
     #[serde(rename = "bridge/response/device/options")]
     BridgeDeviceOptions(Value),
 
@@ -87,7 +98,10 @@ pub enum Message {
     BridgeDeviceConfigureReporting(Value),
 
     #[serde(rename = "bridge/response/device/ota_update/check")]
-    BridgeDeviceOtaUpdateCheck(Value),
+    BridgeDeviceOtaUpdateCheck(Response<DeviceOtaCheckResponse>),
+
+    #[serde(rename = "bridge/response/device/ota_update/update")]
+    BridgeDeviceOtaUpdate(Response<DeviceOtaUpdateResponse>),
 
     #[serde(rename = "bridge/health")]
     BridgeHealth(BridgeHealth),
@@ -119,11 +133,98 @@ pub struct PermitJoin {
     pub device: Option<String>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PermitJoinResponse {
+    #[serde(default)]
+    pub time: Option<u32>,
+    #[serde(default)]
+    pub device: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TouchlinkDevice {
+    pub ieee_address: String,
+    pub channel: u8,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TouchlinkScan {
+    pub found: Vec<TouchlinkDevice>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkMapRequest {
+    #[serde(rename = "type")]
+    pub map_type: String,
+    pub routes: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkMap {
+    #[serde(rename = "type")]
+    pub map_type: String,
+    pub routes: bool,
+    /// The map, in the requested format. Only the "raw" format is structured,
+    /// see [`NetworkMapRaw`].
+    pub value: Value,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NetworkMapRaw {
+    pub nodes: Vec<NetworkMapNode>,
+    pub links: Vec<NetworkMapLink>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkMapNode {
+    pub ieee_addr: String,
+    pub friendly_name: String,
+    #[serde(rename = "type")]
+    pub device_type: String,
+}
+
+/// Neighbor table entry of `target`, describing its link to `source`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkMapLink {
+    pub source: NetworkMapEndpoint,
+    pub target: NetworkMapEndpoint,
+    pub lqi: u8,
+    /// Depth of `source` in the network tree (0 for the coordinator)
+    pub depth: u8,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkMapEndpoint {
+    pub ieee_addr: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DeviceRemove {
     pub id: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeviceOtaUpdate {
+    pub id: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeviceOtaCheckResponse {
+    pub id: String,
+    pub update_available: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeviceOtaUpdateResponse {
+    pub id: String,
+    #[serde(default)]
+    pub from: Option<Value>,
+    #[serde(default)]
+    pub to: Option<Value>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DeviceRemoveResponse {
     pub id: String,
@@ -290,6 +391,13 @@ pub struct BridgeInfo {
     pub log_level: String,
     pub network: Network,
     pub permit_join: bool,
+    /// End of the current permit join period, in milliseconds since the
+    /// epoch (z2m 2.x)
+    #[serde(default)]
+    pub permit_join_end: Option<i64>,
+    /// Seconds left of the current permit join period (z2m 1.x)
+    #[serde(default)]
+    pub permit_join_timeout: Option<u32>,
     pub restart_required: bool,
     pub version: String,
     pub zigbee_herdsman: Version,
@@ -482,6 +590,21 @@ impl Device {
         self.definition.as_ref().map_or(&[], |def| &def.exposes)
     }
 
+    /// Look up this device in the device database, by model id and vendor
+    #[must_use]
+    pub fn product_data(&self) -> Option<SimpleProductData> {
+        let model_id = self.model_id.as_deref()?;
+        let vendors: Vec<&str> = [
+            self.manufacturer.as_deref(),
+            self.definition.as_ref().map(|def| def.vendor.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        vendor_product_data(&vendors, model_id)
+    }
+
     #[must_use]
     pub fn expose_light(&self) -> Option<&ExposeLight> {
         self.exposes().iter().find_map(|exp| {
@@ -513,6 +636,14 @@ impl Device {
         })
     }
 
+    #[must_use]
+    pub fn expose_effect(&self) -> Option<&Expose> {
+        self.exposes().iter().find(|exp| {
+            matches!(exp, Expose::Enum(ExposeEnum { base, .. })
+                if base.property.as_deref() == Some("effect"))
+        })
+    }
+
     #[must_use]
     pub fn expose_action(&self) -> bool {
         self.exposes().iter().any(|exp| {
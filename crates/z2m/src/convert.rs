@@ -1,15 +1,22 @@
 use std::collections::BTreeSet;
 
+use serde::Deserialize;
+
 use hue::api::{
-    ColorGamut, ColorTemperature, DeviceProductData, Dimming, GamutType, GroupedLightUpdate,
-    LightColor, LightGradient, LightGradientMode, LightGradientPoint, LightGradientUpdate,
-    LightUpdate, MirekSchema,
+    ColorGamut, ColorTemperature, ColorTemperatureUpdate, DeviceProductData,
+    DeviceSoftwareUpdateState, Dimming, DimmingUpdate, GamutType, GroupedLightUpdate, LightColor,
+    LightEffect, LightEffects, LightEffectsV2, LightGradient, LightGradientMode,
+    LightGradientPoint, LightGradientUpdate, LightPowerupColor, LightPowerupDimming,
+    LightPowerupOn, LightPowerupUpdate, LightUpdate, MirekSchema, On,
 };
-use hue::devicedb::{hardware_platform_type, product_archetype};
+use hue::devicedb::gamut_type;
 use hue::xy::XY;
 
 use crate::api::{Device, Expose, ExposeList, ExposeNumeric};
-use crate::update::{DeviceColorMode, DeviceUpdate};
+use crate::update::{
+    ColorTempStartup, CurrentLevelStartup, DeviceColorMode, DeviceEffect, DeviceUpdate, OtaState,
+    PowerOnBehavior,
+};
 
 pub trait ExtractExposeNumeric {
     fn extract_mirek_schema(&self) -> Option<MirekSchema>;
@@ -36,6 +43,11 @@ pub trait ExtractLightColor {
     fn extract_from_expose(expose: &Expose) -> Option<Self>
     where
         Self: Sized;
+
+    #[must_use]
+    fn extract_from_device(dev: &Device, expose: &Expose) -> Option<Self>
+    where
+        Self: Sized;
 }
 
 impl ExtractLightColor for LightColor {
@@ -50,6 +62,32 @@ impl ExtractLightColor for LightColor {
             xy: XY::D65_WHITE_POINT,
         })
     }
+
+    fn extract_from_device(dev: &Device, expose: &Expose) -> Option<Self> {
+        let color = Self::extract_from_expose(expose)?;
+
+        // Known models have a known gamut. For everything else, make a
+        // best-effort guess based on the manufacturer, falling back to the
+        // (wide) gamut C, which is what we have always reported.
+        let known_gamut = dev
+            .product_data()
+            .and_then(|pd| pd.gamut_type)
+            .or_else(|| dev.model_id.as_deref().and_then(gamut_type));
+
+        if let Some(gamut_type) = known_gamut {
+            return Some(color.with_gamut(gamut_type, gamut_type.gamut()));
+        }
+
+        if dev
+            .manufacturer
+            .as_deref()
+            .is_some_and(|mf| mf.starts_with("IKEA"))
+        {
+            return Some(color.with_gamut(GamutType::Other, Some(ColorGamut::IKEA_ESTIMATE)));
+        }
+
+        Some(color)
+    }
 }
 
 pub trait ExtractLightGradient {
@@ -115,17 +153,110 @@ pub trait ExtractDimming: Sized {
 impl ExtractDimming for Dimming {
     #[must_use]
     fn extract_from_expose(expose: &Expose) -> Option<Self> {
-        let Expose::Numeric(_) = expose else {
+        const DEFAULT_MIN_DIM_LEVEL: f64 = 0.01;
+
+        let Expose::Numeric(num) = expose else {
             return None;
         };
 
+        // most devices expose the generic zigbee range (0/1 to 254), which
+        // says nothing about how low they can actually go. Only a raised
+        // minimum is a device-specific value worth reporting.
+        let max = num.value_max.filter(|max| *max > 0.0).unwrap_or(254.0);
+        let min_dim_level = num
+            .value_min
+            .filter(|min| *min > 1.0)
+            .map_or(DEFAULT_MIN_DIM_LEVEL, |min| {
+                (min / max * 100.0).clamp(DEFAULT_MIN_DIM_LEVEL, 100.0)
+            });
+
         Some(Self {
-            brightness: 0.01,
-            min_dim_level: Some(0.01),
+            brightness: DEFAULT_MIN_DIM_LEVEL,
+            min_dim_level: Some(min_dim_level),
         })
     }
 }
 
+impl From<LightEffect> for DeviceEffect {
+    fn from(value: LightEffect) -> Self {
+        match value {
+            LightEffect::NoEffect => Self::StopEffect,
+            LightEffect::Prism => Self::Colorloop,
+            LightEffect::Opal => Self::Opal,
+            LightEffect::Glisten => Self::Glisten,
+            LightEffect::Sparkle => Self::Sparkle,
+            LightEffect::Fire => Self::Fireplace,
+            LightEffect::Candle => Self::Candle,
+            LightEffect::Underwater => Self::Underwater,
+            LightEffect::Cosmos => Self::Cosmos,
+            LightEffect::Sunbeam => Self::Sunbeam,
+            LightEffect::Enchant => Self::Enchant,
+        }
+    }
+}
+
+const fn light_effect(effect: DeviceEffect) -> Option<LightEffect> {
+    match effect {
+        DeviceEffect::Colorloop => Some(LightEffect::Prism),
+        DeviceEffect::Opal => Some(LightEffect::Opal),
+        DeviceEffect::Glisten => Some(LightEffect::Glisten),
+        DeviceEffect::Sparkle => Some(LightEffect::Sparkle),
+        DeviceEffect::Fireplace => Some(LightEffect::Fire),
+        DeviceEffect::Candle => Some(LightEffect::Candle),
+        DeviceEffect::Underwater => Some(LightEffect::Underwater),
+        DeviceEffect::Cosmos => Some(LightEffect::Cosmos),
+        DeviceEffect::Sunbeam => Some(LightEffect::Sunbeam),
+        DeviceEffect::Enchant => Some(LightEffect::Enchant),
+        DeviceEffect::Blink
+        | DeviceEffect::Breathe
+        | DeviceEffect::Okay
+        | DeviceEffect::ChannelChange
+        | DeviceEffect::FinishEffect
+        | DeviceEffect::StopEffect => None,
+    }
+}
+
+/// The hue effects listed in an `effect` expose, if there are any
+fn extract_effect_values(expose: &Expose) -> Option<Vec<LightEffect>> {
+    let Expose::Enum(num) = expose else {
+        return None;
+    };
+
+    let effects: Vec<LightEffect> = num
+        .values
+        .iter()
+        .filter_map(|value| DeviceEffect::deserialize(value).ok())
+        .filter_map(light_effect)
+        .collect();
+
+    if effects.is_empty() {
+        return None;
+    }
+
+    Some(
+        std::iter::once(LightEffect::NoEffect)
+            .chain(effects)
+            .collect(),
+    )
+}
+
+pub trait ExtractLightEffects: Sized {
+    #[must_use]
+    fn extract_from_expose(expose: &Expose) -> Option<Self>;
+}
+
+impl ExtractLightEffects for LightEffects {
+    fn extract_from_expose(expose: &Expose) -> Option<Self> {
+        extract_effect_values(expose).map(Self::new)
+    }
+}
+
+impl ExtractLightEffects for LightEffectsV2 {
+    fn extract_from_expose(expose: &Expose) -> Option<Self> {
+        extract_effect_values(expose).map(Self::new)
+    }
+}
+
 pub trait ExtractDeviceProductData {
     #[must_use]
     fn guess_from_device(dev: &Device) -> Self;
@@ -138,14 +269,26 @@ impl ExtractDeviceProductData for DeviceProductData {
             name.map_or("<unknown>", |v| v).to_string()
         }
 
-        let product_name = str_or_unknown(dev.definition.as_ref().map(|def| &def.model));
         let model_id = str_or_unknown(dev.model_id.as_ref());
-        let manufacturer_name = str_or_unknown(dev.manufacturer.as_ref());
-        let certified = manufacturer_name == Self::SIGNIFY_MANUFACTURER_NAME;
         let software_version = str_or_unknown(dev.software_build_id.as_ref());
 
-        let product_archetype = product_archetype(&model_id).unwrap_or_default();
-        let hardware_platform_type = hardware_platform_type(&model_id).map(ToString::to_string);
+        // prefer names from the device database, so known devices look like
+        // they would on a real bridge
+        let known = dev.product_data();
+        let (manufacturer_name, product_name) = known.as_ref().map_or_else(
+            || {
+                (
+                    str_or_unknown(dev.manufacturer.as_ref()),
+                    str_or_unknown(dev.definition.as_ref().map(|def| &def.model)),
+                )
+            },
+            |pd| (pd.manufacturer_name.clone(), pd.product_name.clone()),
+        );
+        let certified = manufacturer_name == Self::SIGNIFY_MANUFACTURER_NAME;
+
+        let (product_archetype, hardware_platform_type) = known
+            .map(|pd| (pd.product_archetype, pd.hardware_platform_type))
+            .unwrap_or_default();
 
         Self {
             model_id,
@@ -159,6 +302,46 @@ impl ExtractDeviceProductData for DeviceProductData {
     }
 }
 
+/// Extract the power-on behavior (startup settings) reported by z2m
+fn extract_powerup(value: &DeviceUpdate) -> Option<LightPowerupUpdate> {
+    let on = match value.power_on_behavior {
+        Some(PowerOnBehavior::On) => LightPowerupOn::On { on: On::new(true) },
+        Some(PowerOnBehavior::Off) => LightPowerupOn::On { on: On::new(false) },
+        Some(PowerOnBehavior::Previous) => LightPowerupOn::Previous,
+        Some(PowerOnBehavior::Toggle | PowerOnBehavior::Unknown) | None => LightPowerupOn::None,
+    };
+
+    let dimming = match value.level_config.and_then(|lc| lc.current_level_startup) {
+        Some(CurrentLevelStartup::Previous) => LightPowerupDimming::Previous,
+        Some(CurrentLevelStartup::Minimum) => LightPowerupDimming::Dimming {
+            dimming: DimmingUpdate::new(1.0 / 254.0 * 100.0),
+        },
+        Some(CurrentLevelStartup::Value(level)) => LightPowerupDimming::Dimming {
+            dimming: DimmingUpdate::new(f64::from(level) / 254.0 * 100.0),
+        },
+        None => LightPowerupDimming::None,
+    };
+
+    let color = match value.color_temp_startup {
+        Some(
+            ColorTempStartup::Value(ColorTempStartup::PREVIOUS_RAW) | ColorTempStartup::Previous,
+        ) => LightPowerupColor::Previous,
+        Some(ColorTempStartup::Value(mirek)) => LightPowerupColor::ColorTemperature {
+            color_temperature: ColorTemperatureUpdate::new(mirek),
+        },
+        None => LightPowerupColor::None,
+    };
+
+    let upd = LightPowerupUpdate {
+        preset: None,
+        on,
+        dimming,
+        color,
+    };
+
+    (!upd.is_empty()).then_some(upd)
+}
+
 impl From<&DeviceUpdate> for LightUpdate {
     fn from(value: &DeviceUpdate) -> Self {
         let mut upd = Self::new()
@@ -173,7 +356,8 @@ impl From<&DeviceUpdate> for LightUpdate {
                         .map(|hc| LightGradientPoint::xy(hc.to_xy_color()))
                         .collect(),
                 }
-            }));
+            }))
+            .with_powerup(extract_powerup(value));
 
         if value.color_mode != Some(DeviceColorMode::ColorTemp) {
             upd = upd.with_color_xy(value.color.and_then(|col| col.xy));
@@ -183,6 +367,17 @@ impl From<&DeviceUpdate> for LightUpdate {
     }
 }
 
+impl From<OtaState> for DeviceSoftwareUpdateState {
+    fn from(value: OtaState) -> Self {
+        match value {
+            OtaState::Idle | OtaState::Unknown => Self::NoUpdate,
+            OtaState::Available => Self::ReadyToInstall,
+            OtaState::Scheduled => Self::UpdatePending,
+            OtaState::Updating => Self::Installing,
+        }
+    }
+}
+
 impl From<&GroupedLightUpdate> for DeviceUpdate {
     fn from(upd: &GroupedLightUpdate) -> Self {
         Self::default()
@@ -197,3 +392,372 @@ impl From<&GroupedLightUpdate> for DeviceUpdate {
             )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hue::api::{
+        ColorTemperature, ColorTemperatureUpdate, ColorUpdate, DeviceSoftwareUpdateState, Dimming,
+        DimmingUpdate, LightEffect, LightEffects, LightPowerup, LightPowerupColor,
+        LightPowerupDimming, LightPowerupOn, LightPowerupPreset, MirekSchema, On,
+    };
+    use hue::xy::XY;
+    use serde_json::{from_value, json, to_value};
+
+    use crate::api::Expose;
+    use crate::convert::{
+        ExtractColorTemperature, ExtractDimming, ExtractLightEffects, extract_powerup,
+    };
+    use crate::error::Z2mResult;
+    use crate::update::{CurrentLevelStartup, DeviceUpdate, OtaState, OtaStatus};
+
+    /// Send powerup settings to z2m and back, through the json wire format
+    fn powerup_roundtrip(powerup: &LightPowerup) -> Z2mResult<LightPowerup> {
+        let wire = to_value(DeviceUpdate::new().with_powerup(powerup))?;
+        let upd = extract_powerup(&from_value(wire)?).unwrap();
+
+        let mut res = LightPowerup {
+            preset: LightPowerupPreset::Custom,
+            configured: false,
+            on: LightPowerupOn::None,
+            dimming: LightPowerupDimming::None,
+            color: LightPowerupColor::None,
+        };
+        res += &upd;
+        Ok(res)
+    }
+
+    #[test]
+    fn effects_from_expose() -> Z2mResult<()> {
+        let expose: Expose = from_value(json!({
+            "type": "enum",
+            "name": "effect",
+            "property": "effect",
+            "access": 2,
+            "values": ["blink", "breathe", "okay", "candle", "fireplace", "colorloop", "finish_effect"],
+        }))?;
+
+        let effects = LightEffects::extract_from_expose(&expose).unwrap();
+        assert_eq!(
+            effects.effect_values,
+            [
+                LightEffect::NoEffect,
+                LightEffect::Candle,
+                LightEffect::Fire,
+                LightEffect::Prism
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn effects_from_expose_none() -> Z2mResult<()> {
+        let expose: Expose = from_value(json!({
+            "type": "enum",
+            "name": "effect",
+            "property": "effect",
+            "access": 2,
+            "values": ["blink", "breathe", "okay", "channel_change", "finish_effect", "stop_effect"],
+        }))?;
+
+        assert!(LightEffects::extract_from_expose(&expose).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn dimming_from_expose() -> Z2mResult<()> {
+        let expose: Expose = from_value(json!({
+            "type": "numeric",
+            "name": "brightness",
+            "property": "brightness",
+            "access": 7,
+            "value_min": 3,
+            "value_max": 254,
+        }))?;
+
+        let dimming = Dimming::extract_from_expose(&expose).unwrap();
+        let expected = 3.0 / 254.0 * 100.0;
+        assert!((dimming.min_dim_level.unwrap() - expected).abs() < 1e-9);
+        assert!((dimming.brightness - 0.01).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn dimming_from_generic_expose() -> Z2mResult<()> {
+        let expose: Expose = from_value(json!({
+            "type": "numeric",
+            "name": "brightness",
+            "property": "brightness",
+            "access": 7,
+            "value_min": 0,
+            "value_max": 254,
+        }))?;
+
+        let dimming = Dimming::extract_from_expose(&expose).unwrap();
+        assert_eq!(dimming.min_dim_level, Some(0.01));
+        assert!((dimming.brightness - 0.01).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn mirek_schema_from_expose() -> Z2mResult<()> {
+        let expose: Expose = from_value(json!({
+            "type": "numeric",
+            "name": "color_temp",
+            "property": "color_temp",
+            "access": 7,
+            "unit": "mired",
+            "value_min": 250,
+            "value_max": 454,
+        }))?;
+
+        let ct = ColorTemperature::extract_from_expose(&expose).unwrap();
+        assert!(ct.mirek_valid);
+        assert_eq!(
+            ct.mirek_schema,
+            MirekSchema {
+                mirek_minimum: 250,
+                mirek_maximum: 454
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn powerup_presets_roundtrip() -> Z2mResult<()> {
+        for preset in [
+            LightPowerupPreset::Safety,
+            LightPowerupPreset::Powerfail,
+            LightPowerupPreset::LastOnState,
+        ] {
+            let powerup = LightPowerup::new(preset);
+            assert_eq!(powerup_roundtrip(&powerup)?, powerup);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn powerup_safety_to_z2m() -> Z2mResult<()> {
+        let upd = DeviceUpdate::new().with_powerup(&LightPowerup::new(LightPowerupPreset::Safety));
+
+        assert_eq!(
+            to_value(upd)?,
+            json!({
+                "power_on_behavior": "on",
+                "level_config": {"current_level_startup": 254},
+                "color_temp_startup": LightPowerup::SAFETY_MIREK,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn powerup_powerfail_to_z2m() -> Z2mResult<()> {
+        let upd =
+            DeviceUpdate::new().with_powerup(&LightPowerup::new(LightPowerupPreset::Powerfail));
+
+        assert_eq!(
+            to_value(upd)?,
+            json!({
+                "power_on_behavior": "previous",
+                "level_config": {"current_level_startup": "previous"},
+                "color_temp_startup": "previous",
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn powerup_custom_roundtrip() -> Z2mResult<()> {
+        let mut powerup = LightPowerup::new(LightPowerupPreset::Custom);
+        powerup.on = LightPowerupOn::On { on: On::new(false) };
+        powerup.dimming = LightPowerupDimming::Dimming {
+            dimming: DimmingUpdate::new(50.0),
+        };
+        powerup.color = LightPowerupColor::ColorTemperature {
+            color_temperature: ColorTemperatureUpdate::new(250),
+        };
+
+        assert_eq!(powerup_roundtrip(&powerup)?, powerup);
+
+        Ok(())
+    }
+
+    #[test]
+    fn powerup_xy_color_keeps_previous() -> Z2mResult<()> {
+        let mut powerup = LightPowerup::new(LightPowerupPreset::Custom);
+        powerup.color = LightPowerupColor::Color {
+            color: ColorUpdate::new(XY::new(0.3, 0.3)),
+        };
+
+        let upd = to_value(DeviceUpdate::new().with_powerup(&powerup))?;
+        assert_eq!(upd["color_temp_startup"], json!("previous"));
+        assert_eq!(upd["color_options"], json!({"execute_if_off": true}));
+
+        assert_eq!(
+            powerup_roundtrip(&powerup)?.color,
+            LightPowerupColor::Previous
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn powerup_brightness_to_level() {
+        let level = |brightness| {
+            let mut powerup = LightPowerup::new(LightPowerupPreset::Custom);
+            powerup.dimming = LightPowerupDimming::Dimming {
+                dimming: DimmingUpdate::new(brightness),
+            };
+            let upd = DeviceUpdate::new().with_powerup(&powerup);
+            match upd.level_config.unwrap().current_level_startup {
+                Some(CurrentLevelStartup::Value(level)) => level,
+                other => panic!("unexpected level startup {other:?}"),
+            }
+        };
+
+        assert_eq!(level(100.0), 254);
+        assert_eq!(level(50.0), 127);
+        // zigbee levels start at 1, and never exceed 254
+        assert_eq!(level(0.0), 1);
+        assert_eq!(level(150.0), 254);
+    }
+
+    #[test]
+    fn powerup_level_to_brightness() -> Z2mResult<()> {
+        let brightness = |startup| -> Z2mResult<LightPowerupDimming> {
+            let upd: DeviceUpdate =
+                from_value(json!({"level_config": {"current_level_startup": startup}}))?;
+            Ok(extract_powerup(&upd).unwrap().dimming)
+        };
+
+        let dimming = |brightness| LightPowerupDimming::Dimming {
+            dimming: DimmingUpdate::new(brightness),
+        };
+
+        assert_eq!(brightness(json!(254))?, dimming(100.0));
+        assert_eq!(brightness(json!(127))?, dimming(50.0));
+        assert_eq!(brightness(json!("minimum"))?, dimming(1.0 / 254.0 * 100.0));
+        assert_eq!(
+            brightness(json!("previous"))?,
+            LightPowerupDimming::Previous
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn powerup_color_temp_startup() -> Z2mResult<()> {
+        let color = |startup| -> Z2mResult<LightPowerupColor> {
+            let upd: DeviceUpdate = from_value(json!({"color_temp_startup": startup}))?;
+            Ok(extract_powerup(&upd).unwrap().color)
+        };
+
+        // z2m reports "previous" either by name, or as the raw zcl value
+        assert_eq!(color(json!("previous"))?, LightPowerupColor::Previous);
+        assert_eq!(color(json!(0xFFFF))?, LightPowerupColor::Previous);
+        assert_eq!(
+            color(json!(366))?,
+            LightPowerupColor::ColorTemperature {
+                color_temperature: ColorTemperatureUpdate::new(366),
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn powerup_empty() -> Z2mResult<()> {
+        let upd: DeviceUpdate = from_value(json!({"state": "ON", "brightness": 100}))?;
+        assert!(extract_powerup(&upd).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn ota_status_parse() -> Z2mResult<()> {
+        let upd: DeviceUpdate = from_value(json!({
+            "update": {
+                "state": "updating",
+                "installed_version": 0x0100_1C05_u32,
+                "latest_version": 0x0100_1D02_u32,
+                "progress": 42.5,
+                "remaining": 1200,
+            },
+        }))?;
+
+        let ota = upd.update.unwrap();
+        assert_eq!(ota.state, Some(OtaState::Updating));
+        assert_eq!(ota.installed_version, Some(0x0100_1C05));
+        assert_eq!(ota.latest_version, Some(0x0100_1D02));
+        assert_eq!(ota.progress, Some(42.5));
+        assert_eq!(ota.remaining, Some(1200.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn ota_status_parse_partial() -> Z2mResult<()> {
+        let ota: OtaStatus = from_value(json!({"state": "idle"}))?;
+
+        assert_eq!(ota.state, Some(OtaState::Idle));
+        assert!(ota.installed_version.is_none());
+        assert!(ota.software_version().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn ota_state_parse() -> Z2mResult<()> {
+        for (name, state) in [
+            ("idle", OtaState::Idle),
+            ("available", OtaState::Available),
+            ("scheduled", OtaState::Scheduled),
+            ("updating", OtaState::Updating),
+            // states added by newer z2m versions are not an error
+            ("downgrading", OtaState::Unknown),
+        ] {
+            assert_eq!(from_value::<OtaState>(json!(name))?, state);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn ota_state_to_hue() {
+        for (state, expected) in [
+            (OtaState::Idle, DeviceSoftwareUpdateState::NoUpdate),
+            (OtaState::Unknown, DeviceSoftwareUpdateState::NoUpdate),
+            (
+                OtaState::Available,
+                DeviceSoftwareUpdateState::ReadyToInstall,
+            ),
+            (
+                OtaState::Scheduled,
+                DeviceSoftwareUpdateState::UpdatePending,
+            ),
+            (OtaState::Updating, DeviceSoftwareUpdateState::Installing),
+        ] {
+            assert_eq!(DeviceSoftwareUpdateState::from(state), expected);
+        }
+    }
+
+    #[test]
+    fn ota_software_version() -> Z2mResult<()> {
+        let ota: OtaStatus = from_value(json!({"installed_version": 0x0102_1C05_u32}))?;
+        assert_eq!(ota.software_version().as_deref(), Some("1.2.28.5"));
+
+        // not a valid ota file version
+        let ota: OtaStatus = from_value(json!({"installed_version": 1_u64 << 32}))?;
+        assert!(ota.software_version().is_none());
+
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
 use serde::Serialize;
 use serde_json::Value;
 
-use crate::api::{DeviceRemove, GroupMemberChange, PermitJoin};
+use crate::api::{
+    DeviceOtaUpdate, DeviceRemove, DeviceRename, GroupMemberChange, NetworkMapRequest, PermitJoin,
+    TouchlinkDevice,
+};
 use crate::update::DeviceUpdate;
 
 #[derive(Clone, Debug, Serialize)]
@@ -33,6 +36,8 @@ pub enum Z2mRequest<'a> {
         payload: Z2mPayload,
     },
 
+    TouchlinkScan,
+
     #[serde(untagged)]
     GroupMemberAdd(GroupMemberChange),
 
@@ -45,6 +50,24 @@ pub enum Z2mRequest<'a> {
     #[serde(untagged)]
     DeviceRemove(DeviceRemove),
 
+    #[serde(untagged)]
+    DeviceRename(DeviceRename),
+
+    #[serde(untagged)]
+    DeviceOtaUpdate(DeviceOtaUpdate),
+
+    #[serde(untagged)]
+    DeviceOtaCheck(DeviceOtaUpdate),
+
+    #[serde(untagged)]
+    NetworkMap(NetworkMapRequest),
+
+    #[serde(untagged)]
+    TouchlinkIdentify(TouchlinkDevice),
+
+    #[serde(untagged)]
+    TouchlinkFactoryReset(TouchlinkDevice),
+
     #[serde(untagged)]
     Update(&'a DeviceUpdate),
 
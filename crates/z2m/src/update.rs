@@ -3,7 +3,9 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use hue::api::{LightGradientUpdate, On};
+use hue::api::{
+    LightGradientUpdate, LightPowerup, LightPowerupColor, LightPowerupDimming, LightPowerupOn, On,
+};
 use hue::xy::XY;
 
 use crate::hexcolor::HexColor;
@@ -28,7 +30,7 @@ pub struct DeviceUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub color_options: Option<ColorOptions>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub color_temp_startup: Option<f64>,
+    pub color_temp_startup: Option<ColorTempStartup>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub level_config: Option<LevelConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -37,9 +39,9 @@ pub struct DeviceUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub power_on_behavior: Option<PowerOnBehavior>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
-    pub update: HashMap<String, Value>,
+    pub update: Option<OtaStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub update_available: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -127,6 +129,59 @@ impl DeviceUpdate {
     pub fn with_transition(self, transition: Option<f64>) -> Self {
         Self { transition, ..self }
     }
+
+    /// Set the power-on behavior (startup settings) of a light
+    ///
+    /// Zigbee has no startup attribute for xy colors, so a powerup xy color
+    /// is mapped to "previous", with `execute_if_off` enabled, so the light
+    /// keeps the last color set, even while off.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn with_powerup(self, powerup: &LightPowerup) -> Self {
+        let power_on_behavior = match powerup.on {
+            LightPowerupOn::None => None,
+            LightPowerupOn::Previous => Some(PowerOnBehavior::Previous),
+            LightPowerupOn::On {
+                on: On { on: true },
+            } => Some(PowerOnBehavior::On),
+            LightPowerupOn::On {
+                on: On { on: false },
+            } => Some(PowerOnBehavior::Off),
+        };
+
+        let current_level_startup = match powerup.dimming {
+            LightPowerupDimming::None => None,
+            LightPowerupDimming::Previous => Some(CurrentLevelStartup::Previous),
+            LightPowerupDimming::Dimming { dimming } => Some(CurrentLevelStartup::Value(
+                (dimming.brightness / 100.0 * 254.0).clamp(1.0, 254.0) as u8,
+            )),
+        };
+
+        let (color_temp_startup, color_options) = match &powerup.color {
+            LightPowerupColor::None => (None, None),
+            LightPowerupColor::Previous => (Some(ColorTempStartup::Previous), None),
+            LightPowerupColor::ColorTemperature { color_temperature } => {
+                (color_temperature.mirek.map(ColorTempStartup::Value), None)
+            }
+            LightPowerupColor::Color { .. } => (
+                Some(ColorTempStartup::Previous),
+                Some(ColorOptions {
+                    execute_if_off: true,
+                }),
+            ),
+        };
+
+        Self {
+            power_on_behavior,
+            level_config: current_level_startup.map(|startup| LevelConfig {
+                current_level_startup: Some(startup),
+                ..LevelConfig::default()
+            }),
+            color_temp_startup,
+            color_options,
+            ..self
+        }
+    }
 }
 
 #[derive(Copy, Debug, Serialize, Deserialize, Clone)]
@@ -184,6 +239,9 @@ pub enum PowerOnBehavior {
 
     #[serde(rename = "previous")]
     Previous,
+
+    #[serde(rename = "toggle")]
+    Toggle,
 }
 
 #[derive(Copy, Debug, Serialize, Deserialize, Clone)]
@@ -192,14 +250,20 @@ pub struct ColorOptions {
     pub execute_if_off: bool,
 }
 
-#[derive(Copy, Debug, Serialize, Deserialize, Clone)]
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(deny_unknown_fields)]
 pub struct LevelConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub execute_if_off: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub on_off_transition_time: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub on_transition_time: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub off_transition_time: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub current_level_startup: Option<CurrentLevelStartup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub on_level: Option<OnLevel>,
 }
 
@@ -212,6 +276,62 @@ pub enum CurrentLevelStartup {
     Value(u8),
 }
 
+/// Firmware (OTA) update status, as reported by z2m
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OtaStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<OtaState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed_version: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_version: Option<u64>,
+    /// Install progress, in percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f64>,
+    /// Estimated time remaining, in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<f64>,
+}
+
+impl OtaStatus {
+    /// The installed firmware version, as a dotted version string.
+    ///
+    /// Zigbee ota file versions hold the application release and build,
+    /// followed by the stack release and build, one byte each.
+    #[must_use]
+    pub fn software_version(&self) -> Option<String> {
+        let version = u32::try_from(self.installed_version?).ok()?;
+        let [app_release, app_build, stack_release, stack_build] = version.to_be_bytes();
+        Some(format!(
+            "{app_release}.{app_build}.{stack_release}.{stack_build}"
+        ))
+    }
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OtaState {
+    Idle,
+    Available,
+    Scheduled,
+    Updating,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Copy, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorTempStartup {
+    Previous,
+    #[serde(untagged)]
+    Value(u16),
+}
+
+impl ColorTempStartup {
+    /// Raw zcl value for "previous", which z2m might report as-is
+    pub const PREVIOUS_RAW: u16 = 0xFFFF;
+}
+
 #[derive(Copy, Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum OnLevel {
@@ -254,4 +374,16 @@ pub enum DeviceEffect {
     ChannelChange,
     FinishEffect,
     StopEffect,
+
+    /* dynamic effects, supported by some (mostly hue) lights */
+    Candle,
+    Fireplace,
+    Colorloop,
+    Sparkle,
+    Opal,
+    Glisten,
+    Underwater,
+    Cosmos,
+    Sunbeam,
+    Enchant,
 }
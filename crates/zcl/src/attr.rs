@@ -3,6 +3,7 @@ use std::{fmt::Debug, io::Cursor};
 
 use byteorder::{LE, ReadBytesExt};
 use packed_struct::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::error::{ZclError, ZclResult};
 
@@ -41,7 +42,7 @@ pub enum ZclCommand {
     DiscoverAttrExtRes = 0x16,
 }
 
-#[derive(PrimitiveEnum_u8, Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(PrimitiveEnum_u8, Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ZclDataType {
     /** Null data type */
     Null = 0x00,
@@ -130,7 +131,7 @@ impl ZclReadAttr {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ZclAttrValue {
     Null,
     X8(i8),
@@ -156,6 +157,37 @@ pub enum ZclAttrValue {
     Unsupported,
 }
 
+impl ZclAttrValue {
+    /// Zcl data type used to encode this value
+    #[must_use]
+    pub const fn data_type(&self) -> ZclDataType {
+        match self {
+            Self::Null => ZclDataType::Null,
+            Self::X8(_) => ZclDataType::Zcl8bit,
+            Self::X16(_) => ZclDataType::Zcl16bit,
+            Self::X32(_) => ZclDataType::Zcl32bit,
+            Self::Bool(_) => ZclDataType::ZclBool,
+            Self::B8(_) => ZclDataType::Zcl8bitmap,
+            Self::B16(_) => ZclDataType::Zcl16bitmap,
+            Self::B32(_) => ZclDataType::Zcl32bitmap,
+            Self::B40(_) => ZclDataType::Zcl40bitmap,
+            Self::B48(_) => ZclDataType::Zcl48bitmap,
+            Self::B56(_) => ZclDataType::Zcl56bitmap,
+            Self::B64(_) => ZclDataType::Zcl64bitmap,
+            Self::U8(_) => ZclDataType::ZclU8,
+            Self::U16(_) => ZclDataType::ZclU16,
+            Self::U32(_) => ZclDataType::ZclU32,
+            Self::I16(_) => ZclDataType::ZclI16,
+            Self::E8(_) => ZclDataType::ZclE8,
+            Self::Bytes(_) => ZclDataType::ZclBytearray,
+            Self::String(_) => ZclDataType::ZclCharstring,
+            Self::IeeeAddr(_) => ZclDataType::ZclIeeeaddr,
+            Self::SecurityKey(_) => ZclDataType::ZclSecurityKey,
+            Self::Unsupported => ZclDataType::ZclInvalid,
+        }
+    }
+}
+
 impl Debug for ZclAttrValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
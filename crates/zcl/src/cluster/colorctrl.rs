@@ -1,3 +1,10 @@
+use std::io::Cursor;
+
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+use packed_struct::prelude::*;
+
+use crate::command::{ZclClusterCommand, check_frame, read_enum, unsupported};
+use crate::error::ZclResult;
 use crate::frame::{ZclFrame, ZclFrameDirection};
 
 #[must_use]
@@ -33,3 +40,536 @@ pub fn describe(frame: &ZclFrame, _data: &[u8]) -> Option<String> {
         _ => None,
     }
 }
+
+/// Direction of travel around the color wheel, for hue movements
+#[derive(PrimitiveEnum_u8, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HueDirection {
+    Shortest = 0x00,
+    Longest = 0x01,
+    Up = 0x02,
+    Down = 0x03,
+}
+
+/// Mode for move and step commands
+#[derive(PrimitiveEnum_u8, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMoveMode {
+    Stop = 0x00,
+    Up = 0x01,
+    Down = 0x03,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorCtrlCommand {
+    MoveToHue {
+        hue: u8,
+        direction: HueDirection,
+        transition: u16,
+    },
+    MoveHue {
+        mode: ColorMoveMode,
+        rate: u8,
+    },
+    StepHue {
+        mode: ColorMoveMode,
+        size: u8,
+        transition: u8,
+    },
+    MoveToSaturation {
+        sat: u8,
+        transition: u16,
+    },
+    MoveSaturation {
+        mode: ColorMoveMode,
+        rate: u8,
+    },
+    StepSaturation {
+        mode: ColorMoveMode,
+        size: u8,
+        transition: u8,
+    },
+    MoveToHueAndSaturation {
+        hue: u8,
+        sat: u8,
+        transition: u16,
+    },
+    MoveToColor {
+        x: u16,
+        y: u16,
+        transition: u16,
+    },
+    MoveColor {
+        rate_x: i16,
+        rate_y: i16,
+    },
+    StepColor {
+        step_x: i16,
+        step_y: i16,
+        transition: u16,
+    },
+    MoveToColorTemp {
+        mireds: u16,
+        transition: u16,
+    },
+    EnhancedMoveToHue {
+        hue: u16,
+        direction: HueDirection,
+        transition: u16,
+    },
+    EnhancedMoveHue {
+        mode: ColorMoveMode,
+        rate: u16,
+    },
+    EnhancedStepHue {
+        mode: ColorMoveMode,
+        size: u16,
+        transition: u16,
+    },
+    EnhancedMoveToHueAndSaturation {
+        hue: u16,
+        sat: u8,
+        transition: u16,
+    },
+    ColorLoopSet {
+        update: u8,
+        action: u8,
+        direction: u8,
+        time: u16,
+        start_hue: u16,
+    },
+    StopMoveStep,
+    MoveColorTemp {
+        mode: ColorMoveMode,
+        rate: u16,
+        min: u16,
+        max: u16,
+    },
+    StepColorTemp {
+        mode: ColorMoveMode,
+        size: u16,
+        transition: u16,
+        min: u16,
+        max: u16,
+    },
+}
+
+impl ZclClusterCommand for ColorCtrlCommand {
+    const CLUSTER: u16 = 0x0300;
+
+    fn cmd(&self) -> u8 {
+        match self {
+            Self::MoveToHue { .. } => 0x00,
+            Self::MoveHue { .. } => 0x01,
+            Self::StepHue { .. } => 0x02,
+            Self::MoveToSaturation { .. } => 0x03,
+            Self::MoveSaturation { .. } => 0x04,
+            Self::StepSaturation { .. } => 0x05,
+            Self::MoveToHueAndSaturation { .. } => 0x06,
+            Self::MoveToColor { .. } => 0x07,
+            Self::MoveColor { .. } => 0x08,
+            Self::StepColor { .. } => 0x09,
+            Self::MoveToColorTemp { .. } => 0x0a,
+            Self::EnhancedMoveToHue { .. } => 0x40,
+            Self::EnhancedMoveHue { .. } => 0x41,
+            Self::EnhancedStepHue { .. } => 0x42,
+            Self::EnhancedMoveToHueAndSaturation { .. } => 0x43,
+            Self::ColorLoopSet { .. } => 0x44,
+            Self::StopMoveStep => 0x47,
+            Self::MoveColorTemp { .. } => 0x4b,
+            Self::StepColorTemp { .. } => 0x4c,
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn encode(&self) -> ZclResult<Vec<u8>> {
+        let mut res = vec![];
+
+        match self {
+            Self::MoveToHue {
+                hue,
+                direction,
+                transition,
+            } => {
+                res.write_u8(*hue)?;
+                res.write_u8(direction.to_primitive())?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::MoveHue { mode, rate } | Self::MoveSaturation { mode, rate } => {
+                res.write_u8(mode.to_primitive())?;
+                res.write_u8(*rate)?;
+            }
+            Self::StepHue {
+                mode,
+                size,
+                transition,
+            }
+            | Self::StepSaturation {
+                mode,
+                size,
+                transition,
+            } => {
+                res.write_u8(mode.to_primitive())?;
+                res.write_u8(*size)?;
+                res.write_u8(*transition)?;
+            }
+            Self::MoveToSaturation { sat, transition } => {
+                res.write_u8(*sat)?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::MoveToHueAndSaturation {
+                hue,
+                sat,
+                transition,
+            } => {
+                res.write_u8(*hue)?;
+                res.write_u8(*sat)?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::MoveToColor { x, y, transition } => {
+                res.write_u16::<LE>(*x)?;
+                res.write_u16::<LE>(*y)?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::MoveColor { rate_x, rate_y } => {
+                res.write_i16::<LE>(*rate_x)?;
+                res.write_i16::<LE>(*rate_y)?;
+            }
+            Self::StepColor {
+                step_x,
+                step_y,
+                transition,
+            } => {
+                res.write_i16::<LE>(*step_x)?;
+                res.write_i16::<LE>(*step_y)?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::MoveToColorTemp { mireds, transition } => {
+                res.write_u16::<LE>(*mireds)?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::EnhancedMoveToHue {
+                hue,
+                direction,
+                transition,
+            } => {
+                res.write_u16::<LE>(*hue)?;
+                res.write_u8(direction.to_primitive())?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::EnhancedMoveHue { mode, rate } => {
+                res.write_u8(mode.to_primitive())?;
+                res.write_u16::<LE>(*rate)?;
+            }
+            Self::EnhancedStepHue {
+                mode,
+                size,
+                transition,
+            } => {
+                res.write_u8(mode.to_primitive())?;
+                res.write_u16::<LE>(*size)?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::EnhancedMoveToHueAndSaturation {
+                hue,
+                sat,
+                transition,
+            } => {
+                res.write_u16::<LE>(*hue)?;
+                res.write_u8(*sat)?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::ColorLoopSet {
+                update,
+                action,
+                direction,
+                time,
+                start_hue,
+            } => {
+                res.write_u8(*update)?;
+                res.write_u8(*action)?;
+                res.write_u8(*direction)?;
+                res.write_u16::<LE>(*time)?;
+                res.write_u16::<LE>(*start_hue)?;
+            }
+            Self::StopMoveStep => {}
+            Self::MoveColorTemp {
+                mode,
+                rate,
+                min,
+                max,
+            } => {
+                res.write_u8(mode.to_primitive())?;
+                res.write_u16::<LE>(*rate)?;
+                res.write_u16::<LE>(*min)?;
+                res.write_u16::<LE>(*max)?;
+            }
+            Self::StepColorTemp {
+                mode,
+                size,
+                transition,
+                min,
+                max,
+            } => {
+                res.write_u8(mode.to_primitive())?;
+                res.write_u16::<LE>(*size)?;
+                res.write_u16::<LE>(*transition)?;
+                res.write_u16::<LE>(*min)?;
+                res.write_u16::<LE>(*max)?;
+            }
+        }
+
+        Ok(res)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn parse(frame: &ZclFrame, data: &[u8]) -> ZclResult<Self> {
+        check_frame::<Self>(frame, None)?;
+
+        if !frame.c2s() {
+            return Err(unsupported::<Self>(frame));
+        }
+
+        let mut rdr = Cursor::new(data);
+
+        let res = match frame.cmd {
+            0x00 => Self::MoveToHue {
+                hue: rdr.read_u8()?,
+                direction: read_enum(&mut rdr)?,
+                transition: rdr.read_u16::<LE>()?,
+            },
+            0x01 => Self::MoveHue {
+                mode: read_enum(&mut rdr)?,
+                rate: rdr.read_u8()?,
+            },
+            0x02 => Self::StepHue {
+                mode: read_enum(&mut rdr)?,
+                size: rdr.read_u8()?,
+                transition: rdr.read_u8()?,
+            },
+            0x03 => Self::MoveToSaturation {
+                sat: rdr.read_u8()?,
+                transition: rdr.read_u16::<LE>()?,
+            },
+            0x04 => Self::MoveSaturation {
+                mode: read_enum(&mut rdr)?,
+                rate: rdr.read_u8()?,
+            },
+            0x05 => Self::StepSaturation {
+                mode: read_enum(&mut rdr)?,
+                size: rdr.read_u8()?,
+                transition: rdr.read_u8()?,
+            },
+            0x06 => Self::MoveToHueAndSaturation {
+                hue: rdr.read_u8()?,
+                sat: rdr.read_u8()?,
+                transition: rdr.read_u16::<LE>()?,
+            },
+            0x07 => Self::MoveToColor {
+                x: rdr.read_u16::<LE>()?,
+                y: rdr.read_u16::<LE>()?,
+                transition: rdr.read_u16::<LE>()?,
+            },
+            0x08 => Self::MoveColor {
+                rate_x: rdr.read_i16::<LE>()?,
+                rate_y: rdr.read_i16::<LE>()?,
+            },
+            0x09 => Self::StepColor {
+                step_x: rdr.read_i16::<LE>()?,
+                step_y: rdr.read_i16::<LE>()?,
+                transition: rdr.read_u16::<LE>()?,
+            },
+            0x0a => Self::MoveToColorTemp {
+                mireds: rdr.read_u16::<LE>()?,
+                transition: rdr.read_u16::<LE>()?,
+            },
+            0x40 => Self::EnhancedMoveToHue {
+                hue: rdr.read_u16::<LE>()?,
+                direction: read_enum(&mut rdr)?,
+                transition: rdr.read_u16::<LE>()?,
+            },
+            0x41 => Self::EnhancedMoveHue {
+                mode: read_enum(&mut rdr)?,
+                rate: rdr.read_u16::<LE>()?,
+            },
+            0x42 => Self::EnhancedStepHue {
+                mode: read_enum(&mut rdr)?,
+                size: rdr.read_u16::<LE>()?,
+                transition: rdr.read_u16::<LE>()?,
+            },
+            0x43 => Self::EnhancedMoveToHueAndSaturation {
+                hue: rdr.read_u16::<LE>()?,
+                sat: rdr.read_u8()?,
+                transition: rdr.read_u16::<LE>()?,
+            },
+            0x44 => Self::ColorLoopSet {
+                update: rdr.read_u8()?,
+                action: rdr.read_u8()?,
+                direction: rdr.read_u8()?,
+                time: rdr.read_u16::<LE>()?,
+                start_hue: rdr.read_u16::<LE>()?,
+            },
+            0x47 => Self::StopMoveStep,
+            0x4b => Self::MoveColorTemp {
+                mode: read_enum(&mut rdr)?,
+                rate: rdr.read_u16::<LE>()?,
+                min: rdr.read_u16::<LE>()?,
+                max: rdr.read_u16::<LE>()?,
+            },
+            0x4c => Self::StepColorTemp {
+                mode: read_enum(&mut rdr)?,
+                size: rdr.read_u16::<LE>()?,
+                transition: rdr.read_u16::<LE>()?,
+                min: rdr.read_u16::<LE>()?,
+                max: rdr.read_u16::<LE>()?,
+            },
+            _ => return Err(unsupported::<Self>(frame)),
+        };
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cluster::colorctrl::{ColorCtrlCommand as CCC, ColorMoveMode, HueDirection};
+    use crate::command::ZclClusterCommand;
+    use crate::command::tests::roundtrip;
+    use crate::error::ZclError;
+    use crate::frame::ZclFrame;
+
+    #[allow(clippy::too_many_lines)]
+    #[test]
+    fn colorctrl_roundtrip() {
+        for cmd in [
+            CCC::MoveToHue {
+                hue: 0x10,
+                direction: HueDirection::Longest,
+                transition: 0x1234,
+            },
+            CCC::MoveHue {
+                mode: ColorMoveMode::Up,
+                rate: 0x20,
+            },
+            CCC::StepHue {
+                mode: ColorMoveMode::Down,
+                size: 0x30,
+                transition: 0x05,
+            },
+            CCC::MoveToSaturation {
+                sat: 0xFE,
+                transition: 0x0010,
+            },
+            CCC::MoveSaturation {
+                mode: ColorMoveMode::Stop,
+                rate: 0x00,
+            },
+            CCC::StepSaturation {
+                mode: ColorMoveMode::Up,
+                size: 0x01,
+                transition: 0x02,
+            },
+            CCC::MoveToHueAndSaturation {
+                hue: 0x11,
+                sat: 0x22,
+                transition: 0x3344,
+            },
+            CCC::MoveToColor {
+                x: 0x1122,
+                y: 0x3344,
+                transition: 0x0004,
+            },
+            CCC::MoveColor {
+                rate_x: -100,
+                rate_y: 200,
+            },
+            CCC::StepColor {
+                step_x: 300,
+                step_y: -400,
+                transition: 0x0008,
+            },
+            CCC::MoveToColorTemp {
+                mireds: 366,
+                transition: 0x0004,
+            },
+            CCC::EnhancedMoveToHue {
+                hue: 0xABCD,
+                direction: HueDirection::Down,
+                transition: 0x0004,
+            },
+            CCC::EnhancedMoveHue {
+                mode: ColorMoveMode::Up,
+                rate: 0x0102,
+            },
+            CCC::EnhancedStepHue {
+                mode: ColorMoveMode::Down,
+                size: 0x0304,
+                transition: 0x0506,
+            },
+            CCC::EnhancedMoveToHueAndSaturation {
+                hue: 0x0708,
+                sat: 0x09,
+                transition: 0x0A0B,
+            },
+            CCC::ColorLoopSet {
+                update: 0x0F,
+                action: 0x01,
+                direction: 0x01,
+                time: 0x0019,
+                start_hue: 0x2345,
+            },
+            CCC::StopMoveStep,
+            CCC::MoveColorTemp {
+                mode: ColorMoveMode::Up,
+                rate: 0x0010,
+                min: 153,
+                max: 500,
+            },
+            CCC::StepColorTemp {
+                mode: ColorMoveMode::Down,
+                size: 0x0020,
+                transition: 0x0004,
+                min: 153,
+                max: 500,
+            },
+        ] {
+            assert_eq!(roundtrip(&cmd), cmd);
+        }
+    }
+
+    #[test]
+    fn colorctrl_encode() {
+        let cmd = CCC::MoveToColor {
+            x: 0x1122,
+            y: 0x3344,
+            transition: 0x5566,
+        };
+        assert_eq!(cmd.cmd(), 0x07);
+        assert_eq!(cmd.encode().unwrap(), [0x22, 0x11, 0x44, 0x33, 0x66, 0x55]);
+    }
+
+    #[test]
+    fn colorctrl_invalid_mode() {
+        let cmd = CCC::MoveHue {
+            mode: ColorMoveMode::Up,
+            rate: 0x20,
+        };
+        let frame = cmd.frame(0);
+
+        let res = CCC::parse(&frame, &[0x02, 0x20]);
+        assert!(matches!(res, Err(ZclError::InvalidEnumValue(0x02))));
+    }
+
+    #[test]
+    fn colorctrl_unsupported() {
+        let mut frame: ZclFrame = CCC::StopMoveStep.frame(0);
+        frame.cmd = 0x30;
+
+        let res = CCC::parse(&frame, &[]);
+        assert!(matches!(
+            res,
+            Err(ZclError::UnsupportedCommand {
+                cluster: 0x0300,
+                cmd: 0x30
+            })
+        ));
+    }
+}
@@ -1,3 +1,9 @@
+use std::io::{Cursor, Read};
+
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+
+use crate::command::{ZclClusterCommand, check_frame, unsupported};
+use crate::error::ZclResult;
 use crate::frame::{ZclFrame, ZclFrameDirection};
 
 #[must_use]
@@ -16,3 +22,139 @@ pub fn describe(frame: &ZclFrame, _data: &[u8]) -> Option<String> {
         }
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupsCommand {
+    Add { group: u16, name: String },
+    GetMembership { groups: Vec<u16> },
+    AddResp { status: u8, group: u16 },
+    GetMembershipResp { capacity: u8, groups: Vec<u16> },
+}
+
+fn write_groups(wtr: &mut Vec<u8>, groups: &[u16]) -> ZclResult<()> {
+    wtr.write_u8(u8::try_from(groups.len())?)?;
+    for group in groups {
+        wtr.write_u16::<LE>(*group)?;
+    }
+    Ok(())
+}
+
+fn read_groups(rdr: &mut impl Read) -> ZclResult<Vec<u16>> {
+    let count = rdr.read_u8()?;
+    (0..count).map(|_| Ok(rdr.read_u16::<LE>()?)).collect()
+}
+
+impl ZclClusterCommand for GroupsCommand {
+    const CLUSTER: u16 = 0x0004;
+
+    fn cmd(&self) -> u8 {
+        match self {
+            Self::Add { .. } | Self::AddResp { .. } => 0x00,
+            Self::GetMembership { .. } | Self::GetMembershipResp { .. } => 0x02,
+        }
+    }
+
+    fn direction(&self) -> ZclFrameDirection {
+        match self {
+            Self::Add { .. } | Self::GetMembership { .. } => ZclFrameDirection::ClientToServer,
+            Self::AddResp { .. } | Self::GetMembershipResp { .. } => {
+                ZclFrameDirection::ServerToClient
+            }
+        }
+    }
+
+    fn encode(&self) -> ZclResult<Vec<u8>> {
+        let mut res = vec![];
+
+        match self {
+            Self::Add { group, name } => {
+                res.write_u16::<LE>(*group)?;
+                res.write_u8(u8::try_from(name.len())?)?;
+                res.extend_from_slice(name.as_bytes());
+            }
+            Self::GetMembership { groups } => {
+                write_groups(&mut res, groups)?;
+            }
+            Self::AddResp { status, group } => {
+                res.write_u8(*status)?;
+                res.write_u16::<LE>(*group)?;
+            }
+            Self::GetMembershipResp { capacity, groups } => {
+                res.write_u8(*capacity)?;
+                write_groups(&mut res, groups)?;
+            }
+        }
+
+        Ok(res)
+    }
+
+    fn parse(frame: &ZclFrame, data: &[u8]) -> ZclResult<Self> {
+        check_frame::<Self>(frame, None)?;
+
+        let mut rdr = Cursor::new(data);
+
+        match (frame.c2s(), frame.cmd) {
+            (true, 0x00) => {
+                let group = rdr.read_u16::<LE>()?;
+                let mut name = vec![0; usize::from(rdr.read_u8()?)];
+                rdr.read_exact(&mut name)?;
+                Ok(Self::Add {
+                    group,
+                    name: String::from_utf8(name)?,
+                })
+            }
+            (true, 0x02) => Ok(Self::GetMembership {
+                groups: read_groups(&mut rdr)?,
+            }),
+            (false, 0x00) => Ok(Self::AddResp {
+                status: rdr.read_u8()?,
+                group: rdr.read_u16::<LE>()?,
+            }),
+            (false, 0x02) => Ok(Self::GetMembershipResp {
+                capacity: rdr.read_u8()?,
+                groups: read_groups(&mut rdr)?,
+            }),
+            _ => Err(unsupported::<Self>(frame)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cluster::groups::GroupsCommand;
+    use crate::command::ZclClusterCommand;
+    use crate::command::tests::roundtrip;
+
+    #[test]
+    fn groups_roundtrip() {
+        for cmd in [
+            GroupsCommand::Add {
+                group: 0x1234,
+                name: "Living room".to_string(),
+            },
+            GroupsCommand::GetMembership { groups: vec![] },
+            GroupsCommand::GetMembership {
+                groups: vec![0x0001, 0x0002],
+            },
+            GroupsCommand::AddResp {
+                status: 0x8A,
+                group: 0x1234,
+            },
+            GroupsCommand::GetMembershipResp {
+                capacity: 0xFE,
+                groups: vec![0x0003],
+            },
+        ] {
+            assert_eq!(roundtrip(&cmd), cmd);
+        }
+    }
+
+    #[test]
+    fn groups_encode() {
+        let cmd = GroupsCommand::Add {
+            group: 0x1234,
+            name: "ab".to_string(),
+        };
+        assert_eq!(cmd.encode().unwrap(), [0x34, 0x12, 0x02, b'a', b'b']);
+    }
+}
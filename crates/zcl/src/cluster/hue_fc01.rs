@@ -1,8 +1,12 @@
 use packed_struct::PackedStructSlice;
 
+use crate::command::{ZclClusterCommand, check_frame, unsupported};
 use crate::error::ZclResult;
-use crate::frame::ZclFrame;
-use hue::zigbee::{HueEntFrame, HueEntSegmentConfig, HueEntSegmentLayout, HueEntStop};
+use crate::frame::{ZclFrame, ZclFrameDirection};
+use hue::zigbee::{
+    EntertainmentZigbeeStream as EZS, HueEntFrame, HueEntSegmentConfig, HueEntSegmentLayout,
+    HueEntStop, PHILIPS_HUE_ZIGBEE_VENDOR_ID,
+};
 
 pub fn describe(frame: &ZclFrame, data: &[u8]) -> ZclResult<Option<String>> {
     if !frame.cluster_specific() {
@@ -24,3 +28,161 @@ pub fn describe(frame: &ZclFrame, data: &[u8]) -> ZclResult<Option<String>> {
         _ => Ok(None),
     }
 }
+
+/// Philips Hue entertainment cluster (0xFC01)
+#[derive(Debug, Clone)]
+pub enum HueFc01Command {
+    Frame(HueEntFrame),
+    Stop(HueEntStop),
+    /// Request for the segment layout of a light (single, unknown byte)
+    SegmentLayoutReq(u8),
+    SegmentLayout(HueEntSegmentLayout),
+    SegmentConfig(HueEntSegmentConfig),
+}
+
+impl HueFc01Command {
+    const CMD_SEGMENT_LAYOUT: u8 = 4;
+}
+
+impl ZclClusterCommand for HueFc01Command {
+    const CLUSTER: u16 = EZS::CLUSTER;
+
+    fn cmd(&self) -> u8 {
+        match self {
+            Self::Frame(_) => EZS::CMD_FRAME,
+            Self::Stop(_) => EZS::CMD_RESET,
+            Self::SegmentLayoutReq(_) | Self::SegmentLayout(_) => Self::CMD_SEGMENT_LAYOUT,
+            Self::SegmentConfig(_) => EZS::CMD_SEGMENT_MAP,
+        }
+    }
+
+    fn mfc(&self) -> Option<u16> {
+        Some(PHILIPS_HUE_ZIGBEE_VENDOR_ID)
+    }
+
+    fn direction(&self) -> ZclFrameDirection {
+        match self {
+            Self::SegmentLayout(_) => ZclFrameDirection::ServerToClient,
+            _ => ZclFrameDirection::ClientToServer,
+        }
+    }
+
+    fn encode(&self) -> ZclResult<Vec<u8>> {
+        let res = match self {
+            Self::Frame(frame) => frame.pack()?,
+            Self::Stop(stop) => stop.pack_to_vec()?,
+            Self::SegmentLayoutReq(data) => vec![*data],
+            Self::SegmentLayout(layout) => layout.pack()?,
+            Self::SegmentConfig(config) => config.pack()?,
+        };
+
+        Ok(res)
+    }
+
+    fn parse(frame: &ZclFrame, data: &[u8]) -> ZclResult<Self> {
+        check_frame::<Self>(frame, Some(PHILIPS_HUE_ZIGBEE_VENDOR_ID))?;
+
+        match (frame.c2s(), frame.cmd) {
+            (true, EZS::CMD_FRAME) => Ok(Self::Frame(HueEntFrame::parse(data)?)),
+            (true, EZS::CMD_RESET) => Ok(Self::Stop(HueEntStop::unpack_from_slice(data)?)),
+            (true, Self::CMD_SEGMENT_LAYOUT) if data.len() == 1 => {
+                Ok(Self::SegmentLayoutReq(data[0]))
+            }
+            (false, Self::CMD_SEGMENT_LAYOUT) => {
+                Ok(Self::SegmentLayout(HueEntSegmentLayout::parse(data)?))
+            }
+            (true, EZS::CMD_SEGMENT_MAP) => {
+                Ok(Self::SegmentConfig(HueEntSegmentConfig::parse(data)?))
+            }
+            _ => Err(unsupported::<Self>(frame)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hue::zigbee::{
+        HueEntFrame, HueEntFrameLightRecord, HueEntSegment, HueEntSegmentConfig,
+        HueEntSegmentLayout, HueEntStop, LightRecordMode,
+    };
+
+    use crate::cluster::hue_fc01::HueFc01Command;
+    use crate::command::ZclClusterCommand;
+    use crate::command::tests::roundtrip;
+
+    #[test]
+    fn hue_fc01_roundtrip() {
+        for cmd in [
+            HueFc01Command::Frame(HueEntFrame {
+                counter: 0x1122_3344,
+                smoothing: 0x0400,
+                blks: vec![
+                    HueEntFrameLightRecord::new(0x1234, 0x07FF, LightRecordMode::Device, [1, 2, 3]),
+                    HueEntFrameLightRecord::new(
+                        0x5678,
+                        0x0100,
+                        LightRecordMode::Segment,
+                        [4, 5, 6],
+                    ),
+                ],
+            }),
+            HueFc01Command::Stop(HueEntStop {
+                x0: 0,
+                x1: 1,
+                counter: 0x1122,
+            }),
+            HueFc01Command::SegmentLayoutReq(0x00),
+            HueFc01Command::SegmentLayout(HueEntSegmentLayout::new(&[
+                HueEntSegment {
+                    length: 1,
+                    index: 0,
+                },
+                HueEntSegment {
+                    length: 2,
+                    index: 1,
+                },
+            ])),
+            HueFc01Command::SegmentConfig(HueEntSegmentConfig::new(&[0xA0A1, 0xB0B1])),
+        ] {
+            roundtrip(&cmd);
+        }
+    }
+
+    #[test]
+    fn hue_fc01_stop() {
+        let cmd = HueFc01Command::Stop(HueEntStop {
+            x0: 0,
+            x1: 1,
+            counter: 0x1122,
+        });
+
+        let msg = cmd.to_message().unwrap();
+        assert_eq!(msg.cluster, 0xFC01);
+        assert_eq!(msg.command, 3);
+        assert_eq!(msg.data, [0x00, 0x01, 0x22, 0x11, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn hue_fc01_frame() {
+        let cmd = HueFc01Command::Frame(HueEntFrame {
+            counter: 0x1122,
+            smoothing: 0x3344,
+            blks: vec![],
+        });
+
+        let msg = cmd.to_message().unwrap();
+        assert_eq!(msg.cluster, 0xFC01);
+        assert_eq!(msg.command, 1);
+        assert_eq!(msg.data, [0x22, 0x11, 0x00, 0x00, 0x44, 0x33]);
+    }
+
+    #[test]
+    fn hue_fc01_segment_config() {
+        let cmd = HueFc01Command::SegmentConfig(HueEntSegmentConfig::new(&[0xA0A1, 0xB0B1]));
+
+        let msg = cmd.to_message().unwrap();
+        assert_eq!(msg.cluster, 0xFC01);
+        assert_eq!(msg.command, 7);
+        assert_eq!(msg.data, [0x00, 0x02, 0xA1, 0xA0, 0xB1, 0xB0]);
+    }
+}
@@ -1,5 +1,8 @@
-use hue::zigbee::Flags;
+use std::io::Cursor;
 
+use hue::zigbee::{Flags, HueZigbeeUpdate, PHILIPS_HUE_ZIGBEE_VENDOR_ID};
+
+use crate::command::{ZclClusterCommand, check_frame, unsupported};
 use crate::error::ZclResult;
 use crate::frame::ZclFrame;
 
@@ -16,3 +19,83 @@ pub fn describe(frame: &ZclFrame, data: &[u8]) -> ZclResult<Option<String>> {
         _ => Ok(None),
     }
 }
+
+/// Philips Hue light state/effects cluster (0xFC03)
+#[derive(Debug)]
+pub enum HueFc03Command {
+    Update(HueZigbeeUpdate),
+}
+
+impl ZclClusterCommand for HueFc03Command {
+    const CLUSTER: u16 = 0xFC03;
+
+    fn cmd(&self) -> u8 {
+        match self {
+            Self::Update(_) => 0x00,
+        }
+    }
+
+    fn mfc(&self) -> Option<u16> {
+        Some(PHILIPS_HUE_ZIGBEE_VENDOR_ID)
+    }
+
+    fn encode(&self) -> ZclResult<Vec<u8>> {
+        match self {
+            Self::Update(update) => Ok(update.to_vec()?),
+        }
+    }
+
+    fn parse(frame: &ZclFrame, data: &[u8]) -> ZclResult<Self> {
+        check_frame::<Self>(frame, Some(PHILIPS_HUE_ZIGBEE_VENDOR_ID))?;
+
+        match (frame.c2s(), frame.cmd) {
+            (true, 0x00) => Ok(Self::Update(HueZigbeeUpdate::from_reader(
+                &mut Cursor::new(data),
+            )?)),
+            _ => Err(unsupported::<Self>(frame)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hue::xy::XY;
+    use hue::zigbee::{EffectType, GradientStyle, HueZigbeeUpdate};
+
+    use crate::cluster::hue_fc03::HueFc03Command;
+    use crate::command::ZclClusterCommand;
+    use crate::command::tests::roundtrip;
+
+    #[test]
+    fn hue_fc03_roundtrip() {
+        for update in [
+            HueZigbeeUpdate::new(),
+            HueZigbeeUpdate::new()
+                .with_on_off(true)
+                .with_brightness(0xFE)
+                .with_color_mirek(366)
+                .with_fade_speed(4),
+            HueZigbeeUpdate::new()
+                .with_color_xy(XY::new(0.3, 0.3))
+                .with_effect_type(EffectType::Candle)
+                .with_effect_speed(0x80),
+            HueZigbeeUpdate::new()
+                .with_gradient_colors(
+                    GradientStyle::Linear,
+                    vec![XY::new(0.1, 0.2), XY::new(0.3, 0.4)],
+                )
+                .unwrap(),
+        ] {
+            roundtrip(&HueFc03Command::Update(update));
+        }
+    }
+
+    #[test]
+    fn hue_fc03_encode() {
+        let cmd = HueFc03Command::Update(HueZigbeeUpdate::new().with_on_off(true));
+        let msg = cmd.to_message().unwrap();
+        assert_eq!(msg.cluster, 0xFC03);
+        assert_eq!(msg.command, 0x00);
+        assert_eq!(msg.data, [0x01, 0x00, 0x01]);
+    }
+}
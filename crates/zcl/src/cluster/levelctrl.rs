@@ -1,3 +1,10 @@
+use std::io::Cursor;
+
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+use packed_struct::prelude::*;
+
+use crate::command::{ZclClusterCommand, check_frame, read_enum, unsupported};
+use crate::error::ZclResult;
 use crate::frame::{ZclFrame, ZclFrameDirection};
 
 #[must_use]
@@ -22,3 +29,173 @@ pub fn describe(frame: &ZclFrame, _data: &[u8]) -> Option<String> {
         _ => None,
     }
 }
+
+#[derive(PrimitiveEnum_u8, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelMode {
+    Up = 0x00,
+    Down = 0x01,
+}
+
+/// Level control commands. Each command has a variant that also affects the
+/// on/off state (`with_onoff`), using command id + 4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelCtrlCommand {
+    MoveToLevel {
+        level: u8,
+        transition: u16,
+        with_onoff: bool,
+    },
+    Move {
+        mode: LevelMode,
+        rate: u8,
+        with_onoff: bool,
+    },
+    Step {
+        mode: LevelMode,
+        size: u8,
+        transition: u16,
+        with_onoff: bool,
+    },
+    Stop {
+        with_onoff: bool,
+    },
+}
+
+impl LevelCtrlCommand {
+    const WITH_ONOFF: u8 = 0x04;
+
+    const fn with_onoff(self) -> bool {
+        match self {
+            Self::MoveToLevel { with_onoff, .. }
+            | Self::Move { with_onoff, .. }
+            | Self::Step { with_onoff, .. }
+            | Self::Stop { with_onoff } => with_onoff,
+        }
+    }
+}
+
+impl ZclClusterCommand for LevelCtrlCommand {
+    const CLUSTER: u16 = 0x0008;
+
+    fn cmd(&self) -> u8 {
+        let cmd = match self {
+            Self::MoveToLevel { .. } => 0x00,
+            Self::Move { .. } => 0x01,
+            Self::Step { .. } => 0x02,
+            Self::Stop { .. } => 0x03,
+        };
+
+        if self.with_onoff() {
+            cmd | Self::WITH_ONOFF
+        } else {
+            cmd
+        }
+    }
+
+    fn encode(&self) -> ZclResult<Vec<u8>> {
+        let mut res = vec![];
+
+        match self {
+            Self::MoveToLevel {
+                level, transition, ..
+            } => {
+                res.write_u8(*level)?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::Move { mode, rate, .. } => {
+                res.write_u8(mode.to_primitive())?;
+                res.write_u8(*rate)?;
+            }
+            Self::Step {
+                mode,
+                size,
+                transition,
+                ..
+            } => {
+                res.write_u8(mode.to_primitive())?;
+                res.write_u8(*size)?;
+                res.write_u16::<LE>(*transition)?;
+            }
+            Self::Stop { .. } => {}
+        }
+
+        Ok(res)
+    }
+
+    fn parse(frame: &ZclFrame, data: &[u8]) -> ZclResult<Self> {
+        check_frame::<Self>(frame, None)?;
+
+        if !frame.c2s() {
+            return Err(unsupported::<Self>(frame));
+        }
+
+        let mut rdr = Cursor::new(data);
+        let with_onoff = frame.cmd & Self::WITH_ONOFF != 0;
+
+        match frame.cmd & !Self::WITH_ONOFF {
+            0x00 => Ok(Self::MoveToLevel {
+                level: rdr.read_u8()?,
+                transition: rdr.read_u16::<LE>()?,
+                with_onoff,
+            }),
+            0x01 => Ok(Self::Move {
+                mode: read_enum(&mut rdr)?,
+                rate: rdr.read_u8()?,
+                with_onoff,
+            }),
+            0x02 => Ok(Self::Step {
+                mode: read_enum(&mut rdr)?,
+                size: rdr.read_u8()?,
+                transition: rdr.read_u16::<LE>()?,
+                with_onoff,
+            }),
+            0x03 => Ok(Self::Stop { with_onoff }),
+            _ => Err(unsupported::<Self>(frame)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cluster::levelctrl::{LevelCtrlCommand, LevelMode};
+    use crate::command::ZclClusterCommand;
+    use crate::command::tests::roundtrip;
+
+    #[test]
+    fn levelctrl_roundtrip() {
+        for with_onoff in [false, true] {
+            for cmd in [
+                LevelCtrlCommand::MoveToLevel {
+                    level: 0x80,
+                    transition: 0x1234,
+                    with_onoff,
+                },
+                LevelCtrlCommand::Move {
+                    mode: LevelMode::Down,
+                    rate: 0x10,
+                    with_onoff,
+                },
+                LevelCtrlCommand::Step {
+                    mode: LevelMode::Up,
+                    size: 0x20,
+                    transition: 0x0A,
+                    with_onoff,
+                },
+                LevelCtrlCommand::Stop { with_onoff },
+            ] {
+                assert_eq!(roundtrip(&cmd), cmd);
+            }
+        }
+    }
+
+    #[test]
+    fn levelctrl_encode() {
+        let cmd = LevelCtrlCommand::MoveToLevel {
+            level: 0x80,
+            transition: 0x1234,
+            with_onoff: true,
+        };
+        assert_eq!(cmd.cmd(), 0x04);
+        assert_eq!(cmd.encode().unwrap(), [0x80, 0x34, 0x12]);
+    }
+}
@@ -8,3 +8,62 @@ pub mod levelctrl;
 pub mod onoff;
 pub mod scenes;
 pub mod standard;
+
+use std::fmt::Debug;
+
+use crate::command::ZclClusterCommand;
+use crate::error::{ZclError, ZclResult};
+use crate::frame::ZclFrame;
+
+/// Describe a zcl frame for `cluster`, with `data` as the frame payload
+///
+/// Commands are decoded with the typed cluster commands where possible, and
+/// otherwise summarized by the cluster-specific describe functions.
+pub fn describe(cluster: u16, frame: &ZclFrame, data: &[u8]) -> ZclResult<Option<String>> {
+    fn typed<C: ZclClusterCommand + Debug>(
+        frame: &ZclFrame,
+        data: &[u8],
+        fallback: impl FnOnce() -> ZclResult<Option<String>>,
+    ) -> ZclResult<Option<String>> {
+        match C::parse(frame, data) {
+            Ok(cmd) => Ok(Some(format!("{cmd:?}"))),
+            Err(ZclError::UnsupportedCommand { .. }) => fallback(),
+            Err(err) => Err(err),
+        }
+    }
+
+    if !frame.cluster_specific() {
+        return standard::describe(frame, data);
+    }
+
+    match cluster {
+        0x0003 => Ok(effects::describe(frame, data)),
+        groups::GroupsCommand::CLUSTER => {
+            typed::<groups::GroupsCommand>(frame, data, || Ok(groups::describe(frame, data)))
+        }
+        scenes::ScenesCommand::CLUSTER => {
+            typed::<scenes::ScenesCommand>(frame, data, || Ok(scenes::describe(frame, data)))
+        }
+        onoff::OnOffCommand::CLUSTER => {
+            typed::<onoff::OnOffCommand>(frame, data, || Ok(onoff::describe(frame, data)))
+        }
+        levelctrl::LevelCtrlCommand::CLUSTER => {
+            typed::<levelctrl::LevelCtrlCommand>(frame, data, || {
+                Ok(levelctrl::describe(frame, data))
+            })
+        }
+        colorctrl::ColorCtrlCommand::CLUSTER => {
+            typed::<colorctrl::ColorCtrlCommand>(frame, data, || {
+                Ok(colorctrl::describe(frame, data))
+            })
+        }
+        0x1000 => commissioning::describe(frame, data),
+        hue_fc01::HueFc01Command::CLUSTER => {
+            typed::<hue_fc01::HueFc01Command>(frame, data, || hue_fc01::describe(frame, data))
+        }
+        hue_fc03::HueFc03Command::CLUSTER => {
+            typed::<hue_fc03::HueFc03Command>(frame, data, || hue_fc03::describe(frame, data))
+        }
+        _ => Ok(None),
+    }
+}
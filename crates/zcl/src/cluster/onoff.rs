@@ -1,3 +1,9 @@
+use std::io::Cursor;
+
+use byteorder::ReadBytesExt;
+
+use crate::command::{ZclClusterCommand, check_frame, unsupported};
+use crate::error::ZclResult;
 use crate::frame::{ZclFrame, ZclFrameDirection};
 
 #[must_use]
@@ -13,7 +19,88 @@ pub fn describe(frame: &ZclFrame, _data: &[u8]) -> Option<String> {
     match frame.cmd {
         0x00 => Some("Off".to_string()),
         0x01 => Some("On".to_string()),
+        0x02 => Some("Toggle".to_string()),
         0x40 => Some("OffWithEffect".to_string()),
         _ => None,
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnOffCommand {
+    Off,
+    On,
+    Toggle,
+    OffWithEffect { effect: u8, variant: u8 },
+}
+
+impl ZclClusterCommand for OnOffCommand {
+    const CLUSTER: u16 = 0x0006;
+
+    fn cmd(&self) -> u8 {
+        match self {
+            Self::Off => 0x00,
+            Self::On => 0x01,
+            Self::Toggle => 0x02,
+            Self::OffWithEffect { .. } => 0x40,
+        }
+    }
+
+    fn encode(&self) -> ZclResult<Vec<u8>> {
+        match self {
+            Self::Off | Self::On | Self::Toggle => Ok(vec![]),
+            Self::OffWithEffect { effect, variant } => Ok(vec![*effect, *variant]),
+        }
+    }
+
+    fn parse(frame: &ZclFrame, data: &[u8]) -> ZclResult<Self> {
+        check_frame::<Self>(frame, None)?;
+
+        let mut rdr = Cursor::new(data);
+
+        match (frame.c2s(), frame.cmd) {
+            (true, 0x00) => Ok(Self::Off),
+            (true, 0x01) => Ok(Self::On),
+            (true, 0x02) => Ok(Self::Toggle),
+            (true, 0x40) => Ok(Self::OffWithEffect {
+                effect: rdr.read_u8()?,
+                variant: rdr.read_u8()?,
+            }),
+            _ => Err(unsupported::<Self>(frame)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cluster::onoff::OnOffCommand;
+    use crate::command::ZclClusterCommand;
+    use crate::command::tests::roundtrip;
+
+    #[test]
+    fn onoff_roundtrip() {
+        for cmd in [
+            OnOffCommand::Off,
+            OnOffCommand::On,
+            OnOffCommand::Toggle,
+            OnOffCommand::OffWithEffect {
+                effect: 0x01,
+                variant: 0x02,
+            },
+        ] {
+            assert_eq!(roundtrip(&cmd), cmd);
+        }
+    }
+
+    #[test]
+    fn onoff_encode() {
+        let cmd = OnOffCommand::OffWithEffect {
+            effect: 0x01,
+            variant: 0x02,
+        };
+        let msg = cmd.to_message().unwrap();
+        assert_eq!(msg.cluster, 0x0006);
+        assert_eq!(msg.command, 0x40);
+        assert_eq!(msg.data, [0x01, 0x02]);
+        assert_eq!(msg.mfc, None);
+    }
+}
@@ -1,7 +1,13 @@
 #![allow(clippy::collapsible_else_if)]
 
-use hue::zigbee::Flags;
+use std::io::{Cursor, Read};
 
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+
+use hue::zigbee::{Flags, HueZigbeeUpdate, PHILIPS_HUE_ZIGBEE_VENDOR_ID};
+
+use crate::command::{ZclClusterCommand, check_frame, unsupported};
+use crate::error::ZclResult;
 use crate::frame::{ZclFrame, ZclFrameDirection};
 
 #[must_use]
@@ -37,3 +43,207 @@ pub fn describe(frame: &ZclFrame, data: &[u8]) -> Option<String> {
         }
     }
 }
+
+#[derive(Debug)]
+pub enum ScenesCommand {
+    Remove {
+        group: u16,
+        scene: u8,
+    },
+    Recall {
+        group: u16,
+        scene: u8,
+    },
+    GetMembership {
+        group: u16,
+    },
+    GetMembershipResp {
+        status: u8,
+        capacity: u8,
+        group: u16,
+        /// Only present when `status` is success (0x00)
+        scenes: Vec<u8>,
+    },
+    /// Philips-specific: store a full light state in a scene
+    SetComposite {
+        group: u16,
+        scene: u8,
+        update: HueZigbeeUpdate,
+    },
+}
+
+impl ZclClusterCommand for ScenesCommand {
+    const CLUSTER: u16 = 0x0005;
+
+    fn cmd(&self) -> u8 {
+        match self {
+            Self::Remove { .. } | Self::SetComposite { .. } => 0x02,
+            Self::Recall { .. } => 0x05,
+            Self::GetMembership { .. } | Self::GetMembershipResp { .. } => 0x06,
+        }
+    }
+
+    fn mfc(&self) -> Option<u16> {
+        match self {
+            Self::SetComposite { .. } => Some(PHILIPS_HUE_ZIGBEE_VENDOR_ID),
+            _ => None,
+        }
+    }
+
+    fn direction(&self) -> ZclFrameDirection {
+        match self {
+            Self::GetMembershipResp { .. } => ZclFrameDirection::ServerToClient,
+            _ => ZclFrameDirection::ClientToServer,
+        }
+    }
+
+    fn encode(&self) -> ZclResult<Vec<u8>> {
+        let mut res = vec![];
+
+        match self {
+            Self::Remove { group, scene } | Self::Recall { group, scene } => {
+                res.write_u16::<LE>(*group)?;
+                res.write_u8(*scene)?;
+            }
+            Self::GetMembership { group } => {
+                res.write_u16::<LE>(*group)?;
+            }
+            Self::GetMembershipResp {
+                status,
+                capacity,
+                group,
+                scenes,
+            } => {
+                res.write_u8(*status)?;
+                res.write_u8(*capacity)?;
+                res.write_u16::<LE>(*group)?;
+                if *status == 0 {
+                    res.write_u8(u8::try_from(scenes.len())?)?;
+                    res.extend_from_slice(scenes);
+                }
+            }
+            Self::SetComposite {
+                group,
+                scene,
+                update,
+            } => {
+                res.write_u16::<LE>(*group)?;
+                res.write_u8(*scene)?;
+                update.serialize(&mut res)?;
+            }
+        }
+
+        Ok(res)
+    }
+
+    fn parse(frame: &ZclFrame, data: &[u8]) -> ZclResult<Self> {
+        let mut rdr = Cursor::new(data);
+
+        if frame.manufacturer_specific() {
+            check_frame::<Self>(frame, Some(PHILIPS_HUE_ZIGBEE_VENDOR_ID))?;
+
+            return match (frame.c2s(), frame.cmd) {
+                (true, 0x02) => Ok(Self::SetComposite {
+                    group: rdr.read_u16::<LE>()?,
+                    scene: rdr.read_u8()?,
+                    update: HueZigbeeUpdate::from_reader(&mut rdr)?,
+                }),
+                _ => Err(unsupported::<Self>(frame)),
+            };
+        }
+
+        check_frame::<Self>(frame, None)?;
+
+        match (frame.c2s(), frame.cmd) {
+            (true, 0x02) => Ok(Self::Remove {
+                group: rdr.read_u16::<LE>()?,
+                scene: rdr.read_u8()?,
+            }),
+            (true, 0x05) => Ok(Self::Recall {
+                group: rdr.read_u16::<LE>()?,
+                scene: rdr.read_u8()?,
+            }),
+            (true, 0x06) => Ok(Self::GetMembership {
+                group: rdr.read_u16::<LE>()?,
+            }),
+            (false, 0x06) => {
+                let status = rdr.read_u8()?;
+                let capacity = rdr.read_u8()?;
+                let group = rdr.read_u16::<LE>()?;
+                let mut scenes = vec![];
+                if status == 0 {
+                    scenes.resize(usize::from(rdr.read_u8()?), 0);
+                    rdr.read_exact(&mut scenes)?;
+                }
+                Ok(Self::GetMembershipResp {
+                    status,
+                    capacity,
+                    group,
+                    scenes,
+                })
+            }
+            _ => Err(unsupported::<Self>(frame)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hue::xy::XY;
+    use hue::zigbee::{HueZigbeeUpdate, PHILIPS_HUE_ZIGBEE_VENDOR_ID};
+
+    use crate::cluster::scenes::ScenesCommand;
+    use crate::command::ZclClusterCommand;
+    use crate::command::tests::roundtrip;
+
+    #[test]
+    fn scenes_roundtrip() {
+        for cmd in [
+            ScenesCommand::Remove {
+                group: 0x1234,
+                scene: 0x05,
+            },
+            ScenesCommand::Recall {
+                group: 0x1234,
+                scene: 0x06,
+            },
+            ScenesCommand::GetMembership { group: 0x0001 },
+            ScenesCommand::GetMembershipResp {
+                status: 0x00,
+                capacity: 0x10,
+                group: 0x0001,
+                scenes: vec![0x01, 0x02, 0x03],
+            },
+            ScenesCommand::GetMembershipResp {
+                status: 0x85,
+                capacity: 0x10,
+                group: 0x0001,
+                scenes: vec![],
+            },
+            ScenesCommand::SetComposite {
+                group: 0x0001,
+                scene: 0x07,
+                update: HueZigbeeUpdate::new()
+                    .with_on_off(true)
+                    .with_brightness(0x80)
+                    .with_color_xy(XY::new(0.25, 0.5)),
+            },
+        ] {
+            roundtrip(&cmd);
+        }
+    }
+
+    #[test]
+    fn scenes_set_composite() {
+        let cmd = ScenesCommand::SetComposite {
+            group: 0x1234,
+            scene: 0x05,
+            update: HueZigbeeUpdate::new().with_brightness(0x80),
+        };
+
+        let msg = cmd.to_message().unwrap();
+        assert_eq!(msg.mfc, Some(PHILIPS_HUE_ZIGBEE_VENDOR_ID));
+        assert_eq!(msg.command, 0x02);
+        assert_eq!(msg.data, [0x34, 0x12, 0x05, 0x02, 0x00, 0x80]);
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::Read;
+
+use byteorder::ReadBytesExt;
+use packed_struct::PrimitiveEnum;
+
+use hue::zigbee::ZigbeeMessage;
+
+use crate::error::{ZclError, ZclResult};
+use crate::frame::{ZclFrame, ZclFrameDirection, ZclFrameFlags, ZclFrameType};
+
+/// Typed command for a specific zigbee cluster
+///
+/// Implementations can encode commands to their zcl payload, and parse them
+/// back again from a received [`ZclFrame`] and payload.
+pub trait ZclClusterCommand: Sized {
+    const CLUSTER: u16;
+
+    /// Command id within [`Self::CLUSTER`]
+    fn cmd(&self) -> u8;
+
+    /// Manufacturer code, for manufacturer-specific commands
+    fn mfc(&self) -> Option<u16> {
+        None
+    }
+
+    fn direction(&self) -> ZclFrameDirection {
+        ZclFrameDirection::ClientToServer
+    }
+
+    /// Encode the command payload (without the zcl frame header)
+    fn encode(&self) -> ZclResult<Vec<u8>>;
+
+    /// Parse the command payload `data`, as described by `frame`
+    fn parse(frame: &ZclFrame, data: &[u8]) -> ZclResult<Self>;
+
+    /// Zcl frame header for this command
+    fn frame(&self, seqnr: u8) -> ZclFrame {
+        let mfcode = self.mfc();
+
+        ZclFrame {
+            flags: ZclFrameFlags {
+                frame_type: ZclFrameType::ClusterSpecific,
+                manufacturer_specific: mfcode.is_some(),
+                direction: self.direction(),
+                disable_default_response: true,
+            },
+            mfcode,
+            seqnr,
+            cmd: self.cmd(),
+        }
+    }
+
+    fn to_message(&self) -> ZclResult<ZigbeeMessage> {
+        Ok(ZigbeeMessage::new(Self::CLUSTER, self.cmd(), self.encode()?).with_mfc(self.mfc()))
+    }
+}
+
+/// Check that `frame` is a cluster-specific command with manufacturer code `mfc`
+pub(crate) fn check_frame<C: ZclClusterCommand>(
+    frame: &ZclFrame,
+    mfc: Option<u16>,
+) -> ZclResult<()> {
+    if frame.cluster_specific() && frame.mfcode == mfc {
+        Ok(())
+    } else {
+        Err(unsupported::<C>(frame))
+    }
+}
+
+pub(crate) const fn unsupported<C: ZclClusterCommand>(frame: &ZclFrame) -> ZclError {
+    ZclError::UnsupportedCommand {
+        cluster: C::CLUSTER,
+        cmd: frame.cmd,
+    }
+}
+
+pub(crate) fn read_enum<T: PrimitiveEnum<Primitive = u8>>(rdr: &mut impl Read) -> ZclResult<T> {
+    let value = rdr.read_u8()?;
+    T::from_primitive(value).ok_or(ZclError::InvalidEnumValue(value))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::fmt::Debug;
+
+    use crate::command::ZclClusterCommand;
+    use crate::frame::ZclFrame;
+
+    /// Encode `cmd`, parse it back, and check that it encodes to the same bytes
+    pub fn roundtrip<C: ZclClusterCommand + Debug>(cmd: &C) -> C {
+        let data = cmd.encode().unwrap();
+        let frame = ZclFrame::parse(&mut cmd.frame(0x42).to_vec().unwrap().as_slice()).unwrap();
+
+        let res = C::parse(&frame, &data).unwrap();
+        assert_eq!(res.cmd(), cmd.cmd());
+        assert_eq!(res.encode().unwrap(), data);
+
+        let msg = cmd.to_message().unwrap();
+        assert_eq!(msg.cluster, C::CLUSTER);
+        assert_eq!(msg.command, cmd.cmd());
+        assert_eq!(msg.mfc, cmd.mfc());
+        assert_eq!(msg.data, data);
+
+        res
+    }
+}
@@ -9,12 +9,21 @@ pub enum ZclError {
     #[error(transparent)]
     IOError(#[from] std::io::Error),
 
+    #[error(transparent)]
+    TryFromIntError(#[from] std::num::TryFromIntError),
+
     #[error(transparent)]
     HueError(#[from] hue::error::HueError),
 
     #[error("Attribute type 0x{0:02x} not supported")]
     UnsupportedAttrType(u8),
 
+    #[error("Command 0x{cmd:02x} not supported for cluster 0x{cluster:04x}")]
+    UnsupportedCommand { cluster: u16, cmd: u8 },
+
+    #[error("Invalid enum value 0x{0:02x}")]
+    InvalidEnumValue(u8),
+
     #[error(transparent)]
     PackedStructError(#[from] packed_struct::PackingError),
 }
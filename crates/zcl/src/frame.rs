@@ -1,7 +1,7 @@
 use std::fmt::Debug;
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{BigEndian as BE, ReadBytesExt};
+use byteorder::{BigEndian as BE, ReadBytesExt, WriteBytesExt};
 use packed_struct::prelude::*;
 
 use crate::error::ZclResult;
@@ -83,6 +83,25 @@ impl ZclFrame {
         })
     }
 
+    pub fn write(&self, wtr: &mut impl Write) -> ZclResult<()> {
+        wtr.write_all(&self.flags.pack()?)?;
+
+        if let Some(mfcode) = self.mfcode {
+            wtr.write_u16::<BE>(mfcode)?;
+        }
+
+        wtr.write_u8(self.seqnr)?;
+        wtr.write_u8(self.cmd)?;
+
+        Ok(())
+    }
+
+    pub fn to_vec(&self) -> ZclResult<Vec<u8>> {
+        let mut res = vec![];
+        self.write(&mut res)?;
+        Ok(res)
+    }
+
     #[must_use]
     pub fn c2s(&self) -> bool {
         self.flags.direction == ZclFrameDirection::ClientToServer
@@ -98,3 +117,45 @@ impl ZclFrame {
         self.flags.manufacturer_specific
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::frame::{ZclFrame, ZclFrameDirection, ZclFrameFlags, ZclFrameType};
+
+    #[test]
+    fn frame_roundtrip() {
+        let frame = ZclFrame {
+            flags: ZclFrameFlags {
+                frame_type: ZclFrameType::ClusterSpecific,
+                manufacturer_specific: true,
+                direction: ZclFrameDirection::ServerToClient,
+                disable_default_response: true,
+            },
+            mfcode: Some(0x100B),
+            seqnr: 0x42,
+            cmd: 0x07,
+        };
+
+        let data = frame.to_vec().unwrap();
+        assert_eq!(data, [0x1D, 0x10, 0x0B, 0x42, 0x07]);
+
+        let res = ZclFrame::parse(&mut data.as_slice()).unwrap();
+        assert_eq!(res.to_vec().unwrap(), data);
+        assert_eq!(res.mfcode, Some(0x100B));
+        assert!(!res.c2s());
+        assert!(res.cluster_specific());
+    }
+
+    #[test]
+    fn frame_roundtrip_standard() {
+        let data = [0x01, 0x13, 0x40];
+
+        let frame = ZclFrame::parse(&mut data.as_slice()).unwrap();
+        assert_eq!(frame.mfcode, None);
+        assert_eq!(frame.seqnr, 0x13);
+        assert_eq!(frame.cmd, 0x40);
+        assert!(frame.c2s());
+
+        assert_eq!(frame.to_vec().unwrap(), data);
+    }
+}
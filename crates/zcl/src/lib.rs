@@ -1,4 +1,5 @@
 pub mod attr;
 pub mod cluster;
+pub mod command;
 pub mod error;
 pub mod frame;
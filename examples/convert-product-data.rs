@@ -13,10 +13,12 @@ use hue::devicedb::{SimpleProductData, product_data};
 
 fn print_std(obj: DeviceProductData) {
     let spd = SimpleProductData {
-        manufacturer_name: &obj.manufacturer_name,
-        product_name: &obj.product_name,
-        product_archetype: obj.product_archetype,
-        hardware_platform_type: obj.hardware_platform_type.as_deref(),
+        hardware_platform_type: obj.hardware_platform_type,
+        ..SimpleProductData::third_party(
+            &obj.manufacturer_name,
+            &obj.product_name,
+            obj.product_archetype,
+        )
     };
     println!(
         "{:?} => {},",
@@ -0,0 +1,27 @@
+use clap::Parser;
+use url::Url;
+
+use bifrost_api::Client;
+use bifrost_api::error::BifrostResult;
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Url to bifrost api (<http://example.org/bifrost/>)
+    url: Url,
+
+    /// Name of recording, in the configured recording directory
+    name: String,
+}
+
+#[tokio::main]
+async fn main() -> BifrostResult<()> {
+    let args = Args::parse();
+
+    let client = Client::from_url(args.url);
+
+    let area = client.entertainment_replay(&args.name).await?;
+
+    println!("Replaying {} to entertainment area {area}", args.name);
+
+    Ok(())
+}
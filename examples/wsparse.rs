@@ -89,6 +89,21 @@ async fn main() -> ApiResult<()> {
                 Message::BridgeDeviceRemove(obj) => {
                     println!("{obj:#?}");
                 }
+                Message::BridgeDeviceRename(obj) => {
+                    println!("{obj:#?}");
+                }
+                Message::BridgeTouchlinkIdentify(obj) => {
+                    println!("{obj:#?}");
+                }
+                Message::BridgeTouchlinkFactoryReset(obj) => {
+                    println!("{obj:#?}");
+                }
+                Message::BridgeDeviceOtaUpdate(obj) => {
+                    println!("{obj:#?}");
+                }
+                Message::BridgeHealth(obj) => {
+                    println!("{obj:#?}");
+                }
                 Message::BridgeDeviceOptions(obj) => {
                     println!("{obj:#?}");
                 }
@@ -1 +1,128 @@
 pub mod z2m;
+
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+use bifrost_api::backend::{BackendRequest, BackendResponse};
+use hue::api::ZigbeeConnectivityStatus;
+use svc::serviceid::ServiceName;
+use svc::traits::ServiceState;
+
+use crate::error::{ApiError, ApiResult};
+use crate::server::appstate::AppState;
+
+/// Name of the service template used for z2m backends
+pub const Z2M_SERVICE_NAME: &str = "z2m";
+
+/// Maximum time to wait for a backend to answer a request
+pub const BACKEND_REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Channel for a backend to answer a [`BackendRequest`] that produces a result
+pub type BackendReply = mpsc::UnboundedSender<ApiResult<BackendResponse>>;
+
+/// A [`BackendRequest`], addressed to the backend that owns the affected
+/// resource, or to all backends if there is no single owner.
+#[derive(Clone, Debug)]
+pub struct BackendMessage {
+    pub target: Option<String>,
+    pub request: BackendRequest,
+    pub reply: Option<BackendReply>,
+}
+
+impl BackendMessage {
+    #[must_use]
+    pub const fn new(target: Option<String>, request: BackendRequest) -> Self {
+        Self {
+            target,
+            request,
+            reply: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_reply(self, reply: BackendReply) -> Self {
+        Self {
+            reply: Some(reply),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn is_for(&self, backend: &str) -> bool {
+        self.target
+            .as_deref()
+            .is_none_or(|target| target == backend)
+    }
+}
+
+/// Wait for the answer to a request sent with [`Resources::backend_query`]
+///
+/// [`Resources::backend_query`]: crate::resource::Resources::backend_query
+pub async fn backend_response(
+    mut rx: mpsc::UnboundedReceiver<ApiResult<BackendResponse>>,
+) -> ApiResult<BackendResponse> {
+    timeout(BACKEND_REPLY_TIMEOUT, rx.recv())
+        .await
+        .ok()
+        .flatten()
+        .ok_or(ApiError::BackendNoReply)?
+}
+
+/// Follow the state of all backend services, and mark their devices as
+/// disconnected whenever a backend is not running.
+pub async fn backend_monitor(appstate: AppState) -> ApiResult<()> {
+    let mut mgr = appstate.manager();
+    let (_id, mut rx) = mgr.subscribe().await?;
+
+    // backends might have been started before we subscribed, so begin from
+    // their current state
+    for (id, name) in mgr.list().await? {
+        if let Ok(state) = mgr.status(id).await {
+            update_backend_state(&appstate, &name, state).await;
+        }
+    }
+
+    while let Some(event) = rx.recv().await {
+        let Ok(name) = mgr.lookup_name(event.id()).await else {
+            continue;
+        };
+
+        update_backend_state(&appstate, &name, event.state()).await;
+    }
+
+    Ok(())
+}
+
+async fn update_backend_state(appstate: &AppState, name: &ServiceName, state: ServiceState) {
+    if name.name() != Z2M_SERVICE_NAME {
+        return;
+    }
+
+    let Some(backend) = name.instance() else {
+        return;
+    };
+
+    let status = match state {
+        ServiceState::Running => ZigbeeConnectivityStatus::Connected,
+        ServiceState::Starting
+        | ServiceState::Stopping
+        | ServiceState::Stopped
+        | ServiceState::Failed => ZigbeeConnectivityStatus::Disconnected,
+        ServiceState::Registered | ServiceState::Configured => return,
+    };
+
+    log::debug!("Backend {backend} is {state:?}, marking devices {status:?}");
+
+    let mut lock = appstate.res.lock().await;
+    if let Err(err) = lock.set_backend_connectivity(backend, status) {
+        log::error!("Failed to update connectivity for backend {backend}: {err}");
+    }
+    if status == ZigbeeConnectivityStatus::Disconnected {
+        if let Err(err) = lock.stop_backend_streaming(backend) {
+            log::error!("Failed to stop streaming for backend {backend}: {err}");
+        }
+    }
+    drop(lock);
+}
@@ -1,28 +1,39 @@
 use std::collections::{BTreeMap, BTreeSet};
-use std::sync::Arc;
 
+use chrono::Utc;
 use hue::clamp::Clamp;
 use hue::effect_duration::EffectDuration;
 use hue::zigbee::{GradientParams, GradientStyle, HueZigbeeUpdate};
-use tokio::time::sleep;
+use tokio::time::{Instant, sleep};
 use uuid::Uuid;
 
-use bifrost_api::backend::BackendRequest;
+use bifrost_api::backend::{BackendRequest, BackendResponse};
+use bifrost_api::device::{ZclAttrRead, ZclAttrWrite};
 use hue::api::{
-    Entertainment, EntertainmentConfiguration, GroupedLight, GroupedLightUpdate, Light,
-    LightEffectsV2Update, LightGradientMode, LightUpdate, RType, Resource, ResourceLink, Room,
+    ColorGamut, Device, DeviceProductData, DeviceSoftwareUpdate, DeviceSoftwareUpdateState,
+    DeviceSoftwareUpdateUpdate, Entertainment, EntertainmentConfiguration,
+    EntertainmentConfigurationStreamProxyMode, GroupedLight, GroupedLightUpdate, Light,
+    LightEffectsV2Update, LightGradientMode, LightPowerup, LightPowerupPreset,
+    LightSignalingStatus, LightSignalingUpdate, LightUpdate, RType, Resource, ResourceLink, Room,
     RoomUpdate, Scene, SceneActive, SceneStatus, SceneStatusEnum, SceneUpdate,
-    ZigbeeDeviceDiscoveryUpdate,
+    ZigbeeDeviceDiscoveryStatus, ZigbeeDeviceDiscoveryUpdate,
 };
 use hue::error::HueError;
 use hue::stream::HueStreamLightsV2;
+use z2m::api::DeviceType;
+use z2m::request::Z2mRequest;
 use z2m::update::{DeviceEffect, DeviceUpdate};
 
-use crate::backend::z2m::Z2mBackend;
-use crate::backend::z2m::entertainment::EntStream;
+use crate::backend::BackendReply;
+use crate::backend::z2m::discovery::Discovery;
+use crate::backend::z2m::entertainment::{EntStream, FallbackLight};
+use crate::backend::z2m::signaling::{ActiveSignal, SignalEnd, SignalStep};
 use crate::backend::z2m::websocket::Z2mWebSocket;
+use crate::backend::z2m::zclcommand::{self, PendingZclRead};
+use crate::backend::z2m::{DelayedMessage, Z2mBackend};
 use crate::error::ApiResult;
 use crate::model::state::AuxData;
+use crate::resource::Resources;
 
 impl Z2mBackend {
     #[allow(clippy::match_same_arms)]
@@ -77,8 +88,31 @@ impl Z2mBackend {
         Ok(hz)
     }
 
+    /// Hue lights support effects (and gradients) through a hue-specific
+    /// zigbee cluster, instead of through z2m
+    fn is_hue_light(&self, topic: &str) -> bool {
+        self.network
+            .get(topic)
+            .and_then(|dev| dev.manufacturer.as_deref())
+            == Some(DeviceProductData::SIGNIFY_MANUFACTURER_NAME)
+    }
+
+    fn room_gamut(res: &Resources, room: &ResourceLink) -> Option<ColorGamut> {
+        let room: &Room = res.get(room).ok()?;
+
+        let mut gamuts = room
+            .children
+            .iter()
+            .filter_map(|dev| res.get::<Device>(dev).ok()?.light_service())
+            .filter_map(|light| res.get::<Light>(light).ok()?.as_gamut_opt());
+
+        let first = gamuts.next()?;
+
+        gamuts.all(|gamut| gamut == first).then_some(first)
+    }
+
     async fn backend_light_update(
-        &self,
+        &mut self,
         z2mws: &mut Z2mWebSocket,
         link: &ResourceLink,
         upd: &LightUpdate,
@@ -99,7 +133,30 @@ impl Z2mBackend {
                 }
             })?;
         }
-        let hue_effects = lock.get::<Light>(link)?.effects.is_some();
+
+        // z2m only reports the powerup settings, not the preset they were
+        // chosen from, so the preset has to be remembered here.
+        let powerup = if let Some(pu) = &upd.powerup {
+            let mut powerup = lock
+                .get::<Light>(link)?
+                .powerup
+                .clone()
+                .unwrap_or_else(|| LightPowerup::new(LightPowerupPreset::Safety));
+            powerup += pu;
+            lock.update::<Light>(&link.rid, |light| light.powerup = Some(powerup.clone()))?;
+            Some(powerup)
+        } else {
+            None
+        };
+
+        let light = lock.get::<Light>(link)?;
+        let hue_effects = self.is_hue_light(topic);
+
+        // Make sure we never send colors the light cannot reproduce. This way,
+        // the state reported back from z2m matches what the light shows.
+        let upd = &light
+            .as_gamut_opt()
+            .map_or_else(|| upd.clone(), |gamut| upd.clone().with_gamut_clamp(&gamut));
         drop(lock);
 
         /* step 1: send generic light update */
@@ -126,6 +183,21 @@ impl Z2mBackend {
         // (and only) way to do it
         if !hue_effects {
             payload = payload.with_gradient(upd.gradient.clone());
+
+            // other lights can only run the effects z2m knows them by
+            let effect = upd
+                .effects_v2
+                .as_ref()
+                .and_then(|fx| fx.action.as_ref())
+                .and_then(|act| act.effect);
+
+            if let Some(effect) = effect {
+                payload = payload.with_effect(effect.into());
+            }
+        }
+
+        if let Some(powerup) = &powerup {
+            payload = payload.with_powerup(powerup);
         }
 
         // handle "identify" request (light breathing)
@@ -141,7 +213,7 @@ impl Z2mBackend {
                 sleep(Self::LIGHT_BREATHE_DURATION).await;
 
                 let upd = DeviceUpdate::new().with_effect(DeviceEffect::FinishEffect);
-                tx.send((topic, upd))
+                tx.send((topic, DelayedMessage::Update(upd)))
             });
         }
 
@@ -159,6 +231,103 @@ impl Z2mBackend {
             }
         }
 
+        /* step 3: start (or stop) light signaling */
+
+        if let Some(sig) = &upd.signaling {
+            self.backend_light_signaling(z2mws, link, sig).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn backend_light_signaling(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+        sig: &LightSignalingUpdate,
+    ) -> ApiResult<()> {
+        let Some(topic) = self.rmap.get(link).cloned() else {
+            return Ok(());
+        };
+
+        // If a signal is already running, it is replaced by this one. The
+        // state to restore is still the one from before the first signal.
+        let previous = self
+            .signals
+            .remove(&link.rid)
+            .filter(|active| !active.job.is_finished())
+            .map(|active| {
+                active.job.abort();
+                active.restore
+            });
+
+        let mut lock = self.state.lock().await;
+        let light = lock.get::<Light>(link)?;
+        let hue_effects = self
+            .rmap
+            .get(link)
+            .is_some_and(|topic| self.is_hue_light(topic));
+        let steps = SignalStep::steps(sig, light.as_gamut_opt().as_ref());
+
+        let restore = previous.unwrap_or_else(|| SignalStep::restore(light));
+
+        let duration = sig.duration();
+        let status = (!steps.is_empty()).then(|| LightSignalingStatus {
+            signal: sig.signal,
+            estimated_end: Utc::now() + duration,
+            colors: sig.colors.clone(),
+        });
+
+        lock.update::<Light>(&link.rid, |light| {
+            if let Some(signaling) = &mut light.signaling {
+                signaling.status = status;
+            }
+        })?;
+        drop(lock);
+
+        if steps.is_empty() {
+            log::debug!("[{}] Stopping signal on {topic}", self.name);
+            return z2mws.send_update(&topic, &restore).await;
+        }
+
+        log::debug!(
+            "[{}] Signaling {:?} on {topic} for {duration:?}",
+            self.name,
+            sig.signal
+        );
+
+        let tx = self.message_tx.clone();
+        let state = self.state.clone();
+        let rid = link.rid;
+        let end = Instant::now() + duration;
+        let signal_end = SignalEnd {
+            light: rid,
+            signal: Uuid::new_v4(),
+            restore: restore.clone(),
+        };
+        let id = signal_end.signal;
+
+        let job = tokio::spawn(async move {
+            for step in steps.iter().cycle() {
+                if Instant::now() >= end {
+                    break;
+                }
+                let _ = tx.send((topic.clone(), step.message(hue_effects)));
+                sleep(SignalStep::INTERVAL).await;
+            }
+
+            let _ = tx.send((topic, DelayedMessage::SignalEnd(signal_end)));
+
+            let _ = state.lock().await.update::<Light>(&rid, |light| {
+                if let Some(signaling) = &mut light.signaling {
+                    signaling.status = None;
+                }
+            });
+        });
+
+        self.signals
+            .insert(link.rid, ActiveSignal { id, job, restore });
+
         Ok(())
     }
 
@@ -179,7 +348,8 @@ impl Z2mBackend {
 
         let auxdata = AuxData::new()
             .with_topic(&scene.metadata.name)
-            .with_index(sid);
+            .with_index(sid)
+            .with_backend(&self.name);
 
         lock.aux_set(link_scene, auxdata);
 
@@ -269,10 +439,19 @@ impl Z2mBackend {
         link: &ResourceLink,
         upd: &GroupedLightUpdate,
     ) -> ApiResult<()> {
-        let room = self.state.lock().await.get::<GroupedLight>(link)?.owner;
+        let lock = self.state.lock().await;
+        let room = lock.get::<GroupedLight>(link)?.owner;
+
+        // If all member lights share the same gamut, we can clamp the color
+        // for the entire group. Otherwise, each light has to make do.
+        let mut upd = upd.clone();
+        if let (Some(col), Some(gamut)) = (&mut upd.color, Self::room_gamut(&lock, &room)) {
+            col.xy = gamut.clamp(col.xy);
+        }
+        drop(lock);
 
         if let Some(topic) = self.rmap.get(&room) {
-            z2mws.send_update(topic, &upd.into()).await?;
+            z2mws.send_update(topic, &(&upd).into()).await?;
         }
 
         Ok(())
@@ -373,41 +552,88 @@ impl Z2mBackend {
 
         let mut chans = ent.channels.clone();
 
-        let mut addrs: BTreeMap<String, Vec<u16>> = BTreeMap::new();
+        let mut segments: BTreeMap<u8, Vec<(String, u16)>> = BTreeMap::new();
+        let mut gamuts: BTreeMap<String, ColorGamut> = BTreeMap::new();
+        let mut fallback: BTreeMap<u8, Vec<FallbackLight>> = BTreeMap::new();
+        let mut channels: BTreeSet<u8> = BTreeSet::new();
         let mut targets = vec![];
         chans.sort_by_key(|c| c.channel_id);
 
         log::trace!("[{}] Resolving entertainment channels", self.name);
         for chan in chans {
+            let channel = u8::try_from(chan.channel_id)?;
+            channels.insert(channel);
+
             for member in &chan.members {
                 let ent: &Entertainment = lock.get(&member.service)?;
                 let light_id = ent
                     .renderer_reference
                     .ok_or(HueError::NotFound(member.service.rid))?;
-                let topic = self
-                    .rmap
-                    .get(&light_id)
-                    .ok_or(HueError::NotFound(light_id.rid))?;
+                // entertainment areas can span several backends, and each
+                // backend only renders the lights it owns
+                let Some(topic) = self.rmap.get(&light_id) else {
+                    log::trace!("[{}] Skipping foreign light {light_id:?}", self.name);
+                    continue;
+                };
                 let dev = self
                     .network
                     .get(topic)
                     .ok_or(HueError::NotFound(member.service.rid))?;
 
+                let gamut = lock.get::<Light>(&light_id)?.as_gamut_opt();
+
+                // only Hue lights understand the entertainment stream, so
+                // everything else gets regular (rate-limited) updates
+                if dev.manufacturer.as_deref() != Some(DeviceProductData::SIGNIFY_MANUFACTURER_NAME)
+                {
+                    fallback
+                        .entry(channel)
+                        .or_default()
+                        .push(FallbackLight::new(topic, gamut, self.fallback_fps));
+                    continue;
+                }
+
                 let segment_addr = dev.network_address + member.index;
 
-                addrs
-                    .entry(dev.friendly_name.clone())
+                segments
+                    .entry(channel)
                     .or_default()
-                    .push(segment_addr);
+                    .push((dev.friendly_name.clone(), segment_addr));
+
+                if let Some(gamut) = gamut {
+                    gamuts.insert(dev.friendly_name.clone(), gamut);
+                }
 
                 targets.push(topic);
             }
         }
-        log::debug!("Entertainment addresses: {addrs:04x?}");
+        log::debug!("Entertainment segments: {segments:04x?}");
+
+        let proxy = self.choose_proxy(&lock, ent, &targets);
+        let proxy_mode = ent.stream_proxy.mode;
         drop(lock);
 
-        if let Some(target) = targets.first() {
-            let mut es = EntStream::new(self.counter, target, addrs);
+        if let Some((topic, node)) = &proxy {
+            log::info!("[{}] Using {topic} as entertainment proxy", self.name);
+
+            // in auto mode, report the chosen node back. If the area spans
+            // several networks, the last backend to start wins.
+            if let (EntertainmentConfigurationStreamProxyMode::Auto, Some(node)) =
+                (proxy_mode, node)
+            {
+                self.state
+                    .lock()
+                    .await
+                    .update::<EntertainmentConfiguration>(ent_id, |ec| {
+                        ec.stream_proxy.node = *node;
+                    })?;
+            }
+        }
+
+        if !targets.is_empty() || !fallback.is_empty() {
+            let target = proxy.as_ref().map(|(topic, _)| topic.as_str());
+            let mut es =
+                EntStream::new(self.counter, target, &segments, &gamuts, channels, fallback);
 
             // Not even a real Philips Hue bridge uses this trick!
             //
@@ -426,6 +652,75 @@ impl Z2mBackend {
         Ok(())
     }
 
+    /// Choose the light relaying the entertainment stream on this network,
+    /// returning its topic and (if known) its entertainment service.
+    ///
+    /// In manual mode, the configured node is used, if it is on this network.
+    /// Otherwise, the Hue light (among `targets`) best connected to the other
+    /// targets in the z2m network map is chosen, preferring routers (see
+    /// [`proxy_rank`]). Until the network map is known, or between
+    /// lights it does not tell apart, the link quality from the regular z2m
+    /// state messages decides. Ties go to the first target.
+    ///
+    /// [`proxy_rank`]: crate::backend::z2m::topology::Topology::proxy_rank
+    fn choose_proxy(
+        &self,
+        res: &Resources,
+        ent: &EntertainmentConfiguration,
+        targets: &[&String],
+    ) -> Option<(String, Option<ResourceLink>)> {
+        let ent_service = |topic: &str| -> Option<ResourceLink> {
+            let light = res.get::<Light>(self.map.get(topic)?).ok()?;
+            let dev = res.get::<Device>(&light.owner).ok()?;
+            dev.entertainment_service().copied()
+        };
+
+        if ent.stream_proxy.mode == EntertainmentConfigurationStreamProxyMode::Manual {
+            let node = ent.stream_proxy.node;
+            let manual = res
+                .get::<Entertainment>(&node)
+                .ok()
+                .and_then(|enttm| enttm.renderer_reference)
+                .and_then(|light| self.rmap.get(&light));
+
+            if let Some(topic) = manual {
+                return Some((topic.clone(), Some(node)));
+            }
+
+            // areas can span several networks, each with their own proxy
+            log::info!(
+                "[{}] Manual proxy node {} is not on this network, choosing one automatically",
+                self.name,
+                node.rid
+            );
+        }
+
+        let ieee = |topic: &str| {
+            self.network
+                .get(topic)
+                .map(|dev| dev.ieee_address.to_string())
+        };
+        let members: Vec<String> = targets.iter().filter_map(|topic| ieee(topic)).collect();
+        let members: Vec<&str> = members.iter().map(String::as_str).collect();
+
+        // max_by_key() picks the last of equal elements, so search backwards
+        let topic = targets.iter().rev().max_by_key(|topic| {
+            let router = self
+                .network
+                .get(topic.as_str())
+                .is_some_and(|dev| matches!(dev.device_type, DeviceType::Router));
+            let rank = ieee(topic).map(|addr| self.topology.proxy_rank(&addr, &members));
+            let lqi = self
+                .linkquality
+                .get(topic.as_str())
+                .copied()
+                .unwrap_or_default();
+            (router, rank, lqi)
+        })?;
+
+        Some(((*topic).clone(), ent_service(topic)))
+    }
+
     async fn backend_entertainment_frame(
         &mut self,
         z2mws: &mut Z2mWebSocket,
@@ -469,22 +764,191 @@ impl Z2mBackend {
     }
 
     async fn backend_zigbee_device_discovery(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        rlink: &ResourceLink,
+        zbd: &ZigbeeDeviceDiscoveryUpdate,
+    ) -> ApiResult<()> {
+        let search_codes = zbd.action.search_codes.clone().unwrap_or_default();
+        let discovery = Discovery::new(*rlink, search_codes);
+
+        discovery.update_resource(
+            &mut *self.state.lock().await,
+            ZigbeeDeviceDiscoveryStatus::Active,
+        )?;
+
+        z2mws.send_permit_join(Self::DISCOVERY_TIME, None).await?;
+
+        // lights paired to another bridge will not join on their own, so
+        // searching for them means resetting them over touchlink
+        if !discovery.search_codes.is_empty() {
+            log::info!(
+                "[{}] Searching for devices {:?} using touchlink",
+                self.name,
+                discovery.search_codes
+            );
+            z2mws.send_touchlink_scan().await?;
+        }
+
+        self.discovery = Some(discovery);
+
+        Ok(())
+    }
+
+    async fn backend_zcl_read(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+        req: &ZclAttrRead,
+        reply: Option<&BackendReply>,
+    ) -> ApiResult<()> {
+        let Some(topic) = self.rmap.get(link) else {
+            // answer right away, instead of leaving the reader waiting
+            if let Some(reply) = reply {
+                let _ = reply.send(Err(HueError::NotFound(link.rid).into()));
+            }
+            return Ok(());
+        };
+
+        // forget about readers that have given up waiting
+        for queue in self.zcl_reads.values_mut() {
+            queue.retain(|pending| !pending.reply.is_closed());
+        }
+        self.zcl_reads.retain(|_, queue| !queue.is_empty());
+
+        log::debug!(
+            "[{}] Reading zcl attributes {:04x?} from cluster {:04x} on {topic}",
+            self.name,
+            req.attributes,
+            req.cluster
+        );
+
+        let z2mreq = Z2mRequest::Raw(zclcommand::zcl_read(req));
+        z2mws.send(topic, &z2mreq).await?;
+
+        // z2m answers reads on the same device in order, so results are
+        // matched to readers first-in, first-out
+        if let Some(reply) = reply {
+            let pending = PendingZclRead {
+                reply: reply.clone(),
+                types: req.types.clone(),
+            };
+            self.zcl_reads
+                .entry(topic.clone())
+                .or_default()
+                .push_back(pending);
+        }
+
+        Ok(())
+    }
+
+    async fn backend_zcl_write(
+        &self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+        req: &ZclAttrWrite,
+    ) -> ApiResult<()> {
+        let Some(topic) = self.rmap.get(link) else {
+            return Ok(());
+        };
+
+        log::debug!(
+            "[{}] Writing zcl attributes {:04x?} to cluster {:04x} on {topic}",
+            self.name,
+            req.attributes,
+            req.cluster
+        );
+
+        let z2mreq = Z2mRequest::Raw(zclcommand::zcl_write(req));
+        z2mws.send(topic, &z2mreq).await
+    }
+
+    async fn backend_device_rename(
         &self,
         z2mws: &mut Z2mWebSocket,
-        _rlink: &ResourceLink,
-        _zbd: &ZigbeeDeviceDiscoveryUpdate,
+        link: &ResourceLink,
+        name: &str,
     ) -> ApiResult<()> {
-        z2mws.send_permit_join(60 * 4, None).await
+        if !self.server.rename_devices.unwrap_or_default() {
+            return Ok(());
+        }
+
+        let Some(topic) = self.rmap.get(link) else {
+            return Ok(());
+        };
+
+        if topic == name {
+            return Ok(());
+        }
+
+        log::info!("[{}] Renaming device {topic:?} to {name:?}", self.name);
+
+        // the topic maps are updated when z2m confirms the rename
+        z2mws
+            .send_device_rename(topic.clone(), name.to_string())
+            .await
+    }
+
+    async fn backend_device_software_check(
+        &self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+    ) -> ApiResult<()> {
+        let Some(topic) = self.rmap.get(link) else {
+            return Ok(());
+        };
+
+        log::info!("[{}] Checking for software updates on {topic:?}", self.name);
+
+        // z2m publishes the result in the device state, as an update state
+        z2mws.send_device_ota_check(topic.clone()).await
+    }
+
+    async fn backend_device_software_update(
+        &self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+        upd: &DeviceSoftwareUpdateUpdate,
+    ) -> ApiResult<()> {
+        if upd.install != Some(true) {
+            return Ok(());
+        }
+
+        let Some(topic) = self.rmap.get(link) else {
+            return Ok(());
+        };
+
+        let mut lock = self.state.lock().await;
+        let swupd: &DeviceSoftwareUpdate = lock.get(link)?;
+        if swupd.state != DeviceSoftwareUpdateState::ReadyToInstall {
+            log::warn!(
+                "[{}] No software update ready to install on {topic:?}",
+                self.name
+            );
+            return Ok(());
+        }
+
+        log::info!("[{}] Installing software update on {topic:?}", self.name);
+
+        // z2m reports progress (and the final result) in the device state
+        lock.update(&link.rid, |swupd: &mut DeviceSoftwareUpdate| {
+            swupd.state = DeviceSoftwareUpdateState::Installing;
+            swupd.progress = Some(0);
+        })?;
+        drop(lock);
+
+        z2mws.send_device_ota_update(topic.clone()).await
     }
 
     pub async fn handle_backend_event(
         &mut self,
         z2mws: &mut Z2mWebSocket,
-        req: Arc<BackendRequest>,
+        req: &BackendRequest,
+        reply: Option<&BackendReply>,
     ) -> ApiResult<()> {
         self.learner.cleanup();
 
-        match &*req {
+        match req {
             BackendRequest::LightUpdate(link, upd) => {
                 self.backend_light_update(z2mws, link, upd).await
             }
@@ -507,6 +971,18 @@ impl Z2mBackend {
 
             BackendRequest::Delete(link) => self.backend_delete(z2mws, link).await,
 
+            BackendRequest::DeviceRename(link, name) => {
+                self.backend_device_rename(z2mws, link, name).await
+            }
+
+            BackendRequest::DeviceSoftwareUpdate(link, upd) => {
+                self.backend_device_software_update(z2mws, link, upd).await
+            }
+
+            BackendRequest::DeviceSoftwareCheck(link) => {
+                self.backend_device_software_check(z2mws, link).await
+            }
+
             BackendRequest::EntertainmentStart(ent_id) => {
                 self.backend_entertainment_start(z2mws, ent_id).await
             }
@@ -521,6 +997,211 @@ impl Z2mBackend {
                 self.backend_zigbee_device_discovery(z2mws, rlink, zbd)
                     .await
             }
+
+            BackendRequest::ZclAttrRead(link, read) => {
+                self.backend_zcl_read(z2mws, link, read, reply).await
+            }
+
+            BackendRequest::ZclAttrWrite(link, write) => {
+                self.backend_zcl_write(z2mws, link, write).await
+            }
+
+            BackendRequest::BackendInfo(_name) => {
+                if let Some(reply) = reply {
+                    let _ = reply.send(Ok(BackendResponse::BackendInfo(Box::new(
+                        self.backend_info(),
+                    ))));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hue::api::{
+        Entertainment, EntertainmentConfiguration, EntertainmentConfigurationStreamProxyMode,
+        RType, Resource, ResourceLink,
+    };
+    use serde_json::json;
+    use z2m::api::NetworkMapRaw;
+
+    use crate::backend::z2m::Z2mBackend;
+    use crate::backend::z2m::tests::{backend, config, device};
+    use crate::backend::z2m::topology::Topology;
+    use crate::model::state::State;
+    use crate::resource::Resources;
+
+    fn area(
+        mode: EntertainmentConfigurationStreamProxyMode,
+        node: ResourceLink,
+    ) -> EntertainmentConfiguration {
+        serde_json::from_value(json!({
+            "name": "area",
+            "configuration_type": "screen",
+            "metadata": {"name": "area"},
+            "status": "inactive",
+            "stream_proxy": {"mode": mode, "node": node},
+            "locations": {"service_locations": []},
+            "light_services": [],
+            "channels": [],
+        }))
+        .unwrap()
+    }
+
+    fn auto() -> EntertainmentConfiguration {
+        area(
+            EntertainmentConfigurationStreamProxyMode::Auto,
+            RType::Entertainment.deterministic("none"),
+        )
+    }
+
+    /// Backend with a network of (name, device type, link quality)
+    fn network(devices: &[(&str, &str, Option<u8>)]) -> Z2mBackend {
+        let mut z2m = backend(config());
+        for (nwk, (name, device_type, lqi)) in (1..).zip(devices) {
+            let dev = device(
+                name,
+                u64::from(nwk),
+                nwk,
+                "Signify Netherlands B.V.",
+                device_type,
+            );
+            z2m.network.insert((*name).to_string(), dev);
+            if let Some(lqi) = lqi {
+                z2m.linkquality.insert((*name).to_string(), *lqi);
+            }
         }
+        z2m
+    }
+
+    fn resources() -> Resources {
+        Resources::new(hue::version::SwVersion::new(1, String::new()), State::new())
+    }
+
+    fn topics(names: &[&str]) -> Vec<String> {
+        names.iter().map(ToString::to_string).collect()
+    }
+
+    fn choose(
+        z2m: &Z2mBackend,
+        res: &Resources,
+        ent: &EntertainmentConfiguration,
+        names: &[&str],
+    ) -> Option<String> {
+        let targets = topics(names);
+        let targets: Vec<&String> = targets.iter().collect();
+        z2m.choose_proxy(res, ent, &targets).map(|(topic, _)| topic)
+    }
+
+    #[test]
+    fn proxy_without_targets() {
+        let z2m = network(&[]);
+        assert_eq!(choose(&z2m, &resources(), &auto(), &[]), None);
+    }
+
+    #[test]
+    fn proxy_prefers_routers() {
+        let z2m = network(&[
+            ("end", "EndDevice", Some(255)),
+            ("weak", "Router", Some(100)),
+            ("strong", "Router", Some(200)),
+        ]);
+
+        let topic = choose(&z2m, &resources(), &auto(), &["end", "weak", "strong"]);
+        assert_eq!(topic.as_deref(), Some("strong"));
+    }
+
+    #[test]
+    fn proxy_prefers_network_map() {
+        let mut z2m = network(&[
+            ("loud", "Router", Some(255)),
+            ("central", "Router", Some(100)),
+            ("c", "Router", None),
+        ]);
+
+        // "central" has direct links to both other lights, "loud" only to
+        // "central", despite its better link quality to the coordinator
+        let link = |source: u64, target: u64, lqi: u8| {
+            json!({
+                "source": {"ieeeAddr": format!("0x{source:016x}")},
+                "target": {"ieeeAddr": format!("0x{target:016x}")},
+                "lqi": lqi,
+                "depth": 1,
+            })
+        };
+        let map: NetworkMapRaw = serde_json::from_value(json!({
+            "nodes": [],
+            "links": [link(2, 1, 80), link(2, 3, 80), link(1, 0, 255)],
+        }))
+        .unwrap();
+        z2m.topology = Topology::new(&map);
+
+        let topic = choose(&z2m, &resources(), &auto(), &["loud", "central", "c"]);
+        assert_eq!(topic.as_deref(), Some("central"));
+    }
+
+    #[test]
+    fn proxy_ties_pick_first_target() {
+        let z2m = network(&[
+            ("a", "Router", None),
+            ("b", "Router", None),
+            ("c", "Router", None),
+        ]);
+
+        let topic = choose(&z2m, &resources(), &auto(), &["b", "a", "c"]);
+        assert_eq!(topic.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn proxy_without_entertainment_service() {
+        // lights that are not (yet) known as resources are still usable
+        let z2m = network(&[("a", "Router", Some(10))]);
+        let targets = topics(&["a"]);
+        let targets: Vec<&String> = targets.iter().collect();
+
+        let proxy = z2m.choose_proxy(&resources(), &auto(), &targets);
+        assert_eq!(proxy, Some(("a".to_string(), None)));
+    }
+
+    #[test]
+    fn proxy_manual_node() {
+        let mut z2m = network(&[("auto", "Router", Some(255)), ("manual", "EndDevice", None)]);
+        let mut res = resources();
+
+        let link_light = RType::Light.deterministic("manual");
+        let link_enttm = RType::Entertainment.deterministic("manual");
+        let enttm: Entertainment = serde_json::from_value(json!({
+            "equalizer": true,
+            "owner": RType::Device.deterministic("manual"),
+            "proxy": true,
+            "renderer": true,
+            "renderer_reference": link_light,
+        }))
+        .unwrap();
+        res.add(&link_enttm, Resource::Entertainment(enttm))
+            .unwrap();
+        z2m.rmap.insert(link_light, "manual".to_string());
+
+        let ent = area(
+            EntertainmentConfigurationStreamProxyMode::Manual,
+            link_enttm,
+        );
+        let targets = topics(&["auto", "manual"]);
+        let targets: Vec<&String> = targets.iter().collect();
+
+        let proxy = z2m.choose_proxy(&res, &ent, &targets);
+        assert_eq!(proxy, Some(("manual".to_string(), Some(link_enttm))));
+
+        // a manual node on another network falls back to auto selection
+        let other = area(
+            EntertainmentConfigurationStreamProxyMode::Manual,
+            RType::Entertainment.deterministic("other"),
+        );
+        assert_eq!(
+            choose(&z2m, &res, &other, &["auto", "manual"]).as_deref(),
+            Some("auto")
+        );
     }
 }
@@ -1,16 +1,30 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
 use serde::Deserialize;
 use serde_json::Value;
 use tokio_tungstenite::tungstenite;
 use uuid::Uuid;
 
-use hue::api::{DimmingUpdate, GroupedLight, Light, LightUpdate, RType, Resource, Room};
+use hue::api::{
+    Button, Device, DeviceSoftwareUpdate, DeviceSoftwareUpdateState, GroupedLight, Light,
+    LightUpdate, RType, Resource, ResourceLink, Room, ZigbeeDeviceDiscoveryStatus,
+};
 use z2m::api::{
-    BridgeDevices, DeviceRemoveResponse, GroupMemberChange, Message, RawMessage, Response,
+    BridgeDevices, BridgeEvent, BridgeInfo, DeviceRemoveResponse, DeviceRename, GroupMemberChange,
+    Message, NetworkMap, NetworkMapRaw, PermitJoinResponse, RawMessage, Response, TouchlinkScan,
 };
-use z2m::update::DeviceUpdate;
+use z2m::update::{DeviceUpdate, OtaStatus};
 
-use crate::backend::z2m::Z2mBackend;
+use bifrost_api::backend::BackendResponse;
+
+use crate::backend::z2m::discovery::Discovery;
+use crate::backend::z2m::info::network_info;
+use crate::backend::z2m::topology::Topology;
+use crate::backend::z2m::zclcommand::ZCL_READ_PROPERTY;
+use crate::backend::z2m::{DelayedMessage, Z2mBackend};
 use crate::error::{ApiError, ApiResult};
+use crate::resource::Resources;
 
 impl Z2mBackend {
     async fn handle_update_light(&mut self, uuid: &Uuid, devupd: &DeviceUpdate) -> ApiResult<()> {
@@ -19,6 +33,16 @@ impl Z2mBackend {
         let mut lock = self.state.lock().await;
         lock.update::<Light>(uuid, |light| *light += &upd)?;
 
+        if let Some(ota) = &devupd.update {
+            let owner = lock.get_id::<Light>(*uuid)?.owner;
+            let swupd = lock
+                .get::<Device>(&owner)?
+                .service(RType::DeviceSoftwareUpdate);
+            if let Some(swupd) = swupd.copied() {
+                self.handle_update_ota(&mut lock, &owner, &swupd.rid, ota)?;
+            }
+        }
+
         self.learner.learn(uuid, &lock, devupd)?;
         self.learner.collect(&mut lock)?;
         drop(lock);
@@ -26,19 +50,62 @@ impl Z2mBackend {
         Ok(())
     }
 
-    async fn handle_update_grouped_light(&self, uuid: &Uuid, upd: &DeviceUpdate) -> ApiResult<()> {
-        let mut res = self.state.lock().await;
-        res.update::<GroupedLight>(uuid, |glight| {
-            if let Some(state) = &upd.state {
-                glight.on = Some((*state).into());
+    /// Round install progress (in percent) down to a multiple of
+    /// [`Self::OTA_PROGRESS_STEP`]
+    const fn ota_progress_step(progress: f64) -> u8 {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let percent = progress.clamp(0.0, 100.0) as u8;
+        percent - percent % Self::OTA_PROGRESS_STEP
+    }
+
+    fn handle_update_ota(
+        &self,
+        res: &mut Resources,
+        device: &ResourceLink,
+        uuid: &Uuid,
+        ota: &OtaStatus,
+    ) -> ApiResult<()> {
+        // the installed version changes once an update is finished
+        if let Some(version) = ota.software_version() {
+            if res.get::<Device>(device)?.product_data.software_version != version {
+                log::info!(
+                    "[{}] Software version of {device:?} is now {version}",
+                    self.name
+                );
+                res.update(&device.rid, |dev: &mut Device| {
+                    dev.product_data.software_version = version;
+                })?;
             }
+        }
 
-            if let Some(b) = upd.brightness {
-                glight.dimming = Some(DimmingUpdate {
-                    brightness: b / 254.0 * 100.0,
-                });
+        let Some(state) = ota.state.map(DeviceSoftwareUpdateState::from) else {
+            return Ok(());
+        };
+
+        let progress = if state == DeviceSoftwareUpdateState::Installing {
+            if let Some(progress) = ota.progress {
+                log::info!(
+                    "[{}] Software update in progress: {progress:.1}% ({:.0}s remaining)",
+                    self.name,
+                    ota.remaining.unwrap_or_default()
+                );
             }
-        })
+            Some(Self::ota_progress_step(ota.progress.unwrap_or_default()))
+        } else {
+            None
+        };
+
+        // only update on actual changes (and progress in coarse steps), to
+        // avoid flooding clients with events
+        let swupd: &DeviceSoftwareUpdate = res.get_id(*uuid)?;
+        if swupd.state != state || swupd.progress != progress {
+            res.update(uuid, |swupd: &mut DeviceSoftwareUpdate| {
+                swupd.state = state;
+                swupd.progress = progress;
+            })?;
+        }
+
+        Ok(())
     }
 
     async fn handle_update(&mut self, rid: &Uuid, payload: &Value) -> ApiResult<()> {
@@ -51,24 +118,52 @@ impl Z2mBackend {
 
         let upd = DeviceUpdate::deserialize(payload)?;
 
+        // grouped lights are aggregated from the state of their member
+        // lights, so only light updates are needed here
         let obj = self.state.lock().await.get_resource_by_id(rid)?.obj;
-        match obj {
-            Resource::Light(_) => {
-                if let Err(e) = self.handle_update_light(rid, &upd).await {
-                    log::error!("FAIL: {e:?} in {upd:?}");
-                }
-            }
-            Resource::GroupedLight(_) => {
-                if let Err(e) = self.handle_update_grouped_light(rid, &upd).await {
-                    log::error!("FAIL: {e:?} in {upd:?}");
-                }
+        if let Resource::Light(_) = obj {
+            if let Err(e) = self.handle_update_light(rid, &upd).await {
+                log::error!("FAIL: {e:?} in {upd:?}");
             }
-            _ => {}
         }
 
         Ok(())
     }
 
+    /// Answer the oldest pending zcl attribute read on `topic`, if its result
+    /// is included in the device state published by z2m.
+    ///
+    /// z2m keeps the last result in the device state, so an unrelated state
+    /// update arriving while a read is in flight still carries the previous
+    /// result. There is no way to tell these apart, so such a read is
+    /// answered with the previous result.
+    fn handle_zcl_reads(&mut self, topic: &str, payload: &Value) {
+        let Some(result) = payload.get(ZCL_READ_PROPERTY) else {
+            return;
+        };
+
+        let Some(queue) = self.zcl_reads.get_mut(topic) else {
+            return;
+        };
+
+        // readers that have given up waiting have no result to receive
+        while let Some(pending) = queue.pop_front() {
+            if pending.reply.is_closed() {
+                continue;
+            }
+
+            let attrs = pending.result(result);
+            let _ = pending
+                .reply
+                .send(Ok(BackendResponse::ZclAttributes(attrs)));
+            break;
+        }
+
+        if queue.is_empty() {
+            self.zcl_reads.remove(topic);
+        }
+    }
+
     async fn handle_device_message(&mut self, msg: RawMessage) -> ApiResult<()> {
         if msg.topic.ends_with("/availability") || msg.topic.ends_with("/action") {
             // availability: https://www.zigbee2mqtt.io/guide/usage/mqtt_topics_and_messages.html#zigbee2mqtt-friendly-name-availability
@@ -87,6 +182,14 @@ impl Z2mBackend {
             return Ok(());
         };
 
+        self.handle_zcl_reads(&msg.topic, &msg.payload);
+
+        // remember link quality, for choosing entertainment proxy nodes
+        if let Some(lqi) = msg.payload.get("linkquality").and_then(Value::as_u64) {
+            let lqi = u8::try_from(lqi).unwrap_or(u8::MAX);
+            self.linkquality.insert(msg.topic.clone(), lqi);
+        }
+
         let res = self.handle_update(&val.rid, &msg.payload).await;
         if let Err(ref err) = res {
             log::error!(
@@ -99,10 +202,91 @@ impl Z2mBackend {
         Ok(())
     }
 
+    /// Remove all resources for z2m topics of type `rtype` that are not in
+    /// `known`.
+    ///
+    /// z2m always sends the complete list of devices and groups (including
+    /// right after we (re)connect), so anything missing from it has been
+    /// removed while we were not looking.
+    async fn prune_topics(&mut self, rtype: RType, known: &HashSet<&str>) -> ApiResult<()> {
+        let stale: Vec<String> = self
+            .map
+            .iter()
+            .filter(|(topic, link)| link.rtype == rtype && !known.contains(topic.as_str()))
+            .map(|(topic, _)| topic.clone())
+            .collect();
+
+        for topic in stale {
+            log::info!("[{}] Pruning {rtype:?} for stale topic {topic}", self.name);
+            self.remove_topic(&topic).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn remove_topic(&mut self, topic: &str) -> ApiResult<()> {
+        if let Some(rlink) = self.map.get(topic) {
+            let mut lock = self.state.lock().await;
+            match rlink.rtype {
+                RType::Light => {
+                    let owner = lock.get::<Light>(rlink)?.owner;
+                    log::info!("Removing device: {owner:?}");
+                    lock.delete(&owner)?;
+                }
+                RType::Button => {
+                    let owner = lock.get::<Button>(rlink)?.owner;
+                    log::info!("Removing device: {owner:?}");
+                    lock.delete(&owner)?;
+                }
+                RType::GroupedLight => {
+                    let owner = lock.get::<GroupedLight>(rlink)?.owner;
+                    log::info!("Removing room: {owner:?}");
+                    lock.delete(&owner)?;
+                }
+                rtype => {
+                    log::warn!("Cannot handle removing resource of type {rtype:?}");
+                }
+            }
+            drop(lock);
+        }
+
+        if self.map.remove(topic).is_some() {
+            self.rmap.retain(|_, v| v != topic);
+        }
+
+        Ok(())
+    }
+
     async fn bridge_devices(&mut self, devices: &BridgeDevices) -> ApiResult<()> {
+        let hidden: HashSet<&str> = devices
+            .iter()
+            .filter(|dev| {
+                self.config
+                    .is_hidden(&dev.friendly_name, &dev.ieee_address.to_string())
+            })
+            .map(|dev| dev.friendly_name.as_str())
+            .collect();
+
+        // hidden devices are pruned as well, in case they were imported
+        // before being hidden in the config
+        let known = devices
+            .iter()
+            .map(|dev| dev.friendly_name.as_str())
+            .filter(|name| !hidden.contains(name))
+            .collect();
+        for rtype in [RType::Light, RType::Button] {
+            self.prune_topics(rtype, &known).await?;
+        }
+
+        self.network.clear();
+        self.ignore.clear();
+
         for dev in devices {
             self.network.insert(dev.friendly_name.clone(), dev.clone());
-            if let Some(exp) = dev.expose_light() {
+            if hidden.contains(dev.friendly_name.as_str()) {
+                log::debug!("[{}] Hiding device {}", self.name, dev.friendly_name);
+                self.ignore.insert(dev.friendly_name.to_string());
+            } else if let Some(exp) = dev.expose_light() {
                 log::info!(
                     "[{}] Adding light {:?}: [{}] ({})",
                     self.name,
@@ -133,31 +317,214 @@ impl Z2mBackend {
             */
         }
 
-        Ok(())
+        // devices imported earlier keep their connectivity resource as-is
+        self.apply_zigbee_channel(&mut *self.state.lock().await)
     }
 
     async fn bridge_device_remove(&mut self, data: &DeviceRemoveResponse) -> ApiResult<()> {
-        if let Some(rlink) = self.map.get(&data.id) {
-            match rlink.rtype {
-                RType::Light => {
-                    let mut lock = self.state.lock().await;
-                    let owner = lock.get::<Light>(rlink)?.owner;
-                    log::info!("Removing device: {owner:?}");
-                    lock.delete(&owner)?;
-                }
-                rtype => {
-                    log::warn!("Cannot handle removing resource of type {rtype:?}");
-                }
-            }
+        self.remove_topic(&data.id).await
+    }
+
+    /// Move everything known about topic `from` to topic `to`, so a renamed
+    /// device can still be controlled.
+    fn bridge_device_rename(&mut self, data: &DeviceRename) {
+        let DeviceRename { from, to, .. } = data;
+
+        log::info!("[{}] Device {from:?} renamed to {to:?}", self.name);
+
+        if let Some(link) = self.map.remove(from) {
+            self.map.insert(to.clone(), link);
+        }
+
+        for topic in self.rmap.values_mut().filter(|topic| *topic == from) {
+            topic.clone_from(to);
+        }
+
+        if let Some(dev) = self.network.remove(from) {
+            self.network.insert(to.clone(), dev);
+        }
+
+        if let Some(lqi) = self.linkquality.remove(from) {
+            self.linkquality.insert(to.clone(), lqi);
+        }
+
+        if self.ignore.remove(from) {
+            self.ignore.insert(to.clone());
+        }
+    }
+
+    async fn discovery_finish(&mut self) -> ApiResult<()> {
+        let Some(discovery) = self.discovery.take() else {
+            return Ok(());
+        };
+
+        if discovery.joined.is_empty() {
+            log::info!(
+                "[{}] Device discovery finished, no devices joined",
+                self.name
+            );
+        } else {
+            log::info!(
+                "[{}] Device discovery finished, devices joined: {:?}",
+                self.name,
+                discovery.joined
+            );
+        }
+
+        discovery.update_resource(
+            &mut *self.state.lock().await,
+            ZigbeeDeviceDiscoveryStatus::Ready,
+        )
+    }
+
+    async fn bridge_info(&mut self, info: &BridgeInfo) -> ApiResult<()> {
+        self.info = Some(Box::new(info.clone()));
+
+        if let Some(network) = network_info(&info.network) {
+            log::debug!(
+                "[{}] Zigbee network on channel {}, pan id {:04x}",
+                self.name,
+                network.channel,
+                network.pan_id,
+            );
+            let mut lock = self.state.lock().await;
+            lock.set_zigbee_network(network.channel, network.extended_pan_id)?;
+            self.apply_zigbee_channel(&mut lock)?;
+            drop(lock);
         }
 
-        if let Some(_rlink) = self.map.remove(&data.id) {
-            self.rmap.retain(|_, v| *v != data.id);
+        self.bridge_info_discovery(info).await
+    }
+
+    async fn bridge_info_discovery(&mut self, info: &BridgeInfo) -> ApiResult<()> {
+        if !self.discovery.as_ref().is_some_and(|disc| disc.active) {
+            return Ok(());
+        }
+
+        if !info.permit_join {
+            return self.discovery_finish().await;
+        }
+
+        let remaining = info
+            .permit_join_end
+            .map(|end| (end - Utc::now().timestamp_millis()) / 1000)
+            .or_else(|| info.permit_join_timeout.map(i64::from));
+
+        if let Some(remaining) = remaining {
+            log::debug!("[{}] Device discovery: {remaining}s left", self.name);
         }
 
         Ok(())
     }
 
+    async fn bridge_permit_join(&mut self, resp: &Response<PermitJoinResponse>) -> ApiResult<()> {
+        let Some(discovery) = &mut self.discovery else {
+            return Ok(());
+        };
+
+        match resp {
+            Response::Ok { data, .. } if data.time.unwrap_or_default() > 0 => {
+                log::info!(
+                    "[{}] Permitting devices to join for {}s",
+                    self.name,
+                    data.time.unwrap_or_default()
+                );
+                discovery.active = true;
+                Ok(())
+            }
+            Response::Ok { .. } => self.discovery_finish().await,
+            Response::Error { error, .. } => {
+                log::error!("[{}] Failed to permit joining: {error}", self.name);
+                self.discovery_finish().await
+            }
+        }
+    }
+
+    fn bridge_event(&mut self, event: &BridgeEvent) {
+        if event.event_type != "device_joined" {
+            return;
+        }
+
+        let Some(discovery) = &mut self.discovery else {
+            return;
+        };
+
+        let name = event
+            .data
+            .get("friendly_name")
+            .and_then(Value::as_str)
+            .unwrap_or("<unknown>");
+
+        log::info!("[{}] Device {name:?} joined during discovery", self.name);
+        discovery.joined.push(name.to_string());
+    }
+
+    /// Queue the next touchlink request of the current discovery, if any
+    fn touchlink_next(&mut self) {
+        if let Some(step) = self.discovery.as_mut().and_then(Discovery::next_step) {
+            let _ = self
+                .message_tx
+                .send((String::new(), DelayedMessage::Touchlink(step)));
+        }
+    }
+
+    fn bridge_touchlink_scan(&mut self, resp: &Response<TouchlinkScan>) {
+        let Some(discovery) = &mut self.discovery else {
+            return;
+        };
+
+        match resp {
+            Response::Ok { data, .. } => {
+                log::info!(
+                    "[{}] Touchlink scan found {} devices: {:?}",
+                    self.name,
+                    data.found.len(),
+                    data.found
+                );
+                discovery.plan_touchlink(&data.found);
+                self.touchlink_next();
+            }
+            Response::Error { error, .. } => {
+                log::warn!("[{}] Touchlink scan failed: {error}", self.name);
+            }
+        }
+    }
+
+    fn bridge_networkmap(&mut self, resp: &Response<NetworkMap>) {
+        let map = match resp {
+            Response::Ok { data, .. } if data.map_type == "raw" => data,
+            // other formats are only requested by other z2m clients
+            Response::Ok { .. } => return,
+            Response::Error { error, .. } => {
+                log::warn!("[{}] Network map request failed: {error}", self.name);
+                return;
+            }
+        };
+
+        match NetworkMapRaw::deserialize(&map.value) {
+            Ok(raw) => {
+                log::debug!(
+                    "[{}] Network map received: {} nodes, {} links",
+                    self.name,
+                    raw.nodes.len(),
+                    raw.links.len()
+                );
+                self.topology = Topology::new(&raw);
+            }
+            Err(err) => log::warn!("[{}] Invalid network map: {err}", self.name),
+        }
+    }
+
+    fn bridge_touchlink_response(&mut self, op: &str, resp: &Response<Value>) {
+        if let Response::Error { error, .. } = resp {
+            log::warn!("[{}] Touchlink {op} failed: {error}", self.name);
+        } else {
+            log::info!("[{}] Touchlink {op} done", self.name);
+        }
+
+        self.touchlink_next();
+    }
+
     #[allow(clippy::collapsible_else_if)]
     async fn bridge_group_member_change(
         &self,
@@ -198,20 +565,38 @@ impl Z2mBackend {
     async fn handle_bridge_message(&mut self, msg: Message) -> ApiResult<()> {
         #[allow(unused_variables)]
         match &msg {
-            Message::BridgeInfo(obj) => { /* println!("{obj:#?}"); */ }
-            Message::BridgeHealth(_obj) => { /* println!("{obj:#?}"); */ }
+            Message::BridgeInfo(obj) => self.bridge_info(obj).await?,
+            Message::BridgeHealth(obj) => self.health = Some(obj.clone()),
             Message::BridgeLogging(obj) => { /* println!("{obj:#?}"); */ }
             Message::BridgeExtensions(obj) => { /* println!("{obj:#?}"); */ }
-            Message::BridgeEvent(obj) => { /* println!("{obj:#?}"); */ }
+            Message::BridgeEvent(obj) => self.bridge_event(obj),
             Message::BridgeDefinitions(obj) => { /* println!("{obj:#?}"); */ }
             Message::BridgeState(obj) => { /* println!("{obj:#?}"); */ }
             Message::BridgeConverters(obj) => { /* println!("{obj:#?}"); */ }
             Message::BridgeOptions(obj) => { /* println!("{obj:#?}"); */ }
-            Message::BridgePermitJoin(obj) => {}
-            Message::BridgeTouchlinkScan(obj) => {}
+            Message::BridgePermitJoin(obj) => self.bridge_permit_join(obj).await?,
+            Message::BridgeTouchlinkScan(obj) => self.bridge_touchlink_scan(obj),
+            Message::BridgeTouchlinkIdentify(obj) => {
+                self.bridge_touchlink_response("identify", obj);
+            }
+            Message::BridgeTouchlinkFactoryReset(obj) => {
+                self.bridge_touchlink_response("factory reset", obj);
+            }
             Message::BridgeDeviceOptions(obj) => {}
-            Message::BridgeNetworkmap(obj) => {}
-            Message::BridgeDeviceOtaUpdateCheck(obj) => {}
+            Message::BridgeNetworkmap(obj) => self.bridge_networkmap(obj),
+            Message::BridgeDeviceOtaUpdateCheck(obj) => match obj {
+                Response::Ok { data, .. } => {
+                    log::info!(
+                        "[{}] Software update check of {}: update available: {}",
+                        self.name,
+                        data.id,
+                        data.update_available
+                    );
+                }
+                Response::Error { error, .. } => {
+                    log::warn!("[{}] Software update check failed: {error}", self.name);
+                }
+            },
             Message::BridgeDeviceConfigureReporting(obj) => {}
             Message::BridgeConfig(obj) => {}
             Message::BridgeResponseGroupAdd(obj) => {}
@@ -225,6 +610,9 @@ impl Z2mBackend {
 
             Message::BridgeGroups(obj) => {
                 /* println!("{obj:#?}"); */
+                let known = obj.iter().map(|grp| grp.friendly_name.as_str()).collect();
+                self.prune_topics(RType::GroupedLight, &known).await?;
+
                 for grp in obj {
                     self.add_group(grp).await?;
                 }
@@ -240,6 +628,26 @@ impl Z2mBackend {
                 self.bridge_group_member_change(change, added).await?;
             }
 
+            Message::BridgeDeviceRename(obj) => {
+                let Response::Ok { data, .. } = obj else {
+                    log::warn!("[{}] Error reported from z2m: {obj:?}", self.name);
+                    return Ok(());
+                };
+
+                self.bridge_device_rename(data);
+            }
+
+            Message::BridgeDeviceOtaUpdate(obj) => match obj {
+                Response::Ok { data, .. } => {
+                    log::info!("[{}] Software update of {} finished", self.name, data.id);
+                }
+                Response::Error { error, .. } => {
+                    // z2m publishes the resulting update state on the device
+                    // topic, so there is nothing to roll back here
+                    log::error!("[{}] Software update failed: {error}", self.name);
+                }
+            },
+
             Message::BridgeDeviceRemove(obj) => {
                 let Response::Ok { data, .. } = obj else {
                     log::warn!("[{}] Error reported from z2m: {obj:?}", self.name);
@@ -306,3 +714,201 @@ impl Z2mBackend {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    use bifrost_api::backend::BackendResponse;
+    use hue::api::{
+        Device, DeviceArchetype, DeviceProductData, DeviceSoftwareUpdate,
+        DeviceSoftwareUpdateState, Metadata, RType, Resource,
+    };
+    use hue::version::SwVersion;
+    use maplit::btreeset;
+    use serde_json::Value;
+    use tokio::runtime::Builder;
+    use z2m::api::DeviceRename;
+    use z2m::update::OtaStatus;
+
+    use crate::backend::z2m::Z2mBackend;
+    use crate::backend::z2m::tests::{backend, config, device};
+    use crate::backend::z2m::zclcommand::PendingZclRead;
+    use crate::config::DeviceConfig;
+    use crate::model::state::State;
+    use crate::resource::Resources;
+
+    #[test]
+    fn ota_progress_steps() {
+        assert_eq!(Z2mBackend::ota_progress_step(0.0), 0);
+        assert_eq!(Z2mBackend::ota_progress_step(19.9), 10);
+        assert_eq!(Z2mBackend::ota_progress_step(100.0), 100);
+        assert_eq!(Z2mBackend::ota_progress_step(-3.0), 0);
+    }
+
+    #[test]
+    fn ota_progress_published() {
+        let z2m = backend(config());
+        let link_device = RType::Device.deterministic("lamp");
+        let link_swupd = RType::DeviceSoftwareUpdate.deterministic("lamp");
+
+        let mut res = Resources::new(SwVersion::new(1, String::new()), State::new());
+        let dev = Device {
+            product_data: DeviceProductData::hue_bridge_v2(&SwVersion::new(1, String::new())),
+            metadata: Metadata::new(DeviceArchetype::SultanBulb, "lamp"),
+            services: btreeset![link_swupd],
+            identify: None,
+            usertest: None,
+        };
+        res.add(&link_device, Resource::Device(dev)).unwrap();
+        res.add(
+            &link_swupd,
+            Resource::DeviceSoftwareUpdate(DeviceSoftwareUpdate::new(link_device)),
+        )
+        .unwrap();
+
+        let mut update = |ota: Value| {
+            let ota: OtaStatus = serde_json::from_value(ota).unwrap();
+            z2m.handle_update_ota(&mut res, &link_device, &link_swupd.rid, &ota)
+                .unwrap();
+            let swupd: &DeviceSoftwareUpdate = res.get(&link_swupd).unwrap();
+            (swupd.state, swupd.progress)
+        };
+
+        assert_eq!(
+            update(json!({"state": "updating", "progress": 47.5})),
+            (DeviceSoftwareUpdateState::Installing, Some(40))
+        );
+        assert_eq!(
+            update(json!({"state": "idle"})),
+            (DeviceSoftwareUpdateState::NoUpdate, None)
+        );
+    }
+
+    #[test]
+    fn zcl_reads_answered_in_order() {
+        let mut z2m = backend(config());
+        let (first, mut first_rx) = mpsc::unbounded_channel();
+        let (second, mut second_rx) = mpsc::unbounded_channel();
+
+        for reply in [first, second] {
+            let pending = PendingZclRead {
+                reply,
+                types: BTreeMap::new(),
+            };
+            z2m.zcl_reads
+                .entry("lamp".to_string())
+                .or_default()
+                .push_back(pending);
+        }
+
+        // state without a read result, and results for other devices, are
+        // not answers
+        z2m.handle_zcl_reads("lamp", &json!({"state": "ON"}));
+        z2m.handle_zcl_reads("other", &json!({"bifrost_zcl": {"1": 1}}));
+        assert!(first_rx.try_recv().is_err());
+
+        z2m.handle_zcl_reads("lamp", &json!({"bifrost_zcl": {"1": 1}}));
+        let Ok(Ok(BackendResponse::ZclAttributes(attrs))) = first_rx.try_recv() else {
+            panic!("first read not answered");
+        };
+        assert!(attrs.contains_key("1"));
+        assert!(second_rx.try_recv().is_err());
+
+        z2m.handle_zcl_reads("lamp", &json!({"bifrost_zcl": {"2": 2}}));
+        let Ok(Ok(BackendResponse::ZclAttributes(attrs))) = second_rx.try_recv() else {
+            panic!("second read not answered");
+        };
+        assert!(attrs.contains_key("2"));
+        assert!(z2m.zcl_reads.is_empty());
+    }
+
+    #[test]
+    fn device_rename_rekeys_topic() {
+        let mut z2m = backend(config());
+        let dev = device("switch", 0x0017_8801_0000_0001, 1, "Signify", "EndDevice");
+
+        Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(z2m.add_switch(&dev))
+            .unwrap();
+        z2m.network.insert("switch".to_string(), dev.clone());
+        z2m.linkquality.insert("switch".to_string(), 120);
+
+        let link_device = RType::Device.deterministic(&dev.ieee_address);
+        let link_button = RType::Button.deterministic(&dev.ieee_address);
+        assert_eq!(z2m.rmap[&link_device], "switch");
+
+        z2m.bridge_device_rename(&DeviceRename {
+            from: "switch".to_string(),
+            to: "hallway".to_string(),
+            homeassistant_rename: false,
+        });
+
+        assert_eq!(z2m.map.get("hallway"), Some(&link_button));
+        assert!(!z2m.map.contains_key("switch"));
+
+        assert_eq!(z2m.rmap[&link_device], "hallway");
+        assert_eq!(z2m.rmap[&link_button], "hallway");
+
+        assert!(z2m.network.contains_key("hallway"));
+        assert!(!z2m.network.contains_key("switch"));
+
+        assert_eq!(z2m.linkquality.get("hallway"), Some(&120));
+        assert!(!z2m.linkquality.contains_key("switch"));
+    }
+
+    #[test]
+    fn device_rename_unknown() {
+        let mut z2m = backend(config());
+        z2m.linkquality.insert("light".to_string(), 200);
+
+        z2m.bridge_device_rename(&DeviceRename {
+            from: "switch".to_string(),
+            to: "hallway".to_string(),
+            homeassistant_rename: false,
+        });
+
+        assert!(z2m.map.is_empty());
+        assert!(z2m.rmap.is_empty());
+        assert_eq!(z2m.linkquality.get("light"), Some(&200));
+    }
+
+    #[test]
+    fn hidden_switch_pruned() {
+        let mut conf = config();
+        conf.devices.insert(
+            "switch".to_string(),
+            DeviceConfig {
+                hidden: Some(true),
+                ..DeviceConfig::default()
+            },
+        );
+
+        let mut z2m = backend(conf);
+        let dev = device("switch", 0x0017_8801_0000_0001, 1, "Signify", "EndDevice");
+        let link_device = RType::Device.deterministic(&dev.ieee_address);
+        let rt = Builder::new_current_thread().build().unwrap();
+
+        rt.block_on(z2m.add_switch(&dev)).unwrap();
+        assert!(
+            rt.block_on(z2m.state.lock())
+                .get_resource(&link_device)
+                .is_ok()
+        );
+
+        rt.block_on(z2m.bridge_devices(&vec![dev])).unwrap();
+
+        assert!(!z2m.map.contains_key("switch"));
+        assert!(z2m.ignore.contains("switch"));
+        assert!(
+            rt.block_on(z2m.state.lock())
+                .get_resource(&link_device)
+                .is_err()
+        );
+    }
+}
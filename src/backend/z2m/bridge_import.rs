@@ -7,23 +7,27 @@ use uuid::Uuid;
 
 use hue::api::{
     BridgeHome, Button, ButtonData, ButtonMetadata, ButtonReport, DeviceArchetype,
-    DeviceProductData, Entertainment, EntertainmentSegment, EntertainmentSegments, GroupedLight,
-    Light, LightEffects, LightEffectsV2, LightMetadata, Metadata, RType, Resource, ResourceLink,
-    Room, RoomArchetype, RoomMetadata, Scene, SceneActive, SceneMetadata, SceneRecall, SceneStatus,
-    Stub, Taurus, ZigbeeConnectivity, ZigbeeConnectivityStatus,
+    DeviceProductData, DeviceSoftwareUpdate, Entertainment, EntertainmentSegment,
+    EntertainmentSegments, GroupedLight, Light, LightEffects, LightEffectsV2, LightMetadata,
+    Metadata, RType, Resource, ResourceLink, Room, RoomArchetype, RoomMetadata, Scene, SceneActive,
+    SceneMetadata, SceneRecall, SceneStatus, Stub, Taurus, ZigbeeConnectivity,
+    ZigbeeConnectivityStatus,
 };
 use hue::scene_icons;
-use z2m::api::ExposeLight;
+use z2m::api::{DeviceType, ExposeLight};
 use z2m::convert::{
     ExtractColorTemperature, ExtractDeviceProductData, ExtractDimming, ExtractLightColor,
-    ExtractLightGradient,
+    ExtractLightEffects, ExtractLightGradient,
 };
 
 use crate::backend::z2m::Z2mBackend;
+use crate::config::DeviceConfig;
 use crate::error::ApiResult;
 use crate::model::state::AuxData;
+use crate::resource::Resources;
 
 impl Z2mBackend {
+    #[allow(clippy::too_many_lines)]
     pub async fn add_light(
         &mut self,
         apidev: &z2m::api::Device,
@@ -36,18 +40,38 @@ impl Z2mBackend {
         let link_enttm = RType::Entertainment.deterministic(&apidev.ieee_address);
         let link_taurus = RType::Taurus.deterministic(&apidev.ieee_address);
         let link_zigcon = RType::ZigbeeConnectivity.deterministic(&apidev.ieee_address);
+        let link_swupd = RType::DeviceSoftwareUpdate.deterministic(&apidev.ieee_address);
+
+        let conf = self
+            .config
+            .device(name, &apidev.ieee_address.to_string())
+            .cloned()
+            .unwrap_or_default();
+        let caps = apidev
+            .product_data()
+            .map(|pd| pd.capabilities)
+            .unwrap_or_default();
+        let entertainment = conf.entertainment.or(caps.entertainment).unwrap_or(true);
 
         let product_data = DeviceProductData::guess_from_device(apidev);
         let metadata = LightMetadata::new(product_data.product_archetype.clone(), name);
 
-        let effects =
+        let hue_light =
             apidev.manufacturer.as_deref() == Some(DeviceProductData::SIGNIFY_MANUFACTURER_NAME);
+        let effects = caps.effects.unwrap_or(hue_light);
+        // only Hue lights acting as routers can relay entertainment streams
+        let proxy = hue_light && matches!(apidev.device_type, DeviceType::Router);
         let gradient = apidev.expose_gradient();
 
+        let mut services = btreeset![link_zigcon, link_light, link_swupd];
+        if entertainment {
+            services.extend([link_enttm, link_taurus]);
+        }
+
         let dev = hue::api::Device {
             product_data,
             metadata: metadata.clone().into(),
-            services: btreeset![link_zigcon, link_light, link_enttm, link_taurus],
+            services,
             identify: Some(Stub),
             usertest: None,
         };
@@ -55,6 +79,7 @@ impl Z2mBackend {
         self.map.insert(name.to_string(), link_light);
         self.rmap.insert(link_device, name.to_string());
         self.rmap.insert(link_light, name.to_string());
+        self.rmap.insert(link_swupd, name.to_string());
 
         let mut light = Light::new(link_device, metadata);
 
@@ -68,19 +93,32 @@ impl Z2mBackend {
             .and_then(ExtractColorTemperature::extract_from_expose);
         log::trace!("Detected color temperature: {:?}", &light.color_temperature);
 
+        // lights with only hue/saturation support still accept xy colors
+        // through z2m, which converts them as needed
         light.color = expose
             .feature("color_xy")
-            .and_then(ExtractLightColor::extract_from_expose);
+            .or_else(|| expose.feature("color_hs"))
+            .and_then(|exp| ExtractLightColor::extract_from_device(apidev, exp));
         log::trace!("Detected color: {:?}", &light.color);
 
         light.gradient = gradient.and_then(ExtractLightGradient::extract_from_expose);
         log::trace!("Detected gradient support: {:?}", &light.gradient);
 
-        if effects {
-            log::trace!("Detected Hue light: enabling effects");
+        let effect = apidev.expose_effect();
+        light.effects = effect.and_then(LightEffects::extract_from_expose);
+        light.effects_v2 = effect.and_then(LightEffectsV2::extract_from_expose);
+
+        // older z2m versions do not list the dynamic effects for hue lights,
+        // even though all of them are supported
+        if effects && light.effects.is_none() {
+            log::trace!("Detected Hue effects support: enabling effects");
             light.effects = Some(LightEffects::all());
             light.effects_v2 = Some(LightEffectsV2::all());
+        } else if caps.effects == Some(false) {
+            light.effects = None;
+            light.effects_v2 = None;
         }
+        log::trace!("Detected effects: {:?}", &light.effects);
 
         let segments = if gradient.is_some() {
             EntertainmentSegments {
@@ -108,7 +146,7 @@ impl Z2mBackend {
         let enttm = Entertainment {
             equalizer: true,
             owner: link_device,
-            proxy: true,
+            proxy,
             renderer: true,
             max_streams: None,
             renderer_reference: Some(link_light),
@@ -128,7 +166,7 @@ impl Z2mBackend {
         };
 
         let zigcon = ZigbeeConnectivity {
-            channel: None,
+            channel: self.zigbee_channel().map(ZigbeeConnectivity::channel_value),
             extended_pan_id: None,
             mac_address: apidev.ieee_address.to_string(),
             owner: link_device,
@@ -136,12 +174,67 @@ impl Z2mBackend {
         };
 
         let mut res = self.state.lock().await;
-        res.aux_set(&link_light, AuxData::new().with_topic(name));
+        res.aux_set(&link_device, AuxData::new().with_backend(&self.name));
+        res.aux_set(
+            &link_light,
+            AuxData::new().with_topic(name).with_backend(&self.name),
+        );
+        res.aux_set(&link_swupd, AuxData::new().with_backend(&self.name));
+
+        // The gamut might have been learned after the light was first
+        // created, so make sure known lights are kept up to date.
+        if let Some(color) = &light.color {
+            if res.get::<Light>(&link_light).is_ok() {
+                res.update(&link_light.rid, |light: &mut Light| {
+                    if let Some(col) = &mut light.color {
+                        col.gamut = color.gamut;
+                        col.gamut_type = color.gamut_type;
+                    }
+                })?;
+            }
+        }
+
+        let services = dev.services.clone();
         res.add(&link_device, Resource::Device(dev))?;
+        // Devices restored from an older state file might predate the
+        // software update service, and entertainment might have been enabled
+        // since the device was added, so make sure every service is linked.
+        let linked = &res.get::<hue::api::Device>(&link_device)?.services;
+        if !linked.is_superset(&services) {
+            res.update(&link_device.rid, |dev: &mut hue::api::Device| {
+                dev.services.extend(services);
+            })?;
+        }
         res.add(&link_light, Resource::Light(light))?;
-        res.add(&link_enttm, Resource::Entertainment(enttm))?;
-        res.add(&link_taurus, Resource::Taurus(taurus))?;
+        if entertainment {
+            res.add(&link_enttm, Resource::Entertainment(enttm))?;
+            res.add(&link_taurus, Resource::Taurus(taurus))?;
+        } else {
+            for link in [link_enttm, link_taurus] {
+                if res.get_resource(&link).is_ok() {
+                    res.delete(&link)?;
+                }
+            }
+        }
         res.add(&link_zigcon, Resource::ZigbeeConnectivity(zigcon))?;
+        if res.get::<DeviceSoftwareUpdate>(&link_swupd).is_err() {
+            res.add(
+                &link_swupd,
+                Resource::DeviceSoftwareUpdate(DeviceSoftwareUpdate::new(link_device)),
+            )?;
+        }
+
+        // Existing resources are kept as-is when the device is imported
+        // again, so overrides are applied as an update. This way, changes to
+        // the config take effect without removing the device.
+        if conf != DeviceConfig::default() {
+            res.update(&link_device.rid, |dev: &mut hue::api::Device| {
+                apply_device_overrides(&conf, dev);
+            })?;
+            res.update(&link_light.rid, |light: &mut Light| {
+                apply_light_overrides(&conf, light);
+            })?;
+        }
         drop(res);
 
         Ok(())
@@ -163,6 +256,7 @@ impl Z2mBackend {
         };
 
         self.map.insert(name.to_string(), link_button);
+        self.rmap.insert(link_device, name.to_string());
         self.rmap.insert(link_button, name.to_string());
 
         let mut res = self.state.lock().await;
@@ -184,10 +278,7 @@ impl Z2mBackend {
             owner: link_device,
             mac_address: String::from("11:22:33:44:55:66:77:89"),
             status: ZigbeeConnectivityStatus::ConnectivityIssue,
-            channel: Some(json!({
-                "status": "set",
-                "value": "channel_25",
-            })),
+            channel: self.zigbee_channel().map(ZigbeeConnectivity::channel_value),
             extended_pan_id: None,
         };
 
@@ -218,7 +309,9 @@ impl Z2mBackend {
             room_name = &grp.friendly_name;
         }
 
-        let link_room = RType::Room.deterministic(&grp.friendly_name);
+        let mut res = self.state.lock().await;
+
+        let link_room = self.room_link(&res, &grp.friendly_name);
         let link_glight = RType::GroupedLight.deterministic((link_room.rid, grp.id));
 
         let children = grp
@@ -229,8 +322,6 @@ impl Z2mBackend {
 
         let topic = grp.friendly_name.to_string();
 
-        let mut res = self.state.lock().await;
-
         let mut scenes_new = HashSet::new();
 
         for scn in &grp.scenes {
@@ -265,7 +356,10 @@ impl Z2mBackend {
 
             res.aux_set(
                 &link_scene,
-                AuxData::new().with_topic(&topic).with_index(scn.id),
+                AuxData::new()
+                    .with_topic(&topic)
+                    .with_index(scn.id)
+                    .with_backend(&self.name),
             );
 
             scenes_new.insert(link_scene.rid);
@@ -319,7 +413,7 @@ impl Z2mBackend {
 
         self.map.insert(topic.clone(), link_glight);
         self.rmap.insert(link_glight, topic.clone());
-        self.rmap.insert(link_room, topic.clone());
+        self.rmap.insert(link_room, topic);
 
         for id in &res.get_resource_ids_by_type(RType::BridgeHome) {
             res.update(id, |bh: &mut BridgeHome| {
@@ -327,6 +421,9 @@ impl Z2mBackend {
             })?;
         }
 
+        res.aux_set(&link_room, AuxData::new().with_backend(&self.name));
+        res.aux_set(&link_glight, AuxData::new().with_backend(&self.name));
+
         res.add(&link_room, Resource::Room(room))?;
 
         let glight = GroupedLight::new(link_room);
@@ -336,6 +433,59 @@ impl Z2mBackend {
 
         Ok(())
     }
+
+    /// Find the room for a z2m group.
+    ///
+    /// Rooms used to be keyed on the group name alone, which collides when
+    /// several z2m servers have groups with the same name. New rooms are
+    /// namespaced by backend, while existing rooms keep their id, unless
+    /// another backend has already claimed it.
+    fn room_link(&self, res: &Resources, topic: &str) -> ResourceLink {
+        let legacy = RType::Room.deterministic(topic);
+
+        match res.backend_owner(&legacy.rid) {
+            Some(owner) if owner == self.name => legacy,
+            None if res.get::<Room>(&legacy).is_ok() => legacy,
+            _ => RType::Room.deterministic((&self.name, topic)),
+        }
+    }
+}
+
+fn apply_device_overrides(conf: &DeviceConfig, dev: &mut hue::api::Device) {
+    if let Some(name) = &conf.name {
+        dev.metadata.name.clone_from(name);
+    }
+    if let Some(archetype) = &conf.archetype {
+        dev.metadata.archetype = archetype.clone();
+    }
+}
+
+fn apply_light_overrides(conf: &DeviceConfig, light: &mut Light) {
+    if let Some(name) = &conf.name {
+        light.metadata.name.clone_from(name);
+    }
+    if let Some(archetype) = &conf.archetype {
+        light.metadata.archetype = archetype.clone();
+    }
+    if let Some(function) = &conf.function {
+        light.metadata.function = Some(function.clone());
+    }
+
+    if let Some(ct) = &mut light.color_temperature {
+        if let Some(min) = conf.mirek_min {
+            ct.mirek_schema.mirek_minimum = min;
+            ct.mirek_valid = true;
+        }
+        if let Some(max) = conf.mirek_max {
+            ct.mirek_schema.mirek_maximum = max;
+            ct.mirek_valid = true;
+        }
+    }
+
+    if let (Some(color), Some(gamut_type)) = (&mut light.color, conf.gamut) {
+        color.gamut_type = gamut_type;
+        color.gamut = gamut_type.gamut();
+    }
 }
 
 #[allow(clippy::match_same_arms)]
@@ -365,3 +515,170 @@ fn guess_scene_icon(name: &str) -> Option<ResourceLink> {
         rtype: RType::PublicImage,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use hue::api::{
+        ColorTemperature, DeviceArchetype, DeviceProductData, Entertainment, GamutType, Light,
+        LightColor, LightFunction, LightMetadata, Metadata, MirekSchema, RType,
+    };
+    use hue::version::SwVersion;
+    use hue::xy::XY;
+    use maplit::btreeset;
+    use tokio::runtime::Builder;
+    use z2m::api::ExposeLight;
+
+    use crate::backend::z2m::Z2mBackend;
+    use crate::backend::z2m::bridge_import::{apply_device_overrides, apply_light_overrides};
+    use crate::backend::z2m::tests::{backend, config, device};
+    use crate::config::DeviceConfig;
+
+    fn light() -> Light {
+        let mut light = Light::new(
+            RType::Device.deterministic("lamp"),
+            LightMetadata::new(DeviceArchetype::SultanBulb, "lamp"),
+        );
+        light.color = Some(LightColor::new(XY::D65_WHITE_POINT));
+        light.color_temperature = Some(ColorTemperature {
+            mirek: None,
+            mirek_schema: MirekSchema::DEFAULT,
+            mirek_valid: false,
+        });
+        light
+    }
+
+    #[test]
+    fn device_overrides() {
+        let mut dev = hue::api::Device {
+            product_data: DeviceProductData::hue_bridge_v2(&SwVersion::new(1, String::new())),
+            metadata: Metadata::new(DeviceArchetype::SultanBulb, "lamp"),
+            services: btreeset![],
+            identify: None,
+            usertest: None,
+        };
+
+        apply_device_overrides(
+            &DeviceConfig {
+                name: Some("Desk lamp".to_string()),
+                archetype: Some(DeviceArchetype::TableShade),
+                ..DeviceConfig::default()
+            },
+            &mut dev,
+        );
+
+        assert_eq!(dev.metadata.name, "Desk lamp");
+        assert_eq!(dev.metadata.archetype, DeviceArchetype::TableShade);
+    }
+
+    #[test]
+    fn light_overrides() {
+        let mut light = light();
+
+        apply_light_overrides(
+            &DeviceConfig {
+                name: Some("Desk lamp".to_string()),
+                archetype: Some(DeviceArchetype::TableShade),
+                function: Some(LightFunction::Functional),
+                mirek_min: Some(200),
+                mirek_max: Some(400),
+                gamut: Some(GamutType::B),
+                ..DeviceConfig::default()
+            },
+            &mut light,
+        );
+
+        assert_eq!(light.metadata.name, "Desk lamp");
+        assert_eq!(light.metadata.archetype, DeviceArchetype::TableShade);
+        assert_eq!(light.metadata.function, Some(LightFunction::Functional));
+
+        let ct = light.color_temperature.unwrap();
+        assert!(ct.mirek_valid);
+        assert_eq!(
+            ct.mirek_schema,
+            MirekSchema {
+                mirek_minimum: 200,
+                mirek_maximum: 400,
+            }
+        );
+
+        let color = light.color.unwrap();
+        assert_eq!(color.gamut_type, GamutType::B);
+        assert_eq!(color.gamut, GamutType::B.gamut());
+    }
+
+    #[test]
+    fn light_overrides_empty() {
+        let mut light = light();
+        apply_light_overrides(&DeviceConfig::default(), &mut light);
+
+        assert_eq!(light, self::light());
+    }
+
+    #[test]
+    fn light_overrides_need_capabilities() {
+        let mut light = light();
+        light.color = None;
+        light.color_temperature = None;
+
+        apply_light_overrides(
+            &DeviceConfig {
+                mirek_min: Some(200),
+                gamut: Some(GamutType::B),
+                ..DeviceConfig::default()
+            },
+            &mut light,
+        );
+
+        assert!(light.color.is_none());
+        assert!(light.color_temperature.is_none());
+    }
+
+    #[test]
+    fn entertainment_relinked() {
+        let mut conf = config();
+        conf.devices.insert(
+            "lamp".to_string(),
+            DeviceConfig {
+                entertainment: Some(false),
+                ..DeviceConfig::default()
+            },
+        );
+
+        let mut z2m = backend(conf);
+        let dev = device("lamp", 0x0017_8801_0000_0001, 1, "Signify", "Router");
+        let expose: ExposeLight = serde_json::from_str("{}").unwrap();
+        let rt = Builder::new_current_thread().build().unwrap();
+
+        let link_device = RType::Device.deterministic(&dev.ieee_address);
+        let link_enttm = RType::Entertainment.deterministic(&dev.ieee_address);
+        let link_taurus = RType::Taurus.deterministic(&dev.ieee_address);
+
+        rt.block_on(z2m.add_light(&dev, &expose)).unwrap();
+        let services = |z2m: &Z2mBackend| {
+            let res = rt.block_on(z2m.state.lock());
+            res.get::<hue::api::Device>(&link_device)
+                .unwrap()
+                .services
+                .clone()
+        };
+
+        let linked = services(&z2m);
+        assert!(!linked.contains(&link_enttm));
+        assert!(!linked.contains(&link_taurus));
+
+        z2m.config = Arc::new(config());
+        rt.block_on(z2m.add_light(&dev, &expose)).unwrap();
+
+        let linked = services(&z2m);
+        assert!(linked.contains(&link_enttm));
+        assert!(linked.contains(&link_taurus));
+
+        let enttm = rt
+            .block_on(z2m.state.lock())
+            .get::<Entertainment>(&link_enttm)
+            .is_ok();
+        assert!(enttm);
+    }
+}
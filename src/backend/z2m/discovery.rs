@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+
+use hue::api::{ResourceLink, ZigbeeDeviceDiscovery, ZigbeeDeviceDiscoveryStatus};
+use z2m::api::TouchlinkDevice;
+
+use crate::backend::z2m::websocket::Z2mWebSocket;
+use crate::error::ApiResult;
+use crate::resource::Resources;
+
+/// A single touchlink request. z2m only handles one touchlink operation at a
+/// time, so these are sent one by one, as the previous one completes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TouchlinkStep {
+    Identify(TouchlinkDevice),
+    FactoryReset(TouchlinkDevice),
+}
+
+impl TouchlinkStep {
+    pub async fn send(self, z2mws: &mut Z2mWebSocket) -> ApiResult<()> {
+        match self {
+            Self::Identify(dev) => z2mws.send_touchlink_identify(dev).await,
+            Self::FactoryReset(dev) => z2mws.send_touchlink_factory_reset(dev).await,
+        }
+    }
+}
+
+/// A device discovery (permit join, and optionally a touchlink search)
+/// started through the `zigbee_device_discovery` resource
+#[derive(Debug)]
+pub struct Discovery {
+    pub link: ResourceLink,
+    /// Search codes, selecting the devices to factory reset using touchlink
+    pub search_codes: Vec<String>,
+    /// Set when z2m has confirmed that joining is permitted
+    pub active: bool,
+    /// Friendly names of devices that joined during this discovery
+    pub joined: Vec<String>,
+    touchlink: VecDeque<TouchlinkStep>,
+}
+
+impl Discovery {
+    #[must_use]
+    pub const fn new(link: ResourceLink, search_codes: Vec<String>) -> Self {
+        Self {
+            link,
+            search_codes,
+            active: false,
+            joined: vec![],
+            touchlink: VecDeque::new(),
+        }
+    }
+
+    /// Normalize a search code to the `0x`-prefixed, lowercase ieee address
+    /// format used by z2m.
+    ///
+    /// The serial number printed on hue devices is unrelated to the ieee
+    /// address, and is not part of a touchlink scan response, so serial
+    /// numbers cannot be matched. Only complete ieee addresses (with or
+    /// without `0x` prefix) are accepted as search codes.
+    #[must_use]
+    pub fn parse_search_code(code: &str) -> Option<String> {
+        let hex = code
+            .strip_prefix("0x")
+            .or_else(|| code.strip_prefix("0X"))
+            .unwrap_or(code);
+
+        if hex.len() != 16 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        Some(format!("0x{}", hex.to_ascii_lowercase()))
+    }
+
+    /// Check if a device was positively selected by a search code
+    fn matches(&self, dev: &TouchlinkDevice) -> bool {
+        let Some(addr) = Self::parse_search_code(&dev.ieee_address) else {
+            return false;
+        };
+
+        self.search_codes
+            .iter()
+            .filter_map(|code| Self::parse_search_code(code))
+            .any(|code| code == addr)
+    }
+
+    /// Queue touchlink requests for the devices found by a touchlink scan.
+    ///
+    /// Only devices matched by a search code are factory reset. Any other
+    /// device found is made to identify itself, and logged, so its address
+    /// can be used as a search code to reset it in a later discovery.
+    pub fn plan_touchlink(&mut self, found: &[TouchlinkDevice]) {
+        for dev in found {
+            self.touchlink
+                .push_back(TouchlinkStep::Identify(dev.clone()));
+
+            if self.matches(dev) {
+                self.touchlink
+                    .push_back(TouchlinkStep::FactoryReset(dev.clone()));
+            } else {
+                log::warn!(
+                    "Touchlink device {} does not match any search code, not resetting it",
+                    dev.ieee_address
+                );
+            }
+        }
+    }
+
+    pub fn next_step(&mut self) -> Option<TouchlinkStep> {
+        self.touchlink.pop_front()
+    }
+
+    /// Publish the discovery status on the hue resource
+    pub fn update_resource(
+        &self,
+        res: &mut Resources,
+        status: ZigbeeDeviceDiscoveryStatus,
+    ) -> ApiResult<()> {
+        let search_codes = match status {
+            ZigbeeDeviceDiscoveryStatus::Active => self.search_codes.clone(),
+            ZigbeeDeviceDiscoveryStatus::Ready => vec![],
+        };
+
+        res.update(&self.link.rid, |zbd: &mut ZigbeeDeviceDiscovery| {
+            zbd.status = status;
+            zbd.action.search_codes = search_codes;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hue::api::RType;
+    use z2m::api::TouchlinkDevice;
+
+    use crate::backend::z2m::discovery::{Discovery, TouchlinkStep};
+
+    fn discovery(codes: &[&str]) -> Discovery {
+        Discovery::new(
+            RType::ZigbeeDeviceDiscovery.deterministic("discovery"),
+            codes.iter().map(ToString::to_string).collect(),
+        )
+    }
+
+    fn device(ieee_address: &str) -> TouchlinkDevice {
+        TouchlinkDevice {
+            ieee_address: ieee_address.to_string(),
+            channel: 11,
+        }
+    }
+
+    fn steps(disc: &mut Discovery) -> Vec<TouchlinkStep> {
+        std::iter::from_fn(|| disc.next_step()).collect()
+    }
+
+    #[test]
+    fn parse_search_codes() {
+        assert_eq!(
+            Discovery::parse_search_code("0x0017880100A1B2C3").as_deref(),
+            Some("0x0017880100a1b2c3")
+        );
+        assert_eq!(
+            Discovery::parse_search_code("0017880100a1b2c3").as_deref(),
+            Some("0x0017880100a1b2c3")
+        );
+
+        // hue serial numbers, partial addresses and garbage are rejected
+        assert_eq!(Discovery::parse_search_code("A1B2C3"), None);
+        assert_eq!(Discovery::parse_search_code("0x17880100a1b2c3"), None);
+        assert_eq!(Discovery::parse_search_code("0x0017880100a1b2cz"), None);
+        assert_eq!(Discovery::parse_search_code(""), None);
+    }
+
+    #[test]
+    fn matches_address() {
+        let disc = discovery(&["0017880100A1B2C3"]);
+
+        assert!(disc.matches(&device("0x0017880100a1b2c3")));
+        assert!(!disc.matches(&device("0x0017880100a1b2c4")));
+    }
+
+    #[test]
+    fn matches_nothing_without_codes() {
+        assert!(!discovery(&[]).matches(&device("0x0017880100a1b2c3")));
+        assert!(!discovery(&["a1b2c3"]).matches(&device("0x0017880100a1b2c3")));
+    }
+
+    #[test]
+    fn plan_resets_matched() {
+        let mut disc = discovery(&["0x0017880100a1b2c3"]);
+        let dev = device("0x0017880100a1b2c3");
+
+        disc.plan_touchlink(std::slice::from_ref(&dev));
+
+        assert_eq!(
+            steps(&mut disc),
+            [
+                TouchlinkStep::Identify(dev.clone()),
+                TouchlinkStep::FactoryReset(dev),
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_identifies_unmatched() {
+        let mut disc = discovery(&["0x0017880100a1b2c3"]);
+        let matched = device("0x0017880100a1b2c3");
+        let other = device("0x0017880100d4e5f6");
+
+        disc.plan_touchlink(&[other.clone(), matched.clone()]);
+
+        assert_eq!(
+            steps(&mut disc),
+            [
+                TouchlinkStep::Identify(other),
+                TouchlinkStep::Identify(matched.clone()),
+                TouchlinkStep::FactoryReset(matched),
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_never_resets_without_match() {
+        let mut disc = discovery(&["123456"]);
+        let found = [device("0x0017880100a1b2c3"), device("0x0017880100d4e5f6")];
+
+        disc.plan_touchlink(&found);
+
+        let steps = steps(&mut disc);
+        assert_eq!(steps.len(), found.len());
+        assert!(
+            steps
+                .iter()
+                .all(|step| matches!(step, TouchlinkStep::Identify(_)))
+        );
+    }
+
+    #[test]
+    fn plan_nothing_found() {
+        let mut disc = discovery(&["0x0017880100a1b2c3"]);
+        disc.plan_touchlink(&[]);
+
+        assert!(disc.next_step().is_none());
+    }
+}
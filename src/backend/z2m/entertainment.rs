@@ -1,50 +1,126 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use serde_json::json;
 
+use hue::api::ColorGamut;
 use hue::stream::HueStreamLightsV2;
+use hue::xy::XY;
 use hue::zigbee::{
-    EntertainmentZigbeeStream, HueEntFrameLightRecord, LightRecordMode,
+    EntertainmentZigbeeStream, HueEntFrameLightRecord, HueEntSegmentConfig, LightRecordMode,
     PHILIPS_HUE_ZIGBEE_VENDOR_ID,
 };
 use z2m::request::Z2mRequest;
+use z2m::update::DeviceUpdate;
 use zcl::attr::ZclDataType;
+use zcl::cluster::hue_fc01::HueFc01Command;
+use zcl::command::ZclClusterCommand;
 
 use crate::backend::z2m::websocket::Z2mWebSocket;
 use crate::error::ApiResult;
+use crate::model::throttle::Throttle;
+
+/// A light without support for the Hue entertainment stream, which is updated
+/// with (rate-limited) regular light updates instead.
+pub struct FallbackLight {
+    pub topic: String,
+    pub gamut: Option<ColorGamut>,
+    pub throttle: Throttle,
+    pub transition: f64,
+}
+
+impl FallbackLight {
+    #[must_use]
+    pub fn new(topic: &str, gamut: Option<ColorGamut>, fps: u32) -> Self {
+        Self {
+            topic: topic.to_string(),
+            gamut,
+            throttle: Throttle::from_fps(fps),
+            transition: 1.0 / f64::from(fps),
+        }
+    }
+
+    /// Generate an update for this light, unless it is over its fps budget
+    pub fn update(&mut self, xy: XY, bright: f64) -> Option<DeviceUpdate> {
+        if !self.throttle.tick() {
+            return None;
+        }
+
+        let xy = self.gamut.map_or(xy, |gamut| gamut.clamp(xy));
+
+        Some(
+            DeviceUpdate::new()
+                .with_color_xy(Some(xy))
+                .with_brightness(Some(bright / 255.0 * 254.0))
+                .with_transition(Some(self.transition)),
+        )
+    }
+}
 
 pub struct EntStream {
     pub stream: EntertainmentZigbeeStream,
-    pub target: String,
+    pub target: Option<String>,
     pub addrs: BTreeMap<String, Vec<u16>>,
-    pub modes: Vec<(u16, LightRecordMode)>,
+    pub modes: BTreeMap<u8, Vec<(u16, LightRecordMode, Option<ColorGamut>)>>,
+    pub channels: BTreeSet<u8>,
+    pub unknown: BTreeSet<u8>,
+    pub fallback: BTreeMap<u8, Vec<FallbackLight>>,
 }
 
 impl EntStream {
+    /// Create a new stream.
+    ///
+    /// `segments` maps each channel rendered by this stream to the (device,
+    /// segment address) pairs it covers, while `channels` holds every channel
+    /// in the entertainment area, including those rendered elsewhere.
     #[must_use]
-    pub fn new(counter: u32, target: &str, addrs: BTreeMap<String, Vec<u16>>) -> Self {
-        let modes = Self::addrs_to_light_modes(&addrs);
+    pub fn new(
+        counter: u32,
+        target: Option<&str>,
+        segments: &BTreeMap<u8, Vec<(String, u16)>>,
+        gamuts: &BTreeMap<String, ColorGamut>,
+        channels: BTreeSet<u8>,
+        fallback: BTreeMap<u8, Vec<FallbackLight>>,
+    ) -> Self {
+        let mut addrs: BTreeMap<String, Vec<u16>> = BTreeMap::new();
+        for (dev, addr) in segments.values().flatten() {
+            addrs.entry(dev.clone()).or_default().push(*addr);
+        }
+
+        let modes = Self::channel_modes(segments, &addrs, gamuts);
+
         Self {
             stream: EntertainmentZigbeeStream::new(counter),
-            target: target.to_string(),
+            target: target.map(ToString::to_string),
             addrs,
             modes,
+            channels,
+            unknown: BTreeSet::new(),
+            fallback,
         }
     }
 
     #[must_use]
-    pub fn addrs_to_light_modes(addrs: &BTreeMap<String, Vec<u16>>) -> Vec<(u16, LightRecordMode)> {
-        let mut modes = vec![];
-
-        for segments in addrs.values() {
-            let mode = if segments.len() <= 1 {
-                LightRecordMode::Device
-            } else {
-                LightRecordMode::Segment
-            };
+    pub fn channel_modes(
+        segments: &BTreeMap<u8, Vec<(String, u16)>>,
+        addrs: &BTreeMap<String, Vec<u16>>,
+        gamuts: &BTreeMap<String, ColorGamut>,
+    ) -> BTreeMap<u8, Vec<(u16, LightRecordMode, Option<ColorGamut>)>> {
+        let mut modes: BTreeMap<u8, Vec<_>> = BTreeMap::new();
+
+        for (channel, members) in segments {
+            for (dev, addr) in members {
+                let mode = if addrs.get(dev).is_none_or(|segs| segs.len() <= 1) {
+                    LightRecordMode::Device
+                } else {
+                    LightRecordMode::Segment
+                };
+
+                let gamut = gamuts.get(dev).copied();
 
-            for seg in segments {
-                modes.push((*seg, mode));
+                modes
+                    .entry(*channel)
+                    .or_default()
+                    .push((*addr, mode, gamut));
             }
         }
 
@@ -65,43 +141,71 @@ impl EntStream {
         }
     }
 
+    /// Convert a frame into (channel, color, brightness) tuples
+    fn frame_lights(frame: &HueStreamLightsV2) -> Vec<(u8, XY, f64)> {
+        match frame {
+            HueStreamLightsV2::Rgb(rgb) => rgb
+                .iter()
+                .map(|light| {
+                    let (xy, bright) = light.rgb.to_xy();
+                    (light.channel, xy, bright)
+                })
+                .collect(),
+            HueStreamLightsV2::Xy(xy) => xy
+                .iter()
+                .map(|light| {
+                    let (xy, bright) = light.xy.to_xy();
+                    (light.channel, xy, bright)
+                })
+                .collect(),
+        }
+    }
+
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     #[must_use]
     pub fn generate_frame(&self, frame: &HueStreamLightsV2) -> Vec<HueEntFrameLightRecord> {
         let mut blks = vec![];
-        match frame {
-            HueStreamLightsV2::Rgb(rgb) => {
-                for light in rgb {
-                    let (xy, bright) = light.rgb.to_xy();
 
-                    let brightness = (bright / 255.0 * 2047.0).clamp(1.0, 2047.0) as u16;
-                    let (chan, mode) = self.modes[light.channel as usize % self.modes.len()];
-                    let raw = xy.to_quant();
-                    let lrec = HueEntFrameLightRecord::new(chan, brightness, mode, raw);
+        for (channel, xy, bright) in Self::frame_lights(frame) {
+            // channels rendered by other backends, or by fallback
+            let Some(members) = self.modes.get(&channel) else {
+                continue;
+            };
 
-                    blks.push(lrec);
-                }
+            let brightness = (bright / 255.0 * 2047.0).clamp(1.0, 2047.0) as u16;
+            for (addr, mode, gamut) in members {
+                let raw = gamut.map_or(xy, |gamut| gamut.clamp(xy)).to_quant();
+                blks.push(HueEntFrameLightRecord::new(*addr, brightness, *mode, raw));
             }
-            HueStreamLightsV2::Xy(xy) => {
-                for light in xy {
-                    let (xy, bright) = light.xy.to_xy();
+        }
 
-                    let brightness = (bright / 255.0 * 2047.0).clamp(1.0, 2047.0) as u16;
-                    let (chan, mode) = self.modes[light.channel as usize % self.modes.len()];
-                    let raw = xy.to_quant();
-                    let lrec = HueEntFrameLightRecord::new(chan, brightness, mode, raw);
+        blks
+    }
 
-                    blks.push(lrec);
-                }
+    /// Report channels that are not part of the entertainment area (once per
+    /// channel), since they are dropped instead of being rendered anywhere.
+    fn check_channels(&mut self, frame: &HueStreamLightsV2) {
+        let channels = match frame {
+            HueStreamLightsV2::Rgb(rgb) => {
+                rgb.iter().map(|light| light.channel).collect::<Vec<_>>()
             }
-        }
+            HueStreamLightsV2::Xy(xy) => xy.iter().map(|light| light.channel).collect(),
+        };
 
-        blks
+        for channel in channels {
+            if !self.channels.contains(&channel) && self.unknown.insert(channel) {
+                log::warn!("Dropping unknown entertainment channel {channel}");
+            }
+        }
     }
 
     pub async fn start_stream(&mut self, z2mws: &mut Z2mWebSocket) -> ApiResult<()> {
         log::debug!("Entertainment addrs: {:#?}", &self.addrs);
         log::debug!("Entertainment modes: {:#?}", &self.modes);
+        log::debug!(
+            "Entertainment fallback channels: {:?}",
+            self.fallback.keys().collect::<Vec<_>>()
+        );
         for (dev, segments) in &self.addrs {
             let z2mreq = Self::z2m_set_entertainment_brightness(0xFE);
             z2mws.send(dev, &z2mreq).await?;
@@ -110,7 +214,8 @@ impl EntStream {
                 continue;
             }
 
-            let mapping = self.stream.segment_mapping(segments)?;
+            let mapping =
+                HueFc01Command::SegmentConfig(HueEntSegmentConfig::new(segments)).to_message()?;
             z2mws.send_zigbee_message(dev, &mapping).await?;
         }
 
@@ -120,7 +225,7 @@ impl EntStream {
     }
 
     pub async fn stop_stream(&mut self, z2mws: &mut Z2mWebSocket) -> ApiResult<()> {
-        let stop = self.stream.reset()?;
+        let stop = HueFc01Command::Stop(self.stream.stop()).to_message()?;
         for topic in self.addrs.keys() {
             log::debug!("Sending stop to {topic}");
             z2mws.send_zigbee_message(topic, &stop).await?;
@@ -134,9 +239,125 @@ impl EntStream {
         z2mws: &mut Z2mWebSocket,
         frame: &HueStreamLightsV2,
     ) -> ApiResult<()> {
-        let blks = self.generate_frame(frame);
+        self.check_channels(frame);
+
+        if let Some(target) = &self.target {
+            let blks = self.generate_frame(frame);
+
+            let message = HueFc01Command::Frame(self.stream.next_frame(blks)).to_message()?;
+            z2mws.send_entertainment_frame(target, &message).await?;
+        }
+
+        self.fallback_frame(z2mws, frame).await
+    }
+
+    async fn fallback_frame(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        frame: &HueStreamLightsV2,
+    ) -> ApiResult<()> {
+        if self.fallback.is_empty() {
+            return Ok(());
+        }
+
+        for (channel, xy, bright) in Self::frame_lights(frame) {
+            let Some(lights) = self.fallback.get_mut(&channel) else {
+                continue;
+            };
+
+            for light in lights {
+                if let Some(upd) = light.update(xy, bright) {
+                    z2mws.send_update(&light.topic, &upd).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use packed_struct::PackedStruct;
+
+    use hue::stream::{HueStreamLightsV2, Xy16, Xy16V2};
+    use hue::zigbee::{HueEntFrameLightRecord, LightRecordMode};
+
+    use crate::backend::z2m::entertainment::{EntStream, FallbackLight};
+
+    fn segments() -> BTreeMap<u8, Vec<(String, u16)>> {
+        BTreeMap::from([
+            (0, vec![("strip".to_string(), 0x0001)]),
+            (1, vec![("strip".to_string(), 0x0002)]),
+            (2, vec![("bulb".to_string(), 0x0007)]),
+        ])
+    }
+
+    fn frame(channels: &[u8]) -> HueStreamLightsV2 {
+        HueStreamLightsV2::Xy(
+            channels
+                .iter()
+                .map(|&channel| Xy16V2 {
+                    channel,
+                    xy: Xy16 {
+                        x: 0x5000,
+                        y: 0x5000,
+                        b: 0xFFFF,
+                    },
+                })
+                .collect(),
+        )
+    }
+
+    fn addrs(blks: &[HueEntFrameLightRecord]) -> Vec<u16> {
+        blks.iter()
+            .map(|blk| {
+                let raw = blk.pack().unwrap();
+                u16::from_le_bytes([raw[0], raw[1]])
+            })
+            .collect()
+    }
+
+    fn stream(fallback: BTreeMap<u8, Vec<FallbackLight>>) -> EntStream {
+        let channels = BTreeSet::from([0, 1, 2, 3]);
+        EntStream::new(
+            0,
+            Some("strip"),
+            &segments(),
+            &BTreeMap::new(),
+            channels,
+            fallback,
+        )
+    }
+
+    #[test]
+    fn channel_modes() {
+        let stream = stream(BTreeMap::new());
+
+        assert_eq!(stream.modes[&0], [(0x0001, LightRecordMode::Segment, None)]);
+        assert_eq!(stream.modes[&1], [(0x0002, LightRecordMode::Segment, None)]);
+        assert_eq!(stream.modes[&2], [(0x0007, LightRecordMode::Device, None)]);
+    }
+
+    #[test]
+    fn frame_maps_channels_to_lights() {
+        let stream = stream(BTreeMap::new());
+
+        // channel 3 is rendered elsewhere, so it is left out
+        let blks = stream.generate_frame(&frame(&[2, 3, 0, 1]));
+        assert_eq!(addrs(&blks), [0x0007, 0x0001, 0x0002]);
+    }
+
+    #[test]
+    fn frame_with_shared_fallback_channel() {
+        let fallback = BTreeMap::from([(2, vec![FallbackLight::new("other", None, 10)])]);
+        let stream = stream(fallback);
 
-        let message = self.stream.frame(blks)?;
-        z2mws.send_entertainment_frame(&self.target, &message).await
+        // hue lights sharing a channel with a fallback light are still rendered
+        let blks = stream.generate_frame(&frame(&[2]));
+        assert_eq!(addrs(&blks), [0x0007]);
+        assert!(stream.fallback.contains_key(&2));
     }
 }
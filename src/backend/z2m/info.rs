@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use bifrost_api::backend::{BackendInfo, CoordinatorInfo, DeviceLinkInfo, HealthInfo, NetworkInfo};
+use hue::api::{RType, ZigbeeConnectivity};
+use z2m::api::{BridgeHealth, BridgeInfo, Network};
+
+use crate::backend::z2m::Z2mBackend;
+use crate::error::ApiResult;
+use crate::resource::Resources;
+
+fn coordinator_info(info: &BridgeInfo) -> CoordinatorInfo {
+    // the format of the coordinator metadata varies between adapters, but
+    // they all report the firmware revision in some form
+    let firmware = info.coordinator.meta.get("revision").map(|rev| match rev {
+        Value::String(rev) => rev.clone(),
+        rev => rev.to_string(),
+    });
+
+    CoordinatorInfo {
+        coordinator_type: info.coordinator.coordinator_type.clone(),
+        ieee_address: info.coordinator.ieee_address.to_string(),
+        firmware,
+    }
+}
+
+#[must_use]
+pub fn network_info(network: &Network) -> Option<NetworkInfo> {
+    let extended_pan_id = match &network.extended_pan_id {
+        Value::String(epid) => Some(epid.clone()),
+        Value::Number(epid) => Some(format!("0x{:016x}", epid.as_u64()?)),
+        _ => None,
+    };
+
+    Some(NetworkInfo {
+        channel: u8::try_from(network.channel).ok()?,
+        pan_id: u16::try_from(network.pan_id).ok()?,
+        extended_pan_id,
+    })
+}
+
+fn health_info(health: &BridgeHealth) -> HealthInfo {
+    HealthInfo {
+        response_time: health.response_time,
+        mqtt_connected: health.mqtt.as_ref().map(|mqtt| mqtt.connected),
+        uptime_sec: health.process.as_ref().and_then(|proc| proc.uptime_sec),
+        memory_used_mb: health.process.as_ref().and_then(|proc| proc.memory_used_mb),
+        load_average: health.os.as_ref().and_then(|os| os.load_average.clone()),
+    }
+}
+
+impl Z2mBackend {
+    /// The zigbee channel of this backend, as reported by z2m
+    #[must_use]
+    pub fn zigbee_channel(&self) -> Option<u8> {
+        let info = self.info.as_ref()?;
+        network_info(&info.network).map(|network| network.channel)
+    }
+
+    /// Publish the zigbee channel of this backend on the connectivity of
+    /// every device in its network
+    pub fn apply_zigbee_channel(&self, res: &mut Resources) -> ApiResult<()> {
+        let Some(channel) = self.zigbee_channel() else {
+            return Ok(());
+        };
+        let value = Some(ZigbeeConnectivity::channel_value(channel));
+
+        for dev in self.network.values() {
+            let link = RType::ZigbeeConnectivity.deterministic(&dev.ieee_address);
+            if res.get::<ZigbeeConnectivity>(&link).is_ok() {
+                res.update(&link.rid, |zbc: &mut ZigbeeConnectivity| {
+                    zbc.channel.clone_from(&value);
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Collect the most recent network and health information from z2m
+    #[must_use]
+    pub fn backend_info(&self) -> BackendInfo {
+        let health_devices = self
+            .health
+            .as_ref()
+            .and_then(|health| health.devices.as_ref());
+
+        let mut devices = BTreeMap::new();
+        for (topic, dev) in &self.network {
+            let linkquality = self.linkquality.get(topic).copied();
+            let health = health_devices.and_then(|hd| hd.get(&dev.ieee_address.to_string()));
+
+            if linkquality.is_none() && health.is_none() {
+                continue;
+            }
+
+            let link = DeviceLinkInfo {
+                linkquality,
+                messages: health.and_then(|h| h.messages),
+                messages_per_sec: health.and_then(|h| h.messages_per_sec),
+                leave_count: health.and_then(|h| h.leave_count),
+                network_address_changes: health.and_then(|h| h.network_address_changes),
+            };
+
+            devices.insert(topic.clone(), link);
+        }
+
+        BackendInfo {
+            version: self.info.as_ref().map(|info| info.version.clone()),
+            commit: self.info.as_ref().map(|info| info.commit.clone()),
+            coordinator: self.info.as_deref().map(coordinator_info),
+            network: self
+                .info
+                .as_ref()
+                .and_then(|info| network_info(&info.network)),
+            health: self.health.as_ref().map(health_info),
+            devices,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{from_value, json};
+    use tokio::runtime::Builder;
+
+    use hue::api::{RType, ZigbeeConnectivity};
+    use z2m::api::Network;
+
+    use crate::backend::z2m::Z2mBackend;
+    use crate::backend::z2m::info::network_info;
+    use crate::backend::z2m::tests::{backend, bridge_info, config, device};
+
+    fn network(channel: i64, pan_id: i64, extended_pan_id: serde_json::Value) -> Network {
+        Network {
+            channel,
+            extended_pan_id,
+            pan_id,
+        }
+    }
+
+    #[test]
+    fn network_info_string_epid() {
+        let info = network_info(&network(15, 0x1a62, json!("0xdddddddddddddddd"))).unwrap();
+
+        assert_eq!(info.channel, 15);
+        assert_eq!(info.pan_id, 0x1a62);
+        assert_eq!(info.extended_pan_id.as_deref(), Some("0xdddddddddddddddd"));
+    }
+
+    #[test]
+    fn network_info_numeric_epid() {
+        let info = network_info(&network(15, 0x1a62, json!(0xdd_u64))).unwrap();
+
+        assert_eq!(info.extended_pan_id.as_deref(), Some("0x00000000000000dd"));
+    }
+
+    #[test]
+    fn network_info_without_epid() {
+        let info = network_info(&network(15, 0x1a62, json!(null))).unwrap();
+
+        assert!(info.extended_pan_id.is_none());
+    }
+
+    #[test]
+    fn network_info_out_of_range() {
+        assert!(network_info(&network(300, 0x1a62, json!(null))).is_none());
+        assert!(network_info(&network(-1, 0x1a62, json!(null))).is_none());
+        assert!(network_info(&network(15, 0x10000, json!(null))).is_none());
+    }
+
+    #[test]
+    fn backend_info_empty() {
+        let info = backend(config()).backend_info();
+
+        assert!(info.version.is_none());
+        assert!(info.coordinator.is_none());
+        assert!(info.network.is_none());
+        assert!(info.health.is_none());
+        assert!(info.devices.is_empty());
+    }
+
+    #[test]
+    fn backend_info_bridge() {
+        let mut z2m = backend(config());
+        z2m.info = Some(Box::new(bridge_info(20)));
+
+        let info = z2m.backend_info();
+
+        assert_eq!(info.version.as_deref(), Some("2.1.0"));
+        assert_eq!(info.commit.as_deref(), Some("abcdef0"));
+
+        let coordinator = info.coordinator.unwrap();
+        assert_eq!(coordinator.coordinator_type, "EmberZNet");
+        assert_eq!(coordinator.firmware.as_deref(), Some("7.4.4"));
+
+        assert_eq!(info.network.unwrap().channel, 20);
+    }
+
+    #[test]
+    fn backend_info_devices() {
+        let mut z2m = backend(config());
+
+        let lamp = device("lamp", 0x0017_8801_0000_0001, 1, "Signify", "Router");
+        let spot = device("spot", 0x0017_8801_0000_0002, 2, "Signify", "Router");
+        let plug = device("plug", 0x0017_8801_0000_0003, 3, "Signify", "Router");
+
+        z2m.health = Some(
+            from_value(json!({
+                "devices": {
+                    spot.ieee_address.to_string(): {
+                        "leave_count": 1,
+                        "messages": 100,
+                        "messages_per_sec": 0.5,
+                        "network_address_changes": 2,
+                    },
+                },
+                "response_time": 12,
+            }))
+            .unwrap(),
+        );
+        z2m.linkquality.insert("lamp".to_string(), 200);
+        z2m.network = HashMap::from([
+            ("lamp".to_string(), lamp),
+            ("spot".to_string(), spot),
+            ("plug".to_string(), plug),
+        ]);
+
+        let info = z2m.backend_info();
+
+        assert_eq!(info.health.unwrap().response_time, Some(12));
+
+        // devices without any link information are left out
+        assert_eq!(info.devices.len(), 2);
+
+        let lamp = &info.devices["lamp"];
+        assert_eq!(lamp.linkquality, Some(200));
+        assert!(lamp.messages.is_none());
+
+        let spot = &info.devices["spot"];
+        assert!(spot.linkquality.is_none());
+        assert_eq!(spot.messages, Some(100));
+        assert_eq!(spot.leave_count, Some(1));
+        assert_eq!(spot.network_address_changes, Some(2));
+    }
+
+    #[test]
+    fn zigbee_channel_applied_to_devices() {
+        let mut z2m = backend(config());
+        let dev = device("switch", 0x0017_8801_0000_0001, 1, "Signify", "EndDevice");
+        let link_zbc = RType::ZigbeeConnectivity.deterministic(&dev.ieee_address);
+        let rt = Builder::new_current_thread().build().unwrap();
+
+        // imported before the bridge info is known
+        rt.block_on(z2m.add_switch(&dev)).unwrap();
+        z2m.network.insert("switch".to_string(), dev);
+
+        let channel = |z2m: &Z2mBackend| {
+            rt.block_on(z2m.state.lock())
+                .get::<ZigbeeConnectivity>(&link_zbc)
+                .unwrap()
+                .channel_number()
+        };
+        assert_eq!(channel(&z2m), None);
+
+        z2m.info = Some(Box::new(bridge_info(20)));
+        assert_eq!(z2m.zigbee_channel(), Some(20));
+
+        z2m.apply_zigbee_channel(&mut rt.block_on(z2m.state.lock()))
+            .unwrap();
+        assert_eq!(channel(&z2m), Some(20));
+    }
+}
@@ -1,12 +1,16 @@
 mod backend_event;
 mod bridge_event;
 mod bridge_import;
+pub mod discovery;
 pub mod entertainment;
+pub mod info;
 pub mod learn;
+pub mod signaling;
+pub mod topology;
 pub mod websocket;
 pub mod zclcommand;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -14,6 +18,7 @@ use async_trait::async_trait;
 use futures::StreamExt;
 use native_tls::TlsConnector;
 use svc::error::SvcError;
+use svc::policy::{Policy, Retry};
 use svc::template::ServiceTemplate;
 use svc::traits::{BoxDynService, Service};
 use thiserror::Error;
@@ -21,17 +26,25 @@ use tokio::net::TcpStream;
 use tokio::select;
 use tokio::sync::broadcast::Receiver;
 use tokio::sync::{Mutex, mpsc};
+use tokio::time::interval;
 use tokio_tungstenite::{
     Connector, MaybeTlsStream, WebSocketStream, connect_async_tls_with_config,
 };
 
-use bifrost_api::backend::BackendRequest;
 use hue::api::ResourceLink;
+use hue::zigbee::HueZigbeeUpdate;
+use uuid::Uuid;
+use z2m::api::{BridgeHealth, BridgeInfo};
 use z2m::update::DeviceUpdate;
 
+use crate::backend::BackendMessage;
+use crate::backend::z2m::discovery::{Discovery, TouchlinkStep};
 use crate::backend::z2m::entertainment::EntStream;
 use crate::backend::z2m::learn::SceneLearn;
+use crate::backend::z2m::signaling::{ActiveSignal, SignalEnd};
+use crate::backend::z2m::topology::Topology;
 use crate::backend::z2m::websocket::Z2mWebSocket;
+use crate::backend::z2m::zclcommand::PendingZclRead;
 use crate::config::{AppConfig, Z2mServer};
 use crate::error::{ApiError, ApiResult};
 use crate::model::throttle::Throttle;
@@ -78,20 +91,49 @@ pub struct Z2mBackend {
     learner: SceneLearn,
     ignore: HashSet<String>,
     network: HashMap<String, z2m::api::Device>,
+    linkquality: HashMap<String, u8>,
+    topology: Topology,
+    info: Option<Box<BridgeInfo>>,
+    health: Option<BridgeHealth>,
+    zcl_reads: HashMap<String, VecDeque<PendingZclRead>>,
     entstream: Option<EntStream>,
     counter: u32,
     fps: u32,
+    fallback_fps: u32,
     throttle: Throttle,
     socket: Option<WebSocketStream<MaybeTlsStream<TcpStream>>>,
 
+    signals: HashMap<Uuid, ActiveSignal>,
+    discovery: Option<Discovery>,
+
     // for sending delayed messages over the websocket
-    message_rx: mpsc::UnboundedReceiver<(String, DeviceUpdate)>,
-    message_tx: mpsc::UnboundedSender<(String, DeviceUpdate)>,
+    message_rx: mpsc::UnboundedReceiver<(String, DelayedMessage)>,
+    message_tx: mpsc::UnboundedSender<(String, DelayedMessage)>,
+}
+
+/// Message sent over the websocket from a background task, or from a
+/// handler without access to the websocket
+#[allow(clippy::large_enum_variant)]
+pub enum DelayedMessage {
+    Update(DeviceUpdate),
+    HueEffects(HueZigbeeUpdate),
+    Touchlink(TouchlinkStep),
+    SignalEnd(SignalEnd),
 }
 
 impl Z2mBackend {
     const DEFAULT_FPS: u32 = 20;
+    const DEFAULT_FALLBACK_FPS: u32 = 4;
     const LIGHT_BREATHE_DURATION: Duration = Duration::from_secs(2);
+    /// Time (in seconds) to permit joining during device discovery
+    const DISCOVERY_TIME: u32 = 60 * 4;
+    /// Software update progress is published in steps of this many percent
+    const OTA_PROGRESS_STEP: u8 = 10;
+    const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+    const RECONNECT_DELAY_MAX: Duration = Duration::from_secs(60);
+    /// Time between network map requests. Mapping the network is slow, and
+    /// causes a lot of traffic, so the map is only refreshed occasionally.
+    const NETWORKMAP_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
     pub fn new(
         name: String,
@@ -100,11 +142,17 @@ impl Z2mBackend {
         state: Arc<Mutex<Resources>>,
     ) -> ApiResult<Self> {
         let fps = server.streaming_fps.map_or(Self::DEFAULT_FPS, u32::from);
+        let fallback_fps = server
+            .streaming_fallback_fps
+            .map_or(Self::DEFAULT_FALLBACK_FPS, u32::from);
         let map = HashMap::new();
         let rmap = HashMap::new();
         let ignore = HashSet::new();
         let learner = SceneLearn::new(name.clone());
         let network = HashMap::new();
+        let linkquality = HashMap::new();
+        let zcl_reads = HashMap::new();
+        let signals = HashMap::new();
         let entstream = None;
         let throttle = Throttle::from_fps(fps);
         let (message_tx, message_rx) = mpsc::unbounded_channel();
@@ -118,9 +166,17 @@ impl Z2mBackend {
             learner,
             ignore,
             network,
+            linkquality,
+            topology: Topology::default(),
+            info: None,
+            health: None,
+            zcl_reads,
+            signals,
+            discovery: None,
             entstream,
             throttle,
             fps,
+            fallback_fps,
             message_rx,
             message_tx,
             socket: None,
@@ -130,15 +186,20 @@ impl Z2mBackend {
 
     pub async fn event_loop(
         &mut self,
-        chan: &mut Receiver<Arc<BackendRequest>>,
+        chan: &mut Receiver<Arc<BackendMessage>>,
         mut socket: Z2mWebSocket,
     ) -> ApiResult<()> {
+        // the first tick completes right away, to map the network on connect
+        let mut networkmap = interval(Self::NETWORKMAP_INTERVAL);
+
         loop {
             select! {
                 // all backend event handling implemented in backend::z2m::backend_event
                 pkt = chan.recv() => {
-                    let api_req = pkt?;
-                    self.handle_backend_event(&mut socket, api_req).await?;
+                    let msg = pkt?;
+                    if msg.is_for(&self.name) {
+                        self.handle_backend_event(&mut socket, &msg.request, msg.reply.as_ref()).await?;
+                    }
                     // FIXME: this used to be our "throttle" feature, but it breaks entertainment mode
                     /* tokio::time::sleep(std::time::Duration::from_millis(100)).await; */
                 },
@@ -148,8 +209,21 @@ impl Z2mBackend {
                     self.handle_bridge_event(pkt.ok_or(ApiError::UnexpectedZ2mEof)??).await?;
                 },
 
-                Some((topic, upd)) = self.message_rx.recv() => {
-                    socket.send_update(&topic, &upd).await?;
+                _ = networkmap.tick() => {
+                    socket.send_networkmap_request().await?;
+                },
+
+                Some((topic, msg)) = self.message_rx.recv() => {
+                    match msg {
+                        DelayedMessage::Update(upd) => socket.send_update(&topic, &upd).await?,
+                        DelayedMessage::HueEffects(hz) => socket.send_hue_effects(&topic, hz).await?,
+                        DelayedMessage::Touchlink(step) => step.send(&mut socket).await?,
+                        DelayedMessage::SignalEnd(end) => {
+                            if end.finish(&mut self.signals) {
+                                socket.send_update(&topic, &end.restore).await?;
+                            }
+                        }
+                    }
                 }
             };
         }
@@ -214,6 +288,7 @@ impl Service for Z2mBackend {
             let res = self.event_loop(&mut chan, z2m_socket).await;
             if let Err(err) = res {
                 log::error!("[{}] Event loop broke: {err}", self.name);
+                return Err(err);
             }
         }
         Ok(())
@@ -223,4 +298,170 @@ impl Service for Z2mBackend {
         self.socket.take();
         Ok(())
     }
+
+    fn start_policy(&self) -> Option<Policy> {
+        Some(
+            Policy::forever(Self::RECONNECT_DELAY, Self::RECONNECT_DELAY_MAX)
+                .with_jitter(Self::RECONNECT_DELAY),
+        )
+    }
+
+    fn run_policy(&self) -> Option<Policy> {
+        // when the connection drops, reconnect (with backoff in start())
+        Some(
+            Policy::new()
+                .with_retry(Retry::Forever)
+                .with_delay(Self::RECONNECT_DELAY),
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use hue::version::SwVersion;
+    use serde_json::json;
+    use tokio::sync::Mutex;
+    use z2m::api::BridgeInfo;
+
+    use crate::backend::z2m::Z2mBackend;
+    use crate::config::AppConfig;
+    use crate::model::state::State;
+    use crate::resource::Resources;
+
+    /// Bridge config, with a single z2m server named "z2m"
+    #[must_use]
+    pub fn config() -> AppConfig {
+        serde_json::from_value(json!({
+            "bridge": {
+                "name": "Bifrost",
+                "mac": "00:11:22:33:44:55",
+                "ipaddress": "10.0.0.2",
+                "http_port": 80,
+                "https_port": 443,
+                "entm_port": 2100,
+                "netmask": "255.255.255.0",
+                "gateway": "10.0.0.1",
+                "timezone": "UTC",
+            },
+            "z2m": {
+                "z2m": {"url": "ws://10.0.0.3:8080"},
+            },
+            "bifrost": {
+                "state_file": "state.yaml",
+                "cert_file": "cert.pem",
+            },
+        }))
+        .unwrap()
+    }
+
+    /// Backend named "z2m", without a connection to a z2m server
+    #[must_use]
+    pub fn backend(config: AppConfig) -> Z2mBackend {
+        let server = config.z2m.servers["z2m"].clone();
+        let res = Resources::new(SwVersion::new(1, String::new()), State::new());
+
+        Z2mBackend::new(
+            "z2m".to_string(),
+            server,
+            Arc::new(config),
+            Arc::new(Mutex::new(res)),
+        )
+        .unwrap()
+    }
+
+    /// Device, as announced by z2m in `bridge/devices`
+    #[must_use]
+    pub fn device(
+        name: &str,
+        ieee: u64,
+        nwk: u16,
+        manufacturer: &str,
+        device_type: &str,
+    ) -> z2m::api::Device {
+        // ieee addresses only deserialize from borrowed strings
+        let dev = json!({
+            "description": null,
+            "date_code": null,
+            "definition": null,
+            "disabled": false,
+            "endpoints": {},
+            "friendly_name": name,
+            "ieee_address": format!("0x{ieee:016x}"),
+            "interview_completed": true,
+            "interviewing": false,
+            "manufacturer": manufacturer,
+            "model_id": "LCT015",
+            "network_address": nwk,
+            "software_build_id": null,
+            "supported": true,
+            "type": device_type,
+        });
+        serde_json::from_str(&dev.to_string()).unwrap()
+    }
+
+    /// Bridge info, as published by z2m in `bridge/info`
+    #[must_use]
+    pub fn bridge_info(channel: u8) -> BridgeInfo {
+        let info = json!({
+            "commit": "abcdef0",
+            "config": {
+                "advanced": {
+                    "adapter_concurrent": null,
+                    "adapter_delay": null,
+                    "cache_state": true,
+                    "cache_state_persistent": true,
+                    "cache_state_send_on_startup": true,
+                    "channel": channel,
+                    "elapsed": false,
+                    "ext_pan_id": [221, 221, 221, 221, 221, 221, 221, 221],
+                    "homeassistant_legacy_entity_attributes": null,
+                    "last_seen": "disable",
+                    "log_debug_namespace_ignore": "",
+                    "log_debug_to_mqtt_frontend": false,
+                    "log_directory": "log",
+                    "log_file": "log.log",
+                    "log_level": "info",
+                    "log_namespaced_levels": {},
+                    "log_output": ["console"],
+                    "log_rotation": true,
+                    "log_symlink_current": false,
+                    "log_syslog": {},
+                    "output": "json",
+                    "pan_id": 6754,
+                    "timestamp_format": "YYYY-MM-DD HH:mm:ss",
+                },
+                "blocklist": [],
+                "device_options": {},
+                "devices": {},
+                "frontend": {},
+                "groups": {},
+                "homeassistant": false,
+                "map_options": {},
+                "mqtt": {},
+                "ota": {},
+                "passlist": [],
+                "serial": {"adapter": "ember", "disable_led": false},
+            },
+            "config_schema": {"definitions": {}, "properties": {}},
+            "coordinator": {
+                "ieee_address": "0x00124b0000000000",
+                "meta": {"revision": "7.4.4"},
+                "type": "EmberZNet",
+            },
+            "log_level": "info",
+            "network": {
+                "channel": channel,
+                "extended_pan_id": "0xdddddddddddddddd",
+                "pan_id": 6754,
+            },
+            "permit_join": false,
+            "restart_required": false,
+            "version": "2.1.0",
+            "zigbee_herdsman": {"version": "3.2.0"},
+            "zigbee_herdsman_converters": {"version": "23.1.0"},
+        });
+        serde_json::from_str(&info.to_string()).unwrap()
+    }
 }
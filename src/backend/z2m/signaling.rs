@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use hue::api::{ColorGamut, Light, LightSignal, LightSignalingUpdate};
+use hue::xy::XY;
+use hue::zigbee::HueZigbeeUpdate;
+use z2m::update::DeviceUpdate;
+
+use crate::backend::z2m::DelayedMessage;
+
+/// A single step of a light signal, repeated until the signal ends
+#[derive(Clone, Copy, Debug)]
+pub struct SignalStep {
+    pub on: bool,
+    pub xy: Option<XY>,
+}
+
+impl SignalStep {
+    /// Time spent on each step of a signal
+    pub const INTERVAL: Duration = Duration::from_millis(800);
+
+    const fn on(xy: Option<XY>) -> Self {
+        Self { on: true, xy }
+    }
+
+    const fn off() -> Self {
+        Self {
+            on: false,
+            xy: None,
+        }
+    }
+
+    /// The (repeating) sequence of steps for a signal. Colors missing from
+    /// the request fall back to white.
+    #[must_use]
+    pub fn steps(sig: &LightSignalingUpdate, gamut: Option<&ColorGamut>) -> Vec<Self> {
+        let color = |index: usize| {
+            let xy = sig
+                .colors
+                .get(index)
+                .map_or(XY::D65_WHITE_POINT, |col| col.xy);
+            gamut.map_or(xy, |gamut| gamut.clamp(xy))
+        };
+
+        match sig.signal {
+            LightSignal::NoSignal => vec![],
+            LightSignal::OnOff => vec![Self::on(None), Self::off()],
+            LightSignal::OnOffColor => vec![Self::on(Some(color(0))), Self::off()],
+            LightSignal::Alternating => vec![Self::on(Some(color(0))), Self::on(Some(color(1)))],
+        }
+    }
+
+    /// Update to restore a light to its current state, once a signal ends.
+    ///
+    /// Brightness and color are only restored for lights that were on, since
+    /// setting those would turn the light back on.
+    #[must_use]
+    pub fn restore(light: &Light) -> DeviceUpdate {
+        let upd = DeviceUpdate::new().with_state(Some(light.on.on));
+        if !light.on.on {
+            return upd;
+        }
+
+        let mirek = light.as_mirek_opt();
+        upd.with_brightness(light.dimming.map(|dim| dim.brightness / 100.0 * 254.0))
+            .with_color_temp(mirek)
+            .with_color_xy(light.as_color_opt().filter(|_| mirek.is_none()))
+    }
+
+    /// Build the message for this step, using the hue-specific effects
+    /// cluster if the light supports it, and a regular update otherwise.
+    #[must_use]
+    pub fn message(&self, hue_effects: bool) -> DelayedMessage {
+        if hue_effects {
+            let mut hz = HueZigbeeUpdate::new()
+                .with_on_off(self.on)
+                .with_fade_speed(0);
+
+            if let Some(xy) = self.xy {
+                hz = hz.with_color_xy(xy);
+            }
+
+            DelayedMessage::HueEffects(hz)
+        } else {
+            DelayedMessage::Update(
+                DeviceUpdate::new()
+                    .with_state(Some(self.on))
+                    .with_color_xy(self.xy)
+                    .with_transition(Some(0.0)),
+            )
+        }
+    }
+}
+
+/// A signal in progress on a light
+pub struct ActiveSignal {
+    /// Unique id of this signal, to tell it apart from any signal replacing it
+    pub id: Uuid,
+    pub job: JoinHandle<()>,
+    /// Update to restore the light to its state from before the signal
+    pub restore: DeviceUpdate,
+}
+
+/// Sent by a signal job when it runs out, to restore the light
+pub struct SignalEnd {
+    pub light: Uuid,
+    pub signal: Uuid,
+    pub restore: DeviceUpdate,
+}
+
+impl SignalEnd {
+    /// Remove the ended signal from `signals`, returning true if it was
+    /// still the active signal for the light (and so should be restored).
+    ///
+    /// A signal replaced by a newer one is no longer tracked, and must not
+    /// restore the light while the newer signal runs.
+    pub fn finish(&self, signals: &mut HashMap<Uuid, ActiveSignal>) -> bool {
+        if signals
+            .get(&self.light)
+            .is_some_and(|active| active.id == self.signal)
+        {
+            signals.remove(&self.light);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{json, to_value};
+    use tokio::runtime::Builder;
+    use uuid::Uuid;
+
+    use hue::api::{
+        ColorGamut, ColorTemperature, ColorUpdate, DeviceArchetype, Dimming, Light, LightColor,
+        LightMetadata, LightSignal, LightSignalingUpdate, MirekSchema, RType,
+    };
+    use hue::xy::XY;
+    use z2m::update::DeviceUpdate;
+
+    use crate::backend::z2m::signaling::{ActiveSignal, SignalEnd, SignalStep};
+
+    const RED: XY = XY { x: 0.6, y: 0.3 };
+    const BLUE: XY = XY { x: 0.15, y: 0.06 };
+
+    fn signal(signal: LightSignal, colors: &[XY]) -> LightSignalingUpdate {
+        LightSignalingUpdate {
+            signal,
+            duration: None,
+            colors: colors.iter().copied().map(ColorUpdate::new).collect(),
+        }
+    }
+
+    fn pattern(steps: &[SignalStep]) -> Vec<(bool, Option<XY>)> {
+        steps.iter().map(|step| (step.on, step.xy)).collect()
+    }
+
+    fn light(on: bool) -> Light {
+        let mut light = Light::new(
+            RType::Device.deterministic("light"),
+            LightMetadata::new(DeviceArchetype::SultanBulb, "light"),
+        );
+        light.on.on = on;
+        light.dimming = Some(Dimming {
+            brightness: 50.0,
+            min_dim_level: None,
+        });
+        light
+    }
+
+    #[test]
+    fn steps_no_signal() {
+        let steps = SignalStep::steps(&signal(LightSignal::NoSignal, &[RED]), None);
+        assert!(steps.is_empty());
+    }
+
+    #[test]
+    fn steps_on_off() {
+        let steps = SignalStep::steps(&signal(LightSignal::OnOff, &[RED]), None);
+        assert_eq!(pattern(&steps), [(true, None), (false, None)]);
+    }
+
+    #[test]
+    fn steps_on_off_color() {
+        let steps = SignalStep::steps(&signal(LightSignal::OnOffColor, &[RED]), None);
+        assert_eq!(pattern(&steps), [(true, Some(RED)), (false, None)]);
+    }
+
+    #[test]
+    fn steps_alternating() {
+        let steps = SignalStep::steps(&signal(LightSignal::Alternating, &[RED, BLUE]), None);
+        assert_eq!(pattern(&steps), [(true, Some(RED)), (true, Some(BLUE))]);
+    }
+
+    #[test]
+    fn steps_missing_colors_are_white() {
+        let steps = SignalStep::steps(&signal(LightSignal::Alternating, &[RED]), None);
+        assert_eq!(
+            pattern(&steps),
+            [(true, Some(RED)), (true, Some(XY::D65_WHITE_POINT))]
+        );
+    }
+
+    #[test]
+    fn steps_clamped_to_gamut() {
+        let gamut = ColorGamut::GAMUT_C;
+        let steps = SignalStep::steps(&signal(LightSignal::OnOffColor, &[BLUE]), Some(&gamut));
+
+        let xy = steps[0].xy.unwrap();
+        assert_eq!(xy, gamut.clamp(BLUE));
+        assert!(gamut.contains(xy));
+    }
+
+    #[test]
+    fn restore_off_light_stays_off() {
+        let mut light = light(false);
+        light.color = Some(LightColor::new(RED));
+
+        assert_eq!(
+            to_value(SignalStep::restore(&light)).unwrap(),
+            json!({"state": "OFF"})
+        );
+    }
+
+    #[test]
+    fn restore_color_temperature() {
+        let mut light = light(true);
+        light.color = Some(LightColor::new(RED));
+        light.color_temperature = Some(ColorTemperature {
+            mirek: Some(366),
+            mirek_schema: MirekSchema::DEFAULT,
+            mirek_valid: true,
+        });
+
+        assert_eq!(
+            to_value(SignalStep::restore(&light)).unwrap(),
+            json!({"state": "ON", "brightness": 127.0, "color_temp": 366})
+        );
+    }
+
+    #[test]
+    fn restore_color_xy() {
+        let mut light = light(true);
+        light.color = Some(LightColor::new(RED));
+
+        let upd = SignalStep::restore(&light);
+        assert_eq!(upd.color.and_then(|col| col.xy), Some(RED));
+        assert!(upd.color_temp.is_none());
+    }
+
+    #[test]
+    fn signal_end_removes_active_signal() {
+        let rt = Builder::new_current_thread().build().unwrap();
+        let light = Uuid::new_v4();
+        let end = SignalEnd {
+            light,
+            signal: Uuid::new_v4(),
+            restore: DeviceUpdate::new(),
+        };
+
+        let mut signals = HashMap::new();
+        signals.insert(
+            light,
+            ActiveSignal {
+                id: end.signal,
+                job: rt.spawn(async {}),
+                restore: DeviceUpdate::new(),
+            },
+        );
+
+        assert!(end.finish(&mut signals));
+        assert!(signals.is_empty());
+
+        // a second end (or one for an unknown light) is ignored
+        assert!(!end.finish(&mut signals));
+    }
+
+    #[test]
+    fn signal_end_keeps_replacing_signal() {
+        let rt = Builder::new_current_thread().build().unwrap();
+        let light = Uuid::new_v4();
+        let end = SignalEnd {
+            light,
+            signal: Uuid::new_v4(),
+            restore: DeviceUpdate::new(),
+        };
+
+        let mut signals = HashMap::new();
+        signals.insert(
+            light,
+            ActiveSignal {
+                id: Uuid::new_v4(),
+                job: rt.spawn(async {}),
+                restore: DeviceUpdate::new(),
+            },
+        );
+
+        assert!(!end.finish(&mut signals));
+        assert!(signals.contains_key(&light));
+    }
+}
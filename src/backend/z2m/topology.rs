@@ -0,0 +1,147 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use z2m::api::NetworkMapRaw;
+
+/// Zigbee network topology, as reported by the z2m network map
+#[derive(Debug, Default)]
+pub struct Topology {
+    /// Link quality between pairs of devices, keyed by ieee address (lowest
+    /// address first)
+    links: HashMap<(String, String), u8>,
+    /// Depth of devices in the network tree (0 for the coordinator)
+    depth: HashMap<String, u8>,
+}
+
+impl Topology {
+    #[must_use]
+    pub fn new(map: &NetworkMapRaw) -> Self {
+        let mut res = Self::default();
+
+        for link in &map.links {
+            let source = link.source.ieee_addr.to_lowercase();
+            let target = link.target.ieee_addr.to_lowercase();
+
+            res.depth.insert(source.clone(), link.depth);
+
+            // most links are reported by both ends, keep the best quality
+            let lqi = res.links.entry(Self::key(source, target)).or_default();
+            *lqi = (*lqi).max(link.lqi);
+        }
+
+        res
+    }
+
+    fn key(a: String, b: String) -> (String, String) {
+        if a <= b { (a, b) } else { (b, a) }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Link quality of the direct link between two devices, if any
+    #[must_use]
+    pub fn lqi(&self, a: &str, b: &str) -> Option<u8> {
+        let key = Self::key(a.to_lowercase(), b.to_lowercase());
+        self.links.get(&key).copied()
+    }
+
+    /// Rank a device as entertainment proxy for `members` (higher is better).
+    ///
+    /// The proxy relays the stream to the other members, so devices with
+    /// direct links to more members are preferred, then devices with better
+    /// total link quality to them, then devices closer to the coordinator.
+    #[must_use]
+    pub fn proxy_rank(&self, candidate: &str, members: &[&str]) -> (usize, u32, Reverse<u8>) {
+        let (direct, lqi) = members
+            .iter()
+            .filter(|member| !member.eq_ignore_ascii_case(candidate))
+            .filter_map(|member| self.lqi(candidate, member))
+            .fold((0, 0), |(direct, total), lqi| {
+                (direct + 1, total + u32::from(lqi))
+            });
+
+        let depth = self
+            .depth
+            .get(&candidate.to_lowercase())
+            .copied()
+            .unwrap_or(u8::MAX);
+
+        (direct, lqi, Reverse(depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Reverse;
+
+    use serde_json::json;
+    use z2m::api::NetworkMapRaw;
+
+    use crate::backend::z2m::topology::Topology;
+
+    const COORD: &str = "0x00124b0000000000";
+    const LAMP_A: &str = "0x0017880100000001";
+    const LAMP_B: &str = "0x0017880100000002";
+    const LAMP_C: &str = "0x0017880100000003";
+
+    fn link(source: &str, target: &str, lqi: u8, depth: u8) -> serde_json::Value {
+        json!({
+            "source": {"ieeeAddr": source, "networkAddress": 1},
+            "target": {"ieeeAddr": target, "networkAddress": 2},
+            "lqi": lqi,
+            "depth": depth,
+            "relationship": 2,
+            "routes": [],
+        })
+    }
+
+    fn topology() -> Topology {
+        let map: NetworkMapRaw = serde_json::from_value(json!({
+            "nodes": [],
+            "links": [
+                link(LAMP_A, COORD, 200, 1),
+                link(COORD, LAMP_A, 180, 0),
+                link(LAMP_B, LAMP_A, 150, 2),
+                link(LAMP_C, LAMP_A, 120, 2),
+                link(LAMP_C, LAMP_B, 90, 2),
+            ],
+        }))
+        .unwrap();
+
+        Topology::new(&map)
+    }
+
+    #[test]
+    fn links_are_symmetric() {
+        let topo = topology();
+
+        assert_eq!(topo.lqi(COORD, LAMP_A), Some(200));
+        assert_eq!(topo.lqi(LAMP_A, COORD), Some(200));
+        assert_eq!(topo.lqi(LAMP_B, LAMP_C), Some(90));
+        assert_eq!(topo.lqi(COORD, LAMP_B), None);
+    }
+
+    #[test]
+    fn proxy_rank_prefers_connected_members() {
+        let topo = topology();
+        let members = [LAMP_A, LAMP_B, LAMP_C];
+
+        assert_eq!(topo.proxy_rank(LAMP_A, &members), (2, 270, Reverse(1)));
+        assert_eq!(topo.proxy_rank(LAMP_B, &members), (2, 240, Reverse(2)));
+        assert!(topo.proxy_rank(LAMP_A, &members) > topo.proxy_rank(LAMP_C, &members));
+    }
+
+    #[test]
+    fn proxy_rank_unknown_device() {
+        let topo = Topology::default();
+
+        assert!(topo.is_empty());
+        assert_eq!(
+            topo.proxy_rank(LAMP_A, &[LAMP_A, LAMP_B]),
+            (0, 0, Reverse(u8::MAX))
+        );
+    }
+}
@@ -3,13 +3,19 @@ use std::task::{Context, Poll};
 
 use futures::{SinkExt, Stream};
 use hue::zigbee::{HueZigbeeUpdate, ZigbeeMessage};
+use serde_json::json;
 use tokio::net::TcpStream;
 use tokio_tungstenite::tungstenite::{self, Message};
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
-use z2m::api::{DeviceRemove, GroupMemberChange, PermitJoin};
+use z2m::api::{
+    DeviceOtaUpdate, DeviceRemove, DeviceRename, GroupMemberChange, NetworkMapRequest, PermitJoin,
+    TouchlinkDevice,
+};
 use z2m::request::Z2mPayload;
 use z2m::update::DeviceUpdate;
 use z2m::{api::RawMessage, request::Z2mRequest};
+use zcl::cluster::hue_fc03::HueFc03Command;
+use zcl::command::ZclClusterCommand;
 
 use crate::backend::z2m::zclcommand::hue_zclcommand;
 use crate::error::ApiResult;
@@ -55,6 +61,34 @@ impl Z2mWebSocket {
                 topic: "bridge/request/device/remove".into(),
                 payload: serde_json::to_value(dev)?,
             },
+            Z2mRequest::DeviceRename(dev) => RawMessage {
+                topic: "bridge/request/device/rename".into(),
+                payload: serde_json::to_value(dev)?,
+            },
+            Z2mRequest::DeviceOtaUpdate(dev) => RawMessage {
+                topic: "bridge/request/device/ota_update/update".into(),
+                payload: serde_json::to_value(dev)?,
+            },
+            Z2mRequest::DeviceOtaCheck(dev) => RawMessage {
+                topic: "bridge/request/device/ota_update/check".into(),
+                payload: serde_json::to_value(dev)?,
+            },
+            Z2mRequest::NetworkMap(req) => RawMessage {
+                topic: "bridge/request/networkmap".into(),
+                payload: serde_json::to_value(req)?,
+            },
+            Z2mRequest::TouchlinkScan => RawMessage {
+                topic: "bridge/request/touchlink/scan".into(),
+                payload: json!({}),
+            },
+            Z2mRequest::TouchlinkIdentify(dev) => RawMessage {
+                topic: "bridge/request/touchlink/identify".into(),
+                payload: serde_json::to_value(dev)?,
+            },
+            Z2mRequest::TouchlinkFactoryReset(dev) => RawMessage {
+                topic: "bridge/request/touchlink/factory_reset".into(),
+                payload: serde_json::to_value(dev)?,
+            },
             _ => RawMessage {
                 topic: format!("{topic}/set"),
                 payload: serde_json::to_value(payload)?,
@@ -143,13 +177,13 @@ impl Z2mWebSocket {
     }
 
     pub async fn send_hue_effects(&mut self, topic: &str, hz: HueZigbeeUpdate) -> ApiResult<()> {
-        let data = hz.to_vec()?;
-        log::debug!("Sending hue-specific frame: {}", hex::encode(&data));
+        let msg = HueFc03Command::Update(hz).to_message()?;
+        log::debug!("Sending hue-specific frame: {}", hex::encode(&msg.data));
 
         let z2mreq = Z2mRequest::Command {
-            cluster: 0xFC03,
-            command: 0,
-            payload: Z2mPayload { data },
+            cluster: msg.cluster,
+            command: msg.command.into(),
+            payload: Z2mPayload { data: msg.data },
         };
 
         self.send(topic, &z2mreq).await
@@ -161,11 +195,55 @@ impl Z2mWebSocket {
         self.send("", &z2mreq).await
     }
 
+    /// Request a map of the network, with the link quality between
+    /// neighboring devices
+    pub async fn send_networkmap_request(&mut self) -> ApiResult<()> {
+        let req = NetworkMapRequest {
+            map_type: "raw".to_string(),
+            routes: false,
+        };
+        self.send("", &Z2mRequest::NetworkMap(req)).await
+    }
+
+    pub async fn send_touchlink_scan(&mut self) -> ApiResult<()> {
+        self.send("", &Z2mRequest::TouchlinkScan).await
+    }
+
+    pub async fn send_touchlink_identify(&mut self, dev: TouchlinkDevice) -> ApiResult<()> {
+        self.send("", &Z2mRequest::TouchlinkIdentify(dev)).await
+    }
+
+    pub async fn send_touchlink_factory_reset(&mut self, dev: TouchlinkDevice) -> ApiResult<()> {
+        self.send("", &Z2mRequest::TouchlinkFactoryReset(dev)).await
+    }
+
     pub async fn send_device_remove(&mut self, id: String) -> ApiResult<()> {
         let z2mreq = Z2mRequest::DeviceRemove(DeviceRemove { id });
 
         self.send("", &z2mreq).await
     }
+
+    pub async fn send_device_ota_update(&mut self, id: String) -> ApiResult<()> {
+        let z2mreq = Z2mRequest::DeviceOtaUpdate(DeviceOtaUpdate { id });
+
+        self.send("", &z2mreq).await
+    }
+
+    pub async fn send_device_ota_check(&mut self, id: String) -> ApiResult<()> {
+        let z2mreq = Z2mRequest::DeviceOtaCheck(DeviceOtaUpdate { id });
+
+        self.send("", &z2mreq).await
+    }
+
+    pub async fn send_device_rename(&mut self, from: String, to: String) -> ApiResult<()> {
+        let z2mreq = Z2mRequest::DeviceRename(DeviceRename {
+            from,
+            to,
+            homeassistant_rename: true,
+        });
+
+        self.send("", &z2mreq).await
+    }
 }
 
 impl Stream for Z2mWebSocket
@@ -1,5 +1,12 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value, json};
+
+use bifrost_api::device::{ZclAttrRead, ZclAttrResult, ZclAttrWrite};
 use hue::zigbee::ZigbeeMessage;
-use serde_json::{Value, json};
+use zcl::attr::{ZclAttrValue, ZclDataType};
+
+use crate::backend::BackendReply;
 
 /// Use the low-level endpoint for `Zigbee2MQTT`, which allows free-form zigbee
 /// messages to be sent.
@@ -29,3 +36,392 @@ pub fn hue_zclcommand(msg: &ZigbeeMessage) -> Value {
         }
     })
 }
+
+fn zcl_options(mfc: Option<u16>) -> Value {
+    mfc.map_or_else(|| json!({}), |mfc| json!({"manufacturerCode": mfc}))
+}
+
+/// Device state property, that z2m publishes zcl read results under
+///
+/// z2m keeps this property in the device state, so a single, fixed name is
+/// used for all reads, instead of leaving a new property behind for every
+/// read.
+pub const ZCL_READ_PROPERTY: &str = "bifrost_zcl";
+
+/// Read zcl attributes through the z2m "read" converter.
+///
+/// The result is published by z2m as part of the device state, under the name
+/// [`ZCL_READ_PROPERTY`].
+#[must_use]
+pub fn zcl_read(req: &ZclAttrRead) -> Value {
+    json!({
+        "read": {
+            "cluster": req.cluster,
+            "attributes": req.attributes,
+            "options": zcl_options(req.mfc),
+            "state_property": ZCL_READ_PROPERTY,
+        }
+    })
+}
+
+/// Write zcl attributes through the z2m "write" converter.
+///
+/// Attributes are given by id, with an explicit data type, so this works for
+/// attributes unknown to z2m as well.
+#[must_use]
+pub fn zcl_write(req: &ZclAttrWrite) -> Value {
+    let payload: Map<String, Value> = req
+        .attributes
+        .iter()
+        .map(|(id, value)| {
+            let attr = json!({
+                "value": zcl_attr_to_json(value),
+                "type": value.data_type() as u8,
+            });
+            (id.to_string(), attr)
+        })
+        .collect();
+
+    json!({
+        "write": {
+            "cluster": req.cluster,
+            "payload": payload,
+            "options": zcl_options(req.mfc),
+        }
+    })
+}
+
+#[must_use]
+pub fn zcl_attr_to_json(value: &ZclAttrValue) -> Value {
+    match value {
+        ZclAttrValue::Null | ZclAttrValue::Unsupported => Value::Null,
+        ZclAttrValue::X8(val) => json!(val),
+        ZclAttrValue::X16(val) | ZclAttrValue::I16(val) => json!(val),
+        ZclAttrValue::X32(val) => json!(val),
+        ZclAttrValue::Bool(val) => json!(val),
+        ZclAttrValue::B8(val) | ZclAttrValue::U8(val) | ZclAttrValue::E8(val) => json!(val),
+        ZclAttrValue::B16(val) | ZclAttrValue::U16(val) => json!(val),
+        ZclAttrValue::B32(val) | ZclAttrValue::U32(val) => json!(val),
+        ZclAttrValue::B40(val)
+        | ZclAttrValue::B48(val)
+        | ZclAttrValue::B56(val)
+        | ZclAttrValue::B64(val) => json!(val),
+        ZclAttrValue::Bytes(val) => json!(val),
+        ZclAttrValue::String(val) => json!(val),
+        ZclAttrValue::IeeeAddr(val) => json!(format!("0x{}", hex::encode(val))),
+        ZclAttrValue::SecurityKey(val) => json!(val),
+    }
+}
+
+/// Zcl attribute read, waiting for z2m to publish the result
+#[derive(Debug)]
+pub struct PendingZclRead {
+    pub reply: BackendReply,
+    pub types: BTreeMap<u16, ZclDataType>,
+}
+
+impl PendingZclRead {
+    /// Convert the attributes published by z2m, decoding values with the
+    /// requested data types where possible.
+    ///
+    /// z2m names the attributes it knows, and uses the attribute id for all
+    /// others, so only attributes reported by id can be typed.
+    #[must_use]
+    pub fn result(&self, attrs: &Value) -> BTreeMap<String, ZclAttrResult> {
+        attrs
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| {
+                let res = key
+                    .parse::<u16>()
+                    .ok()
+                    .and_then(|id| self.types.get(&id))
+                    .and_then(|data_type| zcl_attr_from_json(value, *data_type))
+                    .map_or_else(|| ZclAttrResult::Raw(value.clone()), ZclAttrResult::Value);
+                (key.clone(), res)
+            })
+            .collect()
+    }
+}
+
+/// Convert an attribute value, as decoded by z2m, back to a [`ZclAttrValue`]
+/// of type `data_type`.
+///
+/// Returns [`None`] if the value does not fit the data type.
+#[must_use]
+pub fn zcl_attr_from_json(value: &Value, data_type: ZclDataType) -> Option<ZclAttrValue> {
+    fn bytes(value: &Value) -> Option<Vec<u8>> {
+        // node.js buffers are serialized as {"type": "Buffer", "data": [...]}
+        let data = match value {
+            Value::Object(obj) if obj.get("type") == Some(&json!("Buffer")) => obj.get("data")?,
+            _ => value,
+        };
+
+        data.as_array()?
+            .iter()
+            .map(|byte| byte.as_u64().and_then(|b| u8::try_from(b).ok()))
+            .collect()
+    }
+
+    fn bitmap(value: &Value, bits: u32) -> Option<u64> {
+        value.as_u64().filter(|val| bits == 64 || *val < 1 << bits)
+    }
+
+    let int = || value.as_i64();
+    let uint = || value.as_u64();
+
+    let res = match data_type {
+        ZclDataType::Null => value.is_null().then_some(ZclAttrValue::Null)?,
+        ZclDataType::Zcl8bit => ZclAttrValue::X8(int()?.try_into().ok()?),
+        ZclDataType::Zcl16bit => ZclAttrValue::X16(int()?.try_into().ok()?),
+        ZclDataType::Zcl32bit => ZclAttrValue::X32(int()?.try_into().ok()?),
+        ZclDataType::ZclBool => match value {
+            Value::Bool(val) => ZclAttrValue::Bool(*val),
+            _ => ZclAttrValue::Bool(match uint()? {
+                0 => false,
+                1 => true,
+                _ => return None,
+            }),
+        },
+        ZclDataType::Zcl8bitmap => ZclAttrValue::B8(uint()?.try_into().ok()?),
+        ZclDataType::Zcl16bitmap => ZclAttrValue::B16(uint()?.try_into().ok()?),
+        ZclDataType::Zcl32bitmap => ZclAttrValue::B32(uint()?.try_into().ok()?),
+        ZclDataType::Zcl40bitmap => ZclAttrValue::B40(bitmap(value, 40)?),
+        ZclDataType::Zcl48bitmap => ZclAttrValue::B48(bitmap(value, 48)?),
+        ZclDataType::Zcl56bitmap => ZclAttrValue::B56(bitmap(value, 56)?),
+        ZclDataType::Zcl64bitmap => ZclAttrValue::B64(bitmap(value, 64)?),
+        ZclDataType::ZclU8 => ZclAttrValue::U8(uint()?.try_into().ok()?),
+        ZclDataType::ZclU16 => ZclAttrValue::U16(uint()?.try_into().ok()?),
+        ZclDataType::ZclU32 => ZclAttrValue::U32(uint()?.try_into().ok()?),
+        ZclDataType::ZclI16 => ZclAttrValue::I16(int()?.try_into().ok()?),
+        ZclDataType::ZclE8 => ZclAttrValue::E8(uint()?.try_into().ok()?),
+        ZclDataType::ZclBytearray => ZclAttrValue::Bytes(bytes(value)?),
+        ZclDataType::ZclCharstring => ZclAttrValue::String(value.as_str()?.to_string()),
+        ZclDataType::ZclIeeeaddr => {
+            let addr = value.as_str()?.trim_start_matches("0x");
+            ZclAttrValue::IeeeAddr(hex::decode(addr).ok().filter(|addr| addr.len() == 8)?)
+        }
+        ZclDataType::ZclSecurityKey => ZclAttrValue::SecurityKey(bytes(value)?.try_into().ok()?),
+        ZclDataType::ZclInvalid => return None,
+    };
+
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::{Value, json};
+    use tokio::sync::mpsc;
+
+    use bifrost_api::device::{ZclAttrRead, ZclAttrResult, ZclAttrWrite};
+    use zcl::attr::{ZclAttrValue, ZclDataType};
+
+    use crate::backend::z2m::zclcommand::{
+        PendingZclRead, zcl_attr_from_json, zcl_attr_to_json, zcl_read, zcl_write,
+    };
+
+    #[test]
+    fn read_request() {
+        let req = ZclAttrRead {
+            cluster: 0xFC03,
+            attributes: vec![0x0002, 0x0011],
+            types: BTreeMap::new(),
+            mfc: Some(0x100B),
+        };
+
+        assert_eq!(
+            zcl_read(&req),
+            json!({
+                "read": {
+                    "cluster": 0xFC03,
+                    "attributes": [2, 17],
+                    "options": {"manufacturerCode": 0x100B},
+                    "state_property": "bifrost_zcl",
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn read_request_without_mfc() {
+        let req = ZclAttrRead {
+            cluster: 0x0006,
+            attributes: vec![0x4003],
+            types: BTreeMap::new(),
+            mfc: None,
+        };
+
+        assert_eq!(zcl_read(&req)["read"]["options"], json!({}));
+    }
+
+    #[test]
+    fn write_request() {
+        let req = ZclAttrWrite {
+            cluster: 0x0008,
+            attributes: BTreeMap::from([
+                (0x4000, ZclAttrValue::U8(0xFF)),
+                (0x0010, ZclAttrValue::U16(4)),
+            ]),
+            mfc: None,
+        };
+
+        assert_eq!(
+            zcl_write(&req),
+            json!({
+                "write": {
+                    "cluster": 8,
+                    "payload": {
+                        "16": {"value": 4, "type": 0x21},
+                        "16384": {"value": 255, "type": 0x20},
+                    },
+                    "options": {},
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn attr_from_json_typed() {
+        for (value, data_type, expected) in [
+            (json!(null), ZclDataType::Null, ZclAttrValue::Null),
+            (json!(-5), ZclDataType::Zcl8bit, ZclAttrValue::X8(-5)),
+            (json!(-300), ZclDataType::Zcl16bit, ZclAttrValue::X16(-300)),
+            (
+                json!(-70000),
+                ZclDataType::Zcl32bit,
+                ZclAttrValue::X32(-70000),
+            ),
+            (json!(true), ZclDataType::ZclBool, ZclAttrValue::Bool(true)),
+            (json!(0), ZclDataType::ZclBool, ZclAttrValue::Bool(false)),
+            (json!(0x81), ZclDataType::Zcl8bitmap, ZclAttrValue::B8(0x81)),
+            (
+                json!(0x0B),
+                ZclDataType::Zcl16bitmap,
+                ZclAttrValue::B16(0x0B),
+            ),
+            (json!(7), ZclDataType::Zcl32bitmap, ZclAttrValue::B32(7)),
+            (
+                json!(1u64 << 39),
+                ZclDataType::Zcl40bitmap,
+                ZclAttrValue::B40(1 << 39),
+            ),
+            (
+                json!(u64::MAX),
+                ZclDataType::Zcl64bitmap,
+                ZclAttrValue::B64(u64::MAX),
+            ),
+            (json!(254), ZclDataType::ZclU8, ZclAttrValue::U8(254)),
+            (json!(3), ZclDataType::ZclU16, ZclAttrValue::U16(3)),
+            (json!(3), ZclDataType::ZclU32, ZclAttrValue::U32(3)),
+            (json!(-1), ZclDataType::ZclI16, ZclAttrValue::I16(-1)),
+            (json!(2), ZclDataType::ZclE8, ZclAttrValue::E8(2)),
+            (
+                json!([1, 2]),
+                ZclDataType::ZclBytearray,
+                ZclAttrValue::Bytes(vec![1, 2]),
+            ),
+            (
+                json!({"type": "Buffer", "data": [3, 4]}),
+                ZclDataType::ZclBytearray,
+                ZclAttrValue::Bytes(vec![3, 4]),
+            ),
+            (
+                json!("LCT015"),
+                ZclDataType::ZclCharstring,
+                ZclAttrValue::String("LCT015".to_string()),
+            ),
+            (
+                json!("0x0017880100112233"),
+                ZclDataType::ZclIeeeaddr,
+                ZclAttrValue::IeeeAddr(vec![0x00, 0x17, 0x88, 0x01, 0x00, 0x11, 0x22, 0x33]),
+            ),
+            (
+                json!(vec![0u8; 16]),
+                ZclDataType::ZclSecurityKey,
+                ZclAttrValue::SecurityKey([0; 16]),
+            ),
+        ] {
+            assert_eq!(
+                zcl_attr_from_json(&value, data_type),
+                Some(expected),
+                "{value} as {data_type:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn attr_from_json_mismatch() {
+        for (value, data_type) in [
+            (json!(1), ZclDataType::Null),
+            (json!(128), ZclDataType::Zcl8bit),
+            (json!(2), ZclDataType::ZclBool),
+            (json!(256), ZclDataType::Zcl8bitmap),
+            (json!(1u64 << 40), ZclDataType::Zcl40bitmap),
+            (json!(-1), ZclDataType::ZclU8),
+            (json!(0x10000), ZclDataType::ZclU16),
+            (json!("x"), ZclDataType::ZclU32),
+            (json!([256]), ZclDataType::ZclBytearray),
+            (json!(1), ZclDataType::ZclCharstring),
+            (json!("0x1122"), ZclDataType::ZclIeeeaddr),
+            (json!(vec![0u8; 15]), ZclDataType::ZclSecurityKey),
+            (json!(1), ZclDataType::ZclInvalid),
+        ] {
+            assert_eq!(
+                zcl_attr_from_json(&value, data_type),
+                None,
+                "{value} as {data_type:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn attr_json_roundtrip() {
+        for value in [
+            ZclAttrValue::X8(-1),
+            ZclAttrValue::B16(0xF00F),
+            ZclAttrValue::U32(123_456),
+            ZclAttrValue::E8(4),
+            ZclAttrValue::Bytes(vec![0xAA, 0xBB]),
+            ZclAttrValue::String("name".to_string()),
+            ZclAttrValue::IeeeAddr(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+        ] {
+            let json = zcl_attr_to_json(&value);
+            assert_eq!(zcl_attr_from_json(&json, value.data_type()), Some(value));
+        }
+    }
+
+    #[test]
+    fn pending_read_result() {
+        let (reply, _rx) = mpsc::unbounded_channel();
+        let pending = PendingZclRead {
+            reply,
+            types: BTreeMap::from([
+                (2, ZclDataType::Zcl16bitmap),
+                (3, ZclDataType::ZclU8),
+                (16384, ZclDataType::ZclU8),
+            ]),
+        };
+
+        let res = pending.result(&json!({
+            "2": 11,
+            "3": "not a number",
+            "4": 5,
+            "startUpOnOff": 255,
+        }));
+
+        assert_eq!(
+            res,
+            BTreeMap::from([
+                ("2".to_string(), ZclAttrResult::Value(ZclAttrValue::B16(11))),
+                ("3".to_string(), ZclAttrResult::Raw(json!("not a number"))),
+                ("4".to_string(), ZclAttrResult::Raw(json!(5))),
+                ("startUpOnOff".to_string(), ZclAttrResult::Raw(json!(255))),
+            ])
+        );
+
+        assert!(pending.result(&Value::Null).is_empty());
+    }
+}
@@ -0,0 +1,435 @@
+use std::io::{ErrorKind, Read};
+
+use byteorder::{BE, ByteOrder, LE};
+
+use crate::error::{DecodeError, DecodeResult};
+
+/// A single packet from a capture file
+pub struct Packet {
+    /// Timestamp, in seconds since the unix epoch
+    pub time: f64,
+    pub linktype: u16,
+    pub data: Vec<u8>,
+}
+
+/// Streaming reader for pcap and pcapng capture files
+pub enum CaptureReader<R> {
+    Pcap {
+        rdr: R,
+        le: bool,
+        nanos: bool,
+        linktype: u16,
+    },
+    PcapNg {
+        rdr: R,
+        le: bool,
+        /// Link type and timestamp resolution (in seconds) for each interface
+        interfaces: Vec<(u16, f64)>,
+    },
+}
+
+const PCAPNG_SHB: u32 = 0x0A0D_0D0A;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+const PCAPNG_IDB: u32 = 1;
+const PCAPNG_SPB: u32 = 3;
+const PCAPNG_EPB: u32 = 6;
+
+/// Read exactly `buf.len()` bytes, or return false on a clean end-of-file
+fn read_or_eof(rdr: &mut impl Read, buf: &mut [u8]) -> DecodeResult<bool> {
+    match rdr.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn read_vec(rdr: &mut impl Read, len: usize) -> DecodeResult<Vec<u8>> {
+    let mut data = vec![0; len];
+    rdr.read_exact(&mut data)?;
+    Ok(data)
+}
+
+const fn u16_at(le: bool, data: &[u8]) -> u16 {
+    let bytes = [data[0], data[1]];
+    if le {
+        u16::from_le_bytes(bytes)
+    } else {
+        u16::from_be_bytes(bytes)
+    }
+}
+
+fn u32_at(le: bool, data: &[u8]) -> u32 {
+    if le {
+        LE::read_u32(data)
+    } else {
+        BE::read_u32(data)
+    }
+}
+
+impl<R: Read> CaptureReader<R> {
+    pub fn new(mut rdr: R) -> DecodeResult<Self> {
+        let mut magic = [0; 4];
+        rdr.read_exact(&mut magic)?;
+
+        let (le, nanos) = match magic {
+            [0xD4, 0xC3, 0xB2, 0xA1] => (true, false),
+            [0x4D, 0x3C, 0xB2, 0xA1] => (true, true),
+            [0xA1, 0xB2, 0xC3, 0xD4] => (false, false),
+            [0xA1, 0xB2, 0x3C, 0x4D] => (false, true),
+            _ if u32::from_be_bytes(magic) == PCAPNG_SHB => {
+                let mut res = Self::PcapNg {
+                    rdr,
+                    le: true,
+                    interfaces: vec![],
+                };
+                res.read_section_header()?;
+                return Ok(res);
+            }
+            _ => return Err(DecodeError::UnsupportedFormat(u32::from_be_bytes(magic))),
+        };
+
+        // skip version, timezone, sigfigs and snaplen
+        read_vec(&mut rdr, 16)?;
+        let network = read_vec(&mut rdr, 4)?;
+
+        // the link type is in the lower 16 bits, the upper bits carry fcs info
+        #[allow(clippy::cast_possible_truncation)]
+        let linktype = u32_at(le, &network) as u16;
+
+        Ok(Self::Pcap {
+            rdr,
+            le,
+            nanos,
+            linktype,
+        })
+    }
+
+    /// Parse the rest of a pcapng section header block, after the block type
+    fn read_section_header(&mut self) -> DecodeResult<()> {
+        let Self::PcapNg {
+            rdr,
+            le,
+            interfaces,
+        } = self
+        else {
+            return Ok(());
+        };
+
+        let mut hdr = [0; 8];
+        rdr.read_exact(&mut hdr)?;
+
+        *le = match LE::read_u32(&hdr[4..]) {
+            PCAPNG_BYTE_ORDER_MAGIC => true,
+            _ if BE::read_u32(&hdr[4..]) == PCAPNG_BYTE_ORDER_MAGIC => false,
+            magic => return Err(DecodeError::UnsupportedFormat(magic)),
+        };
+
+        // skip the rest of the block
+        let len = u32_at(*le, &hdr) as usize;
+        read_vec(rdr, len.saturating_sub(12))?;
+
+        interfaces.clear();
+
+        Ok(())
+    }
+
+    pub fn next_packet(&mut self) -> DecodeResult<Option<Packet>> {
+        match self {
+            Self::Pcap {
+                rdr,
+                le,
+                nanos,
+                linktype,
+            } => {
+                let mut hdr = [0; 16];
+                if !read_or_eof(rdr, &mut hdr)? {
+                    return Ok(None);
+                }
+
+                let secs = u32_at(*le, &hdr[0..]);
+                let frac = u32_at(*le, &hdr[4..]);
+                let len = u32_at(*le, &hdr[8..]) as usize;
+                let scale = if *nanos { 1e-9 } else { 1e-6 };
+
+                Ok(Some(Packet {
+                    time: f64::from(frac).mul_add(scale, f64::from(secs)),
+                    linktype: *linktype,
+                    data: read_vec(rdr, len)?,
+                }))
+            }
+
+            Self::PcapNg { .. } => self.next_pcapng_packet(),
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn next_pcapng_packet(&mut self) -> DecodeResult<Option<Packet>> {
+        loop {
+            let Self::PcapNg {
+                rdr,
+                le,
+                interfaces,
+            } = self
+            else {
+                return Ok(None);
+            };
+            let le = *le;
+
+            let mut btype = [0; 4];
+            if !read_or_eof(rdr, &mut btype)? {
+                return Ok(None);
+            }
+
+            // the section header block type is palindromic, so endianness
+            // does not matter here
+            if u32_at(le, &btype) == PCAPNG_SHB {
+                self.read_section_header()?;
+                continue;
+            }
+
+            let len = u32_at(le, &read_vec(rdr, 4)?) as usize;
+
+            // block body, followed by the block length (again)
+            let body = read_vec(rdr, len.saturating_sub(12))?;
+            read_vec(rdr, 4)?;
+
+            if body.len() < 4 {
+                continue;
+            }
+
+            match u32_at(le, &btype) {
+                PCAPNG_IDB => {
+                    let linktype = u16_at(le, &body);
+                    let resol = Self::timestamp_resolution(le, body.get(8..).unwrap_or_default());
+                    interfaces.push((linktype, resol));
+                }
+
+                PCAPNG_EPB if body.len() >= 20 => {
+                    let iface = u32_at(le, &body[0..]) as usize;
+                    let ts = (u64::from(u32_at(le, &body[4..])) << 32)
+                        | u64::from(u32_at(le, &body[8..]));
+                    let caplen = u32_at(le, &body[12..]) as usize;
+                    let Some(&(linktype, resol)) = interfaces.get(iface) else {
+                        continue;
+                    };
+                    let Some(data) = body.get(20..20 + caplen) else {
+                        return Err(DecodeError::Truncated);
+                    };
+
+                    return Ok(Some(Packet {
+                        time: ts as f64 * resol,
+                        linktype,
+                        data: data.to_vec(),
+                    }));
+                }
+
+                PCAPNG_SPB => {
+                    let Some(&(linktype, _)) = interfaces.first() else {
+                        continue;
+                    };
+                    let origlen = u32_at(le, &body) as usize;
+                    let data = &body[4..];
+
+                    return Ok(Some(Packet {
+                        time: 0.0,
+                        linktype,
+                        data: data[..origlen.min(data.len())].to_vec(),
+                    }));
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    /// Find the timestamp resolution (`if_tsresol`) in interface options
+    fn timestamp_resolution(le: bool, mut opts: &[u8]) -> f64 {
+        const IF_TSRESOL: u16 = 9;
+
+        while opts.len() >= 4 {
+            let code = u16_at(le, opts);
+            let len = usize::from(u16_at(le, &opts[2..]));
+            let Some(value) = opts.get(4..4 + len) else {
+                break;
+            };
+
+            if code == IF_TSRESOL && len == 1 {
+                let resol = value[0];
+                let exp = -i32::from(resol & 0x7F);
+                return if resol & 0x80 == 0 {
+                    10f64.powi(exp)
+                } else {
+                    2f64.powi(exp)
+                };
+            }
+
+            // options are padded to 32 bits
+            opts = opts.get(4 + len.next_multiple_of(4)..).unwrap_or_default();
+        }
+
+        1e-6
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::capture::{CaptureReader, Packet};
+    use crate::error::DecodeError;
+
+    const LINKTYPE: u16 = 195;
+    const FRAME: &[u8] = &[
+        0x41, 0x88, 0x01, 0x62, 0x1a, 0xff, 0xff, 0x00, 0x00, 0x12, 0x34,
+    ];
+
+    fn read_all(data: &[u8]) -> Vec<Packet> {
+        let mut rdr = CaptureReader::new(data).unwrap();
+        let mut res = vec![];
+        while let Some(pkt) = rdr.next_packet().unwrap() {
+            res.push(pkt);
+        }
+        res
+    }
+
+    fn u32_bytes(le: bool, value: u32) -> [u8; 4] {
+        if le {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    }
+
+    fn pcap(le: bool, nanos: bool) -> Vec<u8> {
+        let magic = if nanos { 0xA1B2_3C4D } else { 0xA1B2_C3D4 };
+        let frac = if nanos { 500_000_000 } else { 500_000 };
+        let len = u32::try_from(FRAME.len()).unwrap();
+
+        let mut data = vec![];
+        data.extend(u32_bytes(le, magic));
+        // version 2.4, timezone, sigfigs, snaplen
+        data.extend(if le { [2, 0, 4, 0] } else { [0, 2, 0, 4] });
+        data.extend([0; 8]);
+        data.extend(u32_bytes(le, 0xFFFF));
+        data.extend(u32_bytes(le, LINKTYPE.into()));
+
+        for secs in [10, 11] {
+            data.extend(u32_bytes(le, secs));
+            data.extend(u32_bytes(le, frac));
+            data.extend(u32_bytes(le, len));
+            data.extend(u32_bytes(le, len));
+            data.extend(FRAME);
+        }
+        data
+    }
+
+    fn block(btype: u32, body: &[u8]) -> Vec<u8> {
+        let len = u32::try_from(body.len().next_multiple_of(4) + 12).unwrap();
+
+        let mut data = vec![];
+        data.extend(btype.to_le_bytes());
+        data.extend(len.to_le_bytes());
+        data.extend(body);
+        data.resize(data.len().next_multiple_of(4), 0);
+        data.extend(len.to_le_bytes());
+        data
+    }
+
+    fn pcapng() -> Vec<u8> {
+        let len = u32::try_from(FRAME.len()).unwrap();
+
+        // section header: byte order magic, version 1.0, unknown section length
+        let mut shb = vec![];
+        shb.extend(0x1A2B_3C4D_u32.to_le_bytes());
+        shb.extend([1, 0, 0, 0]);
+        shb.extend([0xFF; 8]);
+
+        // interface: link type, snaplen, if_tsresol = 10^-3, end of options
+        let mut idb = vec![];
+        idb.extend(LINKTYPE.to_le_bytes());
+        idb.extend([0, 0]);
+        idb.extend(0xFFFF_u32.to_le_bytes());
+        idb.extend([9, 0, 1, 0, 3, 0, 0, 0]);
+        idb.extend([0, 0, 0, 0]);
+
+        // enhanced packet: interface 0, timestamp 12345 ms
+        let mut epb = vec![];
+        epb.extend(0_u32.to_le_bytes());
+        epb.extend(0_u32.to_le_bytes());
+        epb.extend(12345_u32.to_le_bytes());
+        epb.extend(len.to_le_bytes());
+        epb.extend(len.to_le_bytes());
+        epb.extend(FRAME);
+
+        // simple packet: original length, data
+        let mut spb = vec![];
+        spb.extend(len.to_le_bytes());
+        spb.extend(FRAME);
+
+        [
+            block(0x0A0D_0D0A, &shb),
+            block(1, &idb),
+            // unknown block types are skipped
+            block(0x0BAD, &[1, 2, 3, 4]),
+            block(6, &epb),
+            block(3, &spb),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn pcap_formats() {
+        for le in [true, false] {
+            for nanos in [true, false] {
+                let pkts = read_all(&pcap(le, nanos));
+
+                assert_eq!(pkts.len(), 2);
+                for (pkt, time) in pkts.iter().zip([10.5, 11.5]) {
+                    assert_eq!(pkt.linktype, LINKTYPE);
+                    assert_eq!(pkt.data, FRAME);
+                    assert!((pkt.time - time).abs() < 1e-6, "{}", pkt.time);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pcap_truncated() {
+        let data = pcap(true, false);
+        let mut rdr = CaptureReader::new(&data[..data.len() - 1]).unwrap();
+
+        assert!(rdr.next_packet().unwrap().is_some());
+        assert!(rdr.next_packet().is_err());
+    }
+
+    #[test]
+    fn pcapng_packets() {
+        let pkts = read_all(&pcapng());
+
+        assert_eq!(pkts.len(), 2);
+
+        assert_eq!(pkts[0].linktype, LINKTYPE);
+        assert_eq!(pkts[0].data, FRAME);
+        assert!((pkts[0].time - 12.345).abs() < 1e-9);
+
+        assert_eq!(pkts[1].linktype, LINKTYPE);
+        assert_eq!(pkts[1].data, FRAME);
+    }
+
+    #[test]
+    fn pcapng_packet_before_interface() {
+        let data = pcapng();
+        // drop the interface description block (shb: 28 bytes, idb: 32 bytes)
+        let data = [&data[..28], &data[28 + 32..]].concat();
+
+        assert!(read_all(&data).is_empty());
+    }
+
+    #[test]
+    fn unsupported_format() {
+        let res = CaptureReader::new(&b"\x7fELF...."[..]);
+        assert!(matches!(
+            res,
+            Err(DecodeError::UnsupportedFormat(0x7f45_4c46))
+        ));
+    }
+}
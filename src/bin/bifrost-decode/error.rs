@@ -0,0 +1,32 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DecodeError {
+    /* mapped errors */
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    ZclError(#[from] zcl::error::ZclError),
+
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    HexError(#[from] hex::FromHexError),
+
+    /* decoder errors */
+    #[error("Unsupported capture file format (magic {0:08x})")]
+    UnsupportedFormat(u32),
+
+    #[error("Truncated packet or capture file")]
+    Truncated,
+
+    #[error("Invalid network key (expected 16 bytes, as hex)")]
+    InvalidKey,
+
+    #[error("Failed to decrypt frame (wrong network key?)")]
+    DecryptFailed,
+}
+
+pub type DecodeResult<T> = Result<T, DecodeError>;
@@ -0,0 +1,200 @@
+//! Unwrapping of captured packets, down to raw IEEE 802.15.4 frames
+
+const LINKTYPE_NULL: u16 = 0;
+const LINKTYPE_ETHERNET: u16 = 1;
+const LINKTYPE_RAW: u16 = 101;
+const LINKTYPE_LOOP: u16 = 108;
+const LINKTYPE_LINUX_SLL: u16 = 113;
+const LINKTYPE_IEEE802_15_4_WITHFCS: u16 = 195;
+const LINKTYPE_IPV4: u16 = 228;
+const LINKTYPE_IEEE802_15_4_NOFCS: u16 = 230;
+const LINKTYPE_LINUX_SLL2: u16 = 276;
+const LINKTYPE_IEEE802_15_4_TAP: u16 = 283;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+
+const IPPROTO_UDP: u8 = 17;
+
+fn be16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([
+        *data.get(offset)?,
+        *data.get(offset + 1)?,
+    ]))
+}
+
+fn le16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes([
+        *data.get(offset)?,
+        *data.get(offset + 1)?,
+    ]))
+}
+
+/// Strip `fcs` trailing bytes (frame check sequence) from a frame
+fn strip_fcs(data: &[u8], fcs: usize) -> Option<&[u8]> {
+    data.get(..data.len().checked_sub(fcs)?)
+}
+
+/// Find the IEEE 802.15.4 frame (without FCS) in a captured packet
+///
+/// Besides the native 802.15.4 link types, this supports ZEP (Zigbee
+/// Encapsulation Protocol) packets over UDP, as sent by network sniffers.
+#[must_use]
+pub fn ieee802154_frame(linktype: u16, data: &[u8]) -> Option<&[u8]> {
+    match linktype {
+        LINKTYPE_IEEE802_15_4_WITHFCS => strip_fcs(data, 2),
+        LINKTYPE_IEEE802_15_4_NOFCS => Some(data),
+        LINKTYPE_IEEE802_15_4_TAP => tap_frame(data),
+        LINKTYPE_ETHERNET => {
+            let (ethertype, offset) = match be16(data, 12)? {
+                ETHERTYPE_VLAN => (be16(data, 16)?, 18),
+                ethertype => (ethertype, 14),
+            };
+            (ethertype == ETHERTYPE_IPV4).then_some(())?;
+            ipv4_frame(data.get(offset..)?)
+        }
+        LINKTYPE_NULL | LINKTYPE_LOOP => ipv4_frame(data.get(4..)?),
+        LINKTYPE_LINUX_SLL => (be16(data, 14)? == ETHERTYPE_IPV4)
+            .then(|| ipv4_frame(data.get(16..)?))
+            .flatten(),
+        LINKTYPE_LINUX_SLL2 => (be16(data, 0)? == ETHERTYPE_IPV4)
+            .then(|| ipv4_frame(data.get(20..)?))
+            .flatten(),
+        LINKTYPE_RAW | LINKTYPE_IPV4 => ipv4_frame(data),
+        _ => None,
+    }
+}
+
+/// IEEE 802.15.4 TAP header, followed by the frame
+fn tap_frame(data: &[u8]) -> Option<&[u8]> {
+    const TLV_FCS_TYPE: u16 = 0;
+
+    let hdrlen = usize::from(le16(data, 2)?);
+    let mut fcs = 0;
+
+    let mut pos = 4;
+    while pos + 4 <= hdrlen {
+        let tlv_type = le16(data, pos)?;
+        let tlv_len = usize::from(le16(data, pos + 2)?);
+
+        if tlv_type == TLV_FCS_TYPE {
+            fcs = match data.get(pos + 4)? {
+                1 => 2,
+                2 => 4,
+                _ => 0,
+            };
+        }
+
+        // values are padded to 32 bits
+        pos += 4 + tlv_len.next_multiple_of(4);
+    }
+
+    strip_fcs(data.get(hdrlen..)?, fcs)
+}
+
+fn ipv4_frame(data: &[u8]) -> Option<&[u8]> {
+    let ihl = usize::from(data.first()? & 0x0F) * 4;
+
+    if data.first()? >> 4 != 4 || *data.get(9)? != IPPROTO_UDP {
+        return None;
+    }
+
+    // skip udp header
+    zep_frame(data.get(ihl + 8..)?)
+}
+
+/// Zigbee Encapsulation Protocol (ZEP), version 1 or 2
+fn zep_frame(data: &[u8]) -> Option<&[u8]> {
+    const ZEP_TYPE_DATA: u8 = 1;
+
+    if !data.starts_with(b"EX") {
+        return None;
+    }
+
+    let hdrlen = match (data.get(2)?, data.get(3)?) {
+        (1, _) => 16,
+        (2, &ZEP_TYPE_DATA) => 32,
+        _ => return None,
+    };
+
+    let len = usize::from(*data.get(hdrlen - 1)?);
+
+    // the frame ends with either the FCS, or LQI and CRC status
+    strip_fcs(data.get(hdrlen..hdrlen + len)?, 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::link::ieee802154_frame;
+
+    const FRAME: &[u8] = &[0x41, 0x88, 0x01, 0x62, 0x1a];
+    const FCS: &[u8] = &[0xAA, 0xBB];
+
+    /// ZEP v2 data packet, in a udp datagram, in an ipv4 packet
+    fn zep_ipv4() -> Vec<u8> {
+        let mut zep = b"EX\x02\x01".to_vec();
+        zep.resize(31, 0);
+        zep.push(u8::try_from(FRAME.len() + FCS.len()).unwrap());
+        zep.extend(FRAME);
+        zep.extend(FCS);
+
+        let mut ip = vec![0x45, 0, 0, 0, 0, 0, 0, 0, 64, 17];
+        ip.resize(20, 0);
+        // udp header (ports 17754)
+        ip.extend([0x45, 0x5a, 0x45, 0x5a, 0, 0, 0, 0]);
+        ip.extend(zep);
+        ip
+    }
+
+    #[test]
+    fn native_frames() {
+        let with_fcs = [FRAME, FCS].concat();
+        assert_eq!(ieee802154_frame(195, &with_fcs), Some(FRAME));
+        assert_eq!(ieee802154_frame(230, FRAME), Some(FRAME));
+    }
+
+    #[test]
+    fn tap_frame() {
+        // header length 12, with a single fcs type tlv (16-bit fcs)
+        let mut data = vec![0, 0, 12, 0, 0, 0, 1, 0, 1, 0, 0, 0];
+        data.extend(FRAME);
+        data.extend(FCS);
+
+        assert_eq!(ieee802154_frame(283, &data), Some(FRAME));
+    }
+
+    #[test]
+    fn zep_frames() {
+        let ip = zep_ipv4();
+        assert_eq!(ieee802154_frame(228, &ip), Some(FRAME));
+
+        let mut eth = vec![0; 12];
+        eth.extend([0x08, 0x00]);
+        eth.extend(&ip);
+        assert_eq!(ieee802154_frame(1, &eth), Some(FRAME));
+
+        let mut null = vec![2, 0, 0, 0];
+        null.extend(&ip);
+        assert_eq!(ieee802154_frame(0, &null), Some(FRAME));
+    }
+
+    #[test]
+    fn unsupported_frames() {
+        // not udp
+        let mut ip = zep_ipv4();
+        ip[9] = 6;
+        assert_eq!(ieee802154_frame(228, &ip), None);
+
+        // not zep
+        let mut ip = zep_ipv4();
+        ip[28] = b'X';
+        assert_eq!(ieee802154_frame(228, &ip), None);
+
+        // truncated
+        let ip = zep_ipv4();
+        assert_eq!(ieee802154_frame(228, &ip[..ip.len() - 1]), None);
+
+        // unknown link type
+        assert_eq!(ieee802154_frame(9999, FRAME), None);
+    }
+}
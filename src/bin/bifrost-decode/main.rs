@@ -0,0 +1,254 @@
+#![allow(clippy::cast_possible_truncation)]
+
+mod capture;
+mod error;
+mod link;
+mod zigbee;
+
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, stdin};
+
+use camino::Utf8PathBuf;
+use clap::Parser;
+use serde::Serialize;
+
+use zcl::cluster;
+use zcl::frame::{ZclFrame, ZclFrameDirection};
+
+use crate::capture::{CaptureReader, Packet};
+use crate::error::{DecodeError, DecodeResult};
+use crate::zigbee::{ApsFrame, MacFrame, NetworkKey, NwkFrame, NwkPayload};
+
+#[macro_use]
+extern crate log;
+
+/// Zigbee Device Profile (ZDP) frames are not zcl frames
+const PROFILE_ZDP: u16 = 0x0000;
+
+#[derive(Parser, Debug)]
+#[command(version, long_about = None)]
+#[command(about("Decodes zigbee (and hue-specific) zcl frames from pcap/pcapng captures"))]
+struct Args {
+    /// Capture files to decode (use "-" for stdin)
+    #[arg(required = true)]
+    files: Vec<Utf8PathBuf>,
+
+    /// Zigbee network key, as hex (colons are allowed). Used to decrypt NWK
+    /// layer encryption, if the capture has not already been decrypted.
+    #[arg(short = 'k', long)]
+    network_key: Option<String>,
+
+    /// Output one json object per frame, instead of a text timeline
+    #[arg(long, default_value_t = false)]
+    json: bool,
+
+    /// Only show frames for this cluster id (as hex)
+    #[arg(short, long, value_parser = parse_hex_u16)]
+    cluster: Option<u16>,
+}
+
+fn parse_hex_u16(s: &str) -> Result<u16, std::num::ParseIntError> {
+    u16::from_str_radix(s.trim_start_matches("0x"), 16)
+}
+
+fn parse_network_key(s: &str) -> DecodeResult<NetworkKey> {
+    let key = hex::decode(s.replace(':', ""))?;
+    key.try_into().map_err(|_| DecodeError::InvalidKey)
+}
+
+#[derive(Serialize, Debug)]
+struct Record {
+    index: u64,
+    time: f64,
+    src: String,
+    dst: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    src64: Option<String>,
+    profile: u16,
+    cluster: u16,
+    src_endpoint: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dst_endpoint: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mfc: Option<u16>,
+    seqnr: u8,
+    cmd: u8,
+    direction: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    data: String,
+}
+
+#[derive(Default)]
+struct Stats {
+    packets: u64,
+    frames: u64,
+    encrypted: u64,
+    failed: u64,
+}
+
+struct Decoder {
+    key: Option<NetworkKey>,
+    cluster: Option<u16>,
+    json: bool,
+    stats: Stats,
+}
+
+impl Decoder {
+    fn decode(&mut self, index: u64, pkt: &Packet) -> DecodeResult<Option<Record>> {
+        let Some(data) = link::ieee802154_frame(pkt.linktype, &pkt.data) else {
+            return Ok(None);
+        };
+
+        let Some(mac) = MacFrame::parse(data)? else {
+            return Ok(None);
+        };
+
+        let Some(nwk) = NwkFrame::parse(mac.payload, mac.src64, self.key.as_ref())? else {
+            return Ok(None);
+        };
+
+        let payload = match nwk.payload {
+            NwkPayload::Plain(payload) if nwk.is_data => payload,
+            NwkPayload::Plain(_) => return Ok(None),
+            NwkPayload::Encrypted => {
+                self.stats.encrypted += 1;
+                return Ok(None);
+            }
+        };
+
+        let Some(aps) = ApsFrame::parse(&payload)? else {
+            return Ok(None);
+        };
+
+        if aps.profile == PROFILE_ZDP || self.cluster.is_some_and(|c| c != aps.cluster) {
+            return Ok(None);
+        }
+
+        let mut cur = Cursor::new(aps.payload);
+        let frame = ZclFrame::parse(&mut cur)?;
+        let data = &aps.payload[cur.position() as usize..];
+
+        let (description, error) = match cluster::describe(aps.cluster, &frame, data) {
+            Ok(desc) => (desc, None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+
+        let direction = match frame.flags.direction {
+            ZclFrameDirection::ClientToServer => "c2s",
+            ZclFrameDirection::ServerToClient => "s2c",
+        };
+
+        Ok(Some(Record {
+            index,
+            time: pkt.time,
+            src: format!("{:04x}", nwk.src),
+            dst: format!("{:04x}", nwk.dst),
+            src64: nwk.src64.map(|addr| format!("{addr:016x}")),
+            profile: aps.profile,
+            cluster: aps.cluster,
+            src_endpoint: aps.src_endpoint,
+            dst_endpoint: aps.dst_endpoint,
+            group: aps.group,
+            mfc: frame.mfcode,
+            seqnr: frame.seqnr,
+            cmd: frame.cmd,
+            direction,
+            description,
+            error,
+            data: hex::encode(data),
+        }))
+    }
+
+    fn print(&self, rec: &Record) -> DecodeResult<()> {
+        if self.json {
+            println!("{}", serde_json::to_string(rec)?);
+            return Ok(());
+        }
+
+        let dir = if rec.direction == "c2s" { " :>" } else { "<: " };
+        let desc = match (&rec.description, &rec.error) {
+            (_, Some(err)) => format!("FAILED: {err}"),
+            (Some(desc), None) => desc.clone(),
+            (None, None) => "Unknown".to_string(),
+        };
+
+        println!(
+            "[{:6}] {:.6} [{} -> {}] [{:04x}] {:02x} {dir} {desc} {}",
+            rec.index, rec.time, rec.src, rec.dst, rec.cluster, rec.cmd, rec.data,
+        );
+
+        Ok(())
+    }
+
+    fn process(&mut self, rdr: impl Read) -> DecodeResult<()> {
+        let mut capture = CaptureReader::new(BufReader::new(rdr))?;
+        let mut index = 0;
+
+        while let Some(pkt) = capture.next_packet()? {
+            index += 1;
+            self.stats.packets += 1;
+
+            match self.decode(index, &pkt) {
+                Ok(Some(rec)) => {
+                    self.stats.frames += 1;
+                    self.print(&rec)?;
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    self.stats.failed += 1;
+                    debug!("[{index:6}] Failed to decode packet: {err}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn main() -> DecodeResult<()> {
+    pretty_env_logger::formatted_builder()
+        .filter_level(log::LevelFilter::Info)
+        .parse_default_env()
+        .init();
+
+    let args = Args::parse();
+
+    let mut decoder = Decoder {
+        key: args
+            .network_key
+            .as_deref()
+            .map(parse_network_key)
+            .transpose()?,
+        cluster: args.cluster,
+        json: args.json,
+        stats: Stats::default(),
+    };
+
+    for name in &args.files {
+        if name == "-" {
+            decoder.process(stdin().lock())?;
+        } else {
+            decoder.process(File::open(name)?)?;
+        }
+    }
+
+    let stats = &decoder.stats;
+    info!(
+        "Decoded {} zcl frames from {} packets ({} failed)",
+        stats.frames, stats.packets, stats.failed
+    );
+
+    if stats.encrypted > 0 {
+        warn!(
+            "Skipped {} encrypted frames (use --network-key to decrypt them)",
+            stats.encrypted
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,491 @@
+//! Minimal parsers for the IEEE 802.15.4 MAC, Zigbee NWK and Zigbee APS layers
+
+use byteorder::{LE, ReadBytesExt};
+use openssl::cipher::Cipher;
+use openssl::cipher_ctx::CipherCtx;
+use openssl::error::ErrorStack;
+
+use crate::error::{DecodeError, DecodeResult};
+
+/// Zigbee network key (used for NWK layer encryption)
+pub type NetworkKey = [u8; 16];
+
+const fn take<'a>(rdr: &mut &'a [u8], len: usize) -> DecodeResult<&'a [u8]> {
+    if rdr.len() < len {
+        return Err(DecodeError::Truncated);
+    }
+    let (head, tail) = rdr.split_at(len);
+    *rdr = tail;
+    Ok(head)
+}
+
+/// Decrypt AES-128-CCM* with a 32-bit MIC.
+///
+/// The tag length has to be configured before the key is set, which the
+/// `openssl::symm` helpers do not support.
+fn decrypt_ccm(
+    key: &NetworkKey,
+    nonce: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    mic: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut ctx = CipherCtx::new()?;
+    ctx.decrypt_init(Some(Cipher::aes_128_ccm()), None, None)?;
+    ctx.set_iv_length(nonce.len())?;
+    ctx.set_tag(mic)?;
+    ctx.decrypt_init(None, Some(key), Some(nonce))?;
+    ctx.set_data_len(data.len())?;
+    ctx.cipher_update(aad, None)?;
+
+    let mut res = vec![];
+    ctx.cipher_update_vec(data, &mut res)?;
+    Ok(res)
+}
+
+pub struct MacFrame<'a> {
+    pub src64: Option<u64>,
+    pub payload: &'a [u8],
+}
+
+impl<'a> MacFrame<'a> {
+    const TYPE_DATA: u16 = 1;
+    const ADDR_SHORT: u16 = 2;
+    const ADDR_EXT: u16 = 3;
+
+    /// Parse an IEEE 802.15.4 frame (without FCS). Returns [`None`] for
+    /// anything but unsecured data frames.
+    pub fn parse(mut rdr: &'a [u8]) -> DecodeResult<Option<Self>> {
+        let fcf = rdr.read_u16::<LE>()?;
+        let frame_type = fcf & 0x07;
+        let security = fcf & (1 << 3) != 0;
+        let panid_comp = fcf & (1 << 6) != 0;
+        let dst_mode = (fcf >> 10) & 0x03;
+        let src_mode = (fcf >> 14) & 0x03;
+
+        if frame_type != Self::TYPE_DATA || security {
+            return Ok(None);
+        }
+
+        let _seq = rdr.read_u8()?;
+
+        if dst_mode != 0 {
+            take(&mut rdr, 2)?;
+        }
+
+        match dst_mode {
+            Self::ADDR_SHORT => take(&mut rdr, 2)?,
+            Self::ADDR_EXT => take(&mut rdr, 8)?,
+            _ => &[],
+        };
+
+        if src_mode != 0 && !(panid_comp && dst_mode != 0) {
+            take(&mut rdr, 2)?;
+        }
+
+        let src64 = match src_mode {
+            Self::ADDR_SHORT => {
+                take(&mut rdr, 2)?;
+                None
+            }
+            Self::ADDR_EXT => Some(rdr.read_u64::<LE>()?),
+            _ => None,
+        };
+
+        Ok(Some(Self {
+            src64,
+            payload: rdr,
+        }))
+    }
+}
+
+pub enum NwkPayload {
+    Plain(Vec<u8>),
+    /// Encrypted, and no network key is known
+    Encrypted,
+}
+
+pub struct NwkFrame {
+    pub src: u16,
+    pub dst: u16,
+    pub src64: Option<u64>,
+    pub is_data: bool,
+    pub payload: NwkPayload,
+}
+
+impl NwkFrame {
+    const FRAME_TYPE_DATA: u16 = 0;
+    const PROTOCOL_GREEN_POWER: u16 = 3;
+    const SECURITY_LEVEL: u8 = 0x05;
+    const KEY_ID_NETWORK: u8 = 1;
+    const MIC_LEN: usize = 4;
+
+    /// Parse a Zigbee NWK frame. Returns [`None`] for Green Power frames,
+    /// which use a different frame format.
+    pub fn parse(
+        data: &[u8],
+        mac_src64: Option<u64>,
+        key: Option<&NetworkKey>,
+    ) -> DecodeResult<Option<Self>> {
+        let mut rdr = data;
+
+        let fcf = rdr.read_u16::<LE>()?;
+        if (fcf >> 2) & 0x0F == Self::PROTOCOL_GREEN_POWER {
+            return Ok(None);
+        }
+
+        let is_data = fcf & 0x03 == Self::FRAME_TYPE_DATA;
+        let multicast = fcf & (1 << 8) != 0;
+        let security = fcf & (1 << 9) != 0;
+        let source_route = fcf & (1 << 10) != 0;
+        let has_dst64 = fcf & (1 << 11) != 0;
+        let has_src64 = fcf & (1 << 12) != 0;
+
+        let dst = rdr.read_u16::<LE>()?;
+        let src = rdr.read_u16::<LE>()?;
+        let _radius = rdr.read_u8()?;
+        let _seq = rdr.read_u8()?;
+
+        if has_dst64 {
+            rdr.read_u64::<LE>()?;
+        }
+
+        let mut src64 = if has_src64 {
+            Some(rdr.read_u64::<LE>()?)
+        } else {
+            mac_src64
+        };
+
+        if multicast {
+            rdr.read_u8()?;
+        }
+
+        if source_route {
+            let count = rdr.read_u8()?;
+            let _index = rdr.read_u8()?;
+            take(&mut rdr, usize::from(count) * 2)?;
+        }
+
+        if !security {
+            return Ok(Some(Self {
+                src,
+                dst,
+                src64,
+                is_data,
+                payload: NwkPayload::Plain(rdr.to_vec()),
+            }));
+        }
+
+        // auxiliary security header
+        let control_pos = data.len() - rdr.len();
+        let control = rdr.read_u8()?;
+        let counter = rdr.read_u32::<LE>()?;
+        if control & (1 << 5) != 0 {
+            src64 = Some(rdr.read_u64::<LE>()?);
+        }
+        let key_id = (control >> 3) & 0x03;
+        if key_id == Self::KEY_ID_NETWORK {
+            rdr.read_u8()?;
+        }
+
+        let payload = match (key, src64) {
+            (Some(key), Some(src64)) if key_id == Self::KEY_ID_NETWORK => {
+                let hdr_len = data.len() - rdr.len();
+
+                // the security level is not transmitted, but is always 5
+                // (encryption, with a 32-bit MIC)
+                let control = (control & !0x07) | Self::SECURITY_LEVEL;
+                let mut aad = data[..hdr_len].to_vec();
+                aad[control_pos] = control;
+
+                // the nonce uses the over-the-air (little-endian) byte order
+                let mut nonce = src64.to_le_bytes().to_vec();
+                nonce.extend(counter.to_le_bytes());
+                nonce.push(control);
+
+                let (payload, mic) = rdr.split_at(
+                    rdr.len()
+                        .checked_sub(Self::MIC_LEN)
+                        .ok_or(DecodeError::Truncated)?,
+                );
+
+                let plain = decrypt_ccm(key, &nonce, &aad, payload, mic)
+                    .map_err(|_| DecodeError::DecryptFailed)?;
+
+                NwkPayload::Plain(plain)
+            }
+            _ => NwkPayload::Encrypted,
+        };
+
+        Ok(Some(Self {
+            src,
+            dst,
+            src64,
+            is_data,
+            payload,
+        }))
+    }
+}
+
+pub struct ApsFrame<'a> {
+    pub dst_endpoint: Option<u8>,
+    pub group: Option<u16>,
+    pub cluster: u16,
+    pub profile: u16,
+    pub src_endpoint: u8,
+    pub payload: &'a [u8],
+}
+
+impl<'a> ApsFrame<'a> {
+    const DELIVERY_GROUP: u8 = 3;
+
+    /// Parse a Zigbee APS frame. Returns [`None`] for anything but complete,
+    /// unsecured data frames.
+    pub fn parse(mut rdr: &'a [u8]) -> DecodeResult<Option<Self>> {
+        let fcf = rdr.read_u8()?;
+        let frame_type = fcf & 0x03;
+        let delivery = (fcf >> 2) & 0x03;
+        let security = fcf & (1 << 5) != 0;
+        let ext_header = fcf & (1 << 7) != 0;
+
+        if frame_type != 0 || security {
+            return Ok(None);
+        }
+
+        let (dst_endpoint, group) = if delivery == Self::DELIVERY_GROUP {
+            (None, Some(rdr.read_u16::<LE>()?))
+        } else {
+            (Some(rdr.read_u8()?), None)
+        };
+
+        let cluster = rdr.read_u16::<LE>()?;
+        let profile = rdr.read_u16::<LE>()?;
+        let src_endpoint = rdr.read_u8()?;
+        let _counter = rdr.read_u8()?;
+
+        // fragmented frames are not reassembled
+        if ext_header && rdr.read_u8()? & 0x03 != 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            dst_endpoint,
+            group,
+            cluster,
+            profile,
+            src_endpoint,
+            payload: rdr,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::DecodeError;
+    use crate::zigbee::{ApsFrame, MacFrame, NetworkKey, NwkFrame, NwkPayload};
+
+    const KEY: NetworkKey = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+
+    const SRC64: u64 = 0x0017_8801_0011_2233;
+
+    /// APS frame: unicast to endpoint 11, On/Off cluster, "On" command
+    const APS_ON: &[u8] = &[
+        0x00, 0x0b, 0x06, 0x00, 0x04, 0x01, 0x01, 0x10, 0x01, 0x05, 0x01,
+    ];
+
+    /// NWK data frame from 0x1234 to 0x0000, carrying [`APS_ON`], encrypted
+    /// with [`KEY`] (aux header with extended nonce, frame counter 0x102)
+    const NWK_SECURED: &[u8] = &[
+        0x08, 0x02, 0x00, 0x00, 0x34, 0x12, 0x1e, 0x42, 0x28, 0x02, 0x01, 0x00, 0x00, 0x33, 0x22,
+        0x11, 0x00, 0x01, 0x88, 0x17, 0x00, 0x00, 0x18, 0xf6, 0xfa, 0x8c, 0xcc, 0x85, 0x61, 0xd2,
+        0xb2, 0xe7, 0x46, 0x02, 0x01, 0x1a, 0x21,
+    ];
+
+    fn plain(nwk: &NwkFrame) -> &[u8] {
+        match &nwk.payload {
+            NwkPayload::Plain(data) => data,
+            NwkPayload::Encrypted => panic!("payload is encrypted"),
+        }
+    }
+
+    #[test]
+    fn mac_short_addresses() {
+        let data = [
+            0x41, 0x88, 0x01, 0x62, 0x1a, 0xff, 0xff, 0x00, 0x00, 0xAA, 0xBB,
+        ];
+
+        let mac = MacFrame::parse(&data).unwrap().unwrap();
+        assert_eq!(mac.src64, None);
+        assert_eq!(mac.payload, [0xAA, 0xBB]);
+    }
+
+    #[test]
+    fn mac_extended_source() {
+        let data = [
+            0x41, 0xc8, 0x01, 0x62, 0x1a, 0xff, 0xff, 0x33, 0x22, 0x11, 0x00, 0x01, 0x88, 0x17,
+            0x00, 0xAA,
+        ];
+
+        let mac = MacFrame::parse(&data).unwrap().unwrap();
+        assert_eq!(mac.src64, Some(SRC64));
+        assert_eq!(mac.payload, [0xAA]);
+    }
+
+    #[test]
+    fn mac_without_pan_id_compression() {
+        let data = [
+            0x01, 0x88, 0x01, 0x62, 0x1a, 0xff, 0xff, 0x63, 0x1a, 0x00, 0x00, 0xAA,
+        ];
+
+        let mac = MacFrame::parse(&data).unwrap().unwrap();
+        assert_eq!(mac.payload, [0xAA]);
+    }
+
+    #[test]
+    fn mac_skips_other_frames() {
+        // acknowledgement
+        assert!(MacFrame::parse(&[0x02, 0x00, 0x01]).unwrap().is_none());
+
+        // secured data frame
+        let data = [0x49, 0x88, 0x01, 0x62, 0x1a, 0xff, 0xff, 0x00, 0x00];
+        assert!(MacFrame::parse(&data).unwrap().is_none());
+    }
+
+    #[test]
+    fn mac_truncated() {
+        let data = [0x41, 0x88, 0x01, 0x62, 0x1a, 0xff];
+        assert!(MacFrame::parse(&data).is_err());
+    }
+
+    #[test]
+    fn nwk_plain() {
+        let mut data = vec![
+            0x08, 0x10, 0xfc, 0xff, 0x34, 0x12, 0x1e, 0x42, 0x33, 0x22, 0x11, 0x00, 0x01, 0x88,
+            0x17, 0x00,
+        ];
+        data.extend(APS_ON);
+
+        let nwk = NwkFrame::parse(&data, None, None).unwrap().unwrap();
+        assert_eq!(nwk.src, 0x1234);
+        assert_eq!(nwk.dst, 0xfffc);
+        assert_eq!(nwk.src64, Some(SRC64));
+        assert!(nwk.is_data);
+        assert_eq!(plain(&nwk), APS_ON);
+    }
+
+    #[test]
+    fn nwk_source_route() {
+        let data = [
+            0x08, 0x04, 0x00, 0x00, 0x34, 0x12, 0x1e, 0x42, 0x02, 0x00, 0x01, 0x00, 0x02, 0x00,
+            0xAA,
+        ];
+
+        let nwk = NwkFrame::parse(&data, Some(SRC64), None).unwrap().unwrap();
+        assert_eq!(nwk.src64, Some(SRC64));
+        assert_eq!(plain(&nwk), [0xAA]);
+    }
+
+    #[test]
+    fn nwk_command() {
+        let data = [0x09, 0x00, 0x00, 0x00, 0x34, 0x12, 0x1e, 0x42, 0x08];
+
+        let nwk = NwkFrame::parse(&data, None, None).unwrap().unwrap();
+        assert!(!nwk.is_data);
+    }
+
+    #[test]
+    fn nwk_green_power() {
+        let data = [0x0c, 0x00, 0x00, 0x00];
+        assert!(NwkFrame::parse(&data, None, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn nwk_decrypt() {
+        let nwk = NwkFrame::parse(NWK_SECURED, None, Some(&KEY))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(nwk.src, 0x1234);
+        assert_eq!(nwk.dst, 0x0000);
+        assert_eq!(nwk.src64, Some(SRC64));
+        assert_eq!(plain(&nwk), APS_ON);
+    }
+
+    #[test]
+    fn nwk_without_key() {
+        let nwk = NwkFrame::parse(NWK_SECURED, None, None).unwrap().unwrap();
+        assert!(matches!(nwk.payload, NwkPayload::Encrypted));
+    }
+
+    #[test]
+    fn nwk_wrong_key() {
+        let mut key = KEY;
+        key[0] ^= 0xFF;
+
+        let res = NwkFrame::parse(NWK_SECURED, None, Some(&key));
+        assert!(matches!(res, Err(DecodeError::DecryptFailed)));
+    }
+
+    #[test]
+    fn nwk_tampered() {
+        let mut data = NWK_SECURED.to_vec();
+        // frame counter is part of the nonce
+        data[9] ^= 0x01;
+
+        let res = NwkFrame::parse(&data, None, Some(&KEY));
+        assert!(matches!(res, Err(DecodeError::DecryptFailed)));
+    }
+
+    #[test]
+    fn nwk_truncated() {
+        let res = NwkFrame::parse(&NWK_SECURED[..12], None, Some(&KEY));
+        assert!(matches!(res, Err(DecodeError::IOError(_))));
+    }
+
+    #[test]
+    fn aps_unicast() {
+        let aps = ApsFrame::parse(APS_ON).unwrap().unwrap();
+        assert_eq!(aps.dst_endpoint, Some(0x0b));
+        assert_eq!(aps.group, None);
+        assert_eq!(aps.cluster, 0x0006);
+        assert_eq!(aps.profile, 0x0104);
+        assert_eq!(aps.src_endpoint, 0x01);
+        assert_eq!(aps.payload, [0x01, 0x05, 0x01]);
+    }
+
+    #[test]
+    fn aps_group() {
+        let data = [
+            0x0c, 0x34, 0x12, 0x06, 0x00, 0x04, 0x01, 0x01, 0x10, 0x01, 0x05, 0x00,
+        ];
+
+        let aps = ApsFrame::parse(&data).unwrap().unwrap();
+        assert_eq!(aps.dst_endpoint, None);
+        assert_eq!(aps.group, Some(0x1234));
+        assert_eq!(aps.payload, [0x01, 0x05, 0x00]);
+    }
+
+    #[test]
+    fn aps_skips_other_frames() {
+        // aps command frame
+        assert!(ApsFrame::parse(&[0x01, 0x05]).unwrap().is_none());
+
+        // secured frame
+        assert!(ApsFrame::parse(&[0x20, 0x0b]).unwrap().is_none());
+
+        // first fragment of a fragmented frame
+        let data = [0x80, 0x0b, 0x06, 0x00, 0x04, 0x01, 0x01, 0x10, 0x01, 0x00];
+        assert!(ApsFrame::parse(&data).unwrap().is_none());
+    }
+
+    #[test]
+    fn aps_extended_header_unfragmented() {
+        let data = [0x80, 0x0b, 0x06, 0x00, 0x04, 0x01, 0x01, 0x10, 0x00, 0xAA];
+
+        let aps = ApsFrame::parse(&data).unwrap().unwrap();
+        assert_eq!(aps.payload, [0xAA]);
+    }
+}
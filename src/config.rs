@@ -1,8 +1,16 @@
+use std::fs;
+
 use camino::Utf8Path;
 use config::{Config, ConfigError};
+use serde::de::DeserializeOwned;
+use serde_yml::{Mapping, Value};
 
 pub use bifrost_api::config::*;
 
+use hue::devicedb::{self, DeviceDbEntry};
+
+use crate::error::{ApiError, ApiResult};
+
 pub fn parse(filename: &Utf8Path) -> Result<AppConfig, ConfigError> {
     let settings = Config::builder()
         .set_default("bifrost.state_file", "state.yaml")?
@@ -15,3 +23,210 @@ pub fn parse(filename: &Utf8Path) -> Result<AppConfig, ConfigError> {
 
     settings.try_deserialize()
 }
+
+/// Load a user-supplied device database file, adding its entries to the
+/// built-in device database.
+pub fn load_device_db(filename: &Utf8Path) -> ApiResult<()> {
+    let entries: Vec<DeviceDbEntry> = serde_yml::from_reader(fs::File::open(filename)?)?;
+    log::info!(
+        "Loaded {} entries from device database {filename}",
+        entries.len()
+    );
+    devicedb::register(entries);
+
+    Ok(())
+}
+
+/// Replace the `z2m` section of the config file, leaving everything else as-is.
+///
+/// The rest of the file is kept verbatim, including comments and formatting.
+/// The `z2m` section itself is regenerated, so any comments inside it are lost.
+///
+/// The section is replaced line by line, which only works for a plain
+/// (block style) `z2m:` section. If the result does not parse back to the
+/// same config, with only the `z2m` section changed (e.g. because of anchors,
+/// aliases or a quoted key), the file is left untouched, and an error is
+/// returned.
+pub fn save_z2m(filename: &Utf8Path, z2m: &Z2mConfig) -> ApiResult<()> {
+    let text = fs::read_to_string(filename)?;
+
+    let mut section = Mapping::new();
+    section.insert(Value::from("z2m"), serde_yml::to_value(z2m)?);
+    let section = serde_yml::to_string(&section)?;
+
+    let updated = replace_section(&text, "z2m", &section);
+    if !is_section_update(&text, &updated, "z2m", z2m) {
+        return Err(ApiError::ConfigLayoutUnsupported("z2m".to_string()));
+    }
+
+    let tmp = filename.with_extension("tmp");
+    fs::write(&tmp, updated)?;
+    fs::rename(&tmp, filename)?;
+
+    Ok(())
+}
+
+/// Check that `updated` is `text` with only the top-level section `key`
+/// changed (or added), to hold `value`
+fn is_section_update<T>(text: &str, updated: &str, key: &str, value: &T) -> bool
+where
+    T: DeserializeOwned + PartialEq,
+{
+    let (Ok(mut old), Ok(mut new)) = (
+        serde_yml::from_str::<Mapping>(text),
+        serde_yml::from_str::<Mapping>(updated),
+    ) else {
+        return false;
+    };
+
+    let section = new
+        .remove(key)
+        .and_then(|section| serde_yml::from_value::<T>(section).ok());
+    old.remove(key);
+
+    section.as_ref() == Some(value) && old == new
+}
+
+/// Replace a top-level section in a yaml document, keeping all other lines
+/// as-is. If the section is missing, it is appended.
+fn replace_section(text: &str, key: &str, section: &str) -> String {
+    let is_header = |line: &str| {
+        line.strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '\r', '\n']))
+    };
+
+    // the section body is everything indented, or blank, or a comment
+    let is_body = |line: &str| line.starts_with([' ', '\t', '#']) || line.trim().is_empty();
+
+    let mut out = String::new();
+    let mut lines = text.split_inclusive('\n').peekable();
+    let mut found = false;
+
+    while let Some(line) = lines.next() {
+        if found || !is_header(line) {
+            out.push_str(line);
+            continue;
+        }
+        found = true;
+
+        let mut body = vec![];
+        while let Some(next) = lines.next_if(|next| is_body(next)) {
+            body.push(next);
+        }
+
+        // comments and blank lines right before the next section belong to
+        // that section, so keep those
+        let keep = body
+            .iter()
+            .rev()
+            .take_while(|line| line.starts_with('#') || line.trim().is_empty())
+            .count();
+
+        out.push_str(section);
+        out.extend(body[body.len() - keep..].iter().copied());
+    }
+
+    if !found {
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(section);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::config::{Z2mConfig, Z2mServer, is_section_update, replace_section};
+
+    const SECTION: &str = "z2m:\n  new: {}\n";
+
+    #[test]
+    fn replace_keeps_comments() {
+        let text = "# bifrost config\nbridge:\n  name: x # name\n\n# servers\nz2m:\n  old:\n    # old server\n    url: ws://old\n\n# rooms\nrooms: {}\n";
+        let expected = "# bifrost config\nbridge:\n  name: x # name\n\n# servers\nz2m:\n  new: {}\n\n# rooms\nrooms: {}\n";
+
+        assert_eq!(replace_section(text, "z2m", SECTION), expected);
+    }
+
+    #[test]
+    fn replace_last_section() {
+        let text = "bridge:\n  name: x\nz2m:\n  old:\n    url: ws://old\n";
+        let expected = "bridge:\n  name: x\nz2m:\n  new: {}\n";
+
+        assert_eq!(replace_section(text, "z2m", SECTION), expected);
+    }
+
+    #[test]
+    fn replace_ignores_similar_keys() {
+        let text = "z2m_other: 1\nz2m: {}\n";
+        let expected = "z2m_other: 1\nz2m:\n  new: {}\n";
+
+        assert_eq!(replace_section(text, "z2m", SECTION), expected);
+    }
+
+    #[test]
+    fn replace_missing_section() {
+        let text = "bridge:\n  name: x";
+        let expected = "bridge:\n  name: x\nz2m:\n  new: {}\n";
+
+        assert_eq!(replace_section(text, "z2m", SECTION), expected);
+    }
+
+    fn z2m_config() -> Z2mConfig {
+        let server = Z2mServer {
+            url: "ws://new".parse().unwrap(),
+            group_prefix: None,
+            disable_tls_verify: None,
+            streaming_fps: None,
+            streaming_fallback_fps: None,
+            rename_devices: None,
+        };
+
+        Z2mConfig {
+            servers: BTreeMap::from([("new".to_string(), server)]),
+        }
+    }
+
+    fn update(text: &str) -> Option<String> {
+        let z2m = z2m_config();
+        let section = "z2m:\n  new:\n    url: ws://new\n";
+        let updated = replace_section(text, "z2m", section);
+        is_section_update(text, &updated, "z2m", &z2m).then_some(updated)
+    }
+
+    #[test]
+    fn update_block_section() {
+        let text = "bridge:\n  name: x\nz2m:\n  old:\n    url: ws://old\nrooms: {}\n";
+
+        assert_eq!(
+            update(text).as_deref(),
+            Some("bridge:\n  name: x\nz2m:\n  new:\n    url: ws://new\nrooms: {}\n")
+        );
+    }
+
+    #[test]
+    fn update_rejects_anchored_section() {
+        let text = "z2m: &servers\n  old:\n    url: ws://old\nbackup: *servers\n";
+
+        assert_eq!(update(text), None);
+    }
+
+    #[test]
+    fn update_rejects_multiline_flow_section() {
+        let text = "z2m: {old: {url: ws://old},\nother: {url: ws://other}}\n";
+
+        assert_eq!(update(text), None);
+    }
+
+    #[test]
+    fn update_rejects_quoted_key() {
+        let text = "\"z2m\":\n  old:\n    url: ws://old\n";
+
+        assert_eq!(update(text), None);
+    }
+}
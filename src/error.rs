@@ -2,14 +2,16 @@ use std::num::{ParseIntError, TryFromIntError};
 use std::sync::Arc;
 
 use camino::Utf8PathBuf;
-use hue::api::RType;
+use hue::api::{RType, ResourceLink};
 use thiserror::Error;
 use tokio::task::JoinError;
+use uuid::Uuid;
 
-use bifrost_api::backend::BackendRequest;
 use hue::event::EventBlock;
 use svc::error::SvcError;
 
+use crate::backend::BackendMessage;
+
 #[derive(Error, Debug)]
 pub enum ApiError {
     /* mapped errors */
@@ -56,7 +58,7 @@ pub enum ApiError {
     SendErrorHue(#[from] tokio::sync::broadcast::error::SendError<EventBlock>),
 
     #[error(transparent)]
-    SendErrorZ2m(#[from] tokio::sync::broadcast::error::SendError<Arc<BackendRequest>>),
+    SendErrorZ2m(#[from] tokio::sync::broadcast::error::SendError<Arc<BackendMessage>>),
 
     #[error(transparent)]
     SetLoggerError(#[from] log::SetLoggerError),
@@ -97,6 +99,9 @@ pub enum ApiError {
     #[error(transparent)]
     HueError(#[from] hue::error::HueError),
 
+    #[error(transparent)]
+    ZclError(#[from] zcl::error::ZclError),
+
     #[error(transparent)]
     OpenSslError(#[from] openssl::error::Error),
 
@@ -144,7 +149,7 @@ pub enum ApiError {
 
     /* bifrost errors */
     #[error("Missing auxiliary data resource {0:?}")]
-    AuxNotFound(uuid::Uuid),
+    AuxNotFound(Uuid),
 
     #[error("Cannot parse state file: no version field found")]
     StateVersionNotFound,
@@ -170,8 +175,44 @@ pub enum ApiError {
     #[error("Entertainment Stream desynchronized")]
     EntStreamDesync,
 
+    #[error("Entertainment Stream already active in area {0}")]
+    EntStreamBusy(Uuid),
+
+    #[error("Entertainment Stream cannot be proxied by {0:?}")]
+    EntStreamInvalidProxy(ResourceLink),
+
+    #[error("Invalid entertainment stream recording")]
+    EntRecordingInvalid,
+
+    #[error("Invalid entertainment stream recording name {0:?}")]
+    EntRecordingName(String),
+
+    #[error("Entertainment stream recording is not enabled")]
+    EntRecordingDisabled,
+
+    #[error("Invalid device discovery search code {0:?}: expected an ieee address")]
+    InvalidSearchCode(String),
+
+    #[error("Cannot update section {0:?} of the config file: unsupported yaml layout")]
+    ConfigLayoutUnsupported(String),
+
     #[error("Invalid zigbee message")]
     ZigbeeMessageError,
+
+    #[error("Backend {0:?} is not available")]
+    BackendUnavailable(String),
+
+    #[error("Backend {0:?} already exists")]
+    BackendExists(String),
+
+    #[error("Backend {0:?} not found")]
+    BackendNotFound(String),
+
+    #[error("No reply from backend")]
+    BackendNoReply,
+
+    #[error("Unexpected reply from backend")]
+    BackendUnexpectedReply,
 }
 
 impl From<SvcError> for ApiError {
@@ -1,5 +1,7 @@
 use std::io::Write;
 
+use camino::Utf8PathBuf;
+
 use bifrost::backend;
 use bifrost::config;
 use bifrost::error::ApiResult;
@@ -99,6 +101,10 @@ async fn build_tasks(appstate: &AppState) -> ApiResult<()> {
     );
     mgr.register_function("config-writer", svc).await?;
 
+    // register bridge home state aggregator
+    let svc = server::home_aggregator(appstate.res.clone());
+    mgr.register_function("home-aggregator", svc).await?;
+
     // register version updater
     let svc = server::version_updater(appstate.res.clone(), appstate.updater());
     mgr.register_function("version-updater", svc).await?;
@@ -111,17 +117,25 @@ async fn build_tasks(appstate: &AppState) -> ApiResult<()> {
     let svc = server::entertainment::EntertainmentService::new(
         bconf.ipaddress,
         bconf.entm_port,
+        appstate.config().bifrost.recording_dir.clone(),
         appstate.res.clone(),
+        appstate.ent_session.clone(),
     )?;
     mgr.register_service("entertainment", svc).await?;
 
+    // register backend state monitor
+    let svc = backend::backend_monitor(appstate.clone());
+    mgr.register_function("backend-monitor", svc).await?;
+
     // register all z2m backends as services
     let template = backend::z2m::Z2mServiceTemplate::new(appstate.clone());
-    mgr.register_template("z2m", template).await?;
+    mgr.register_template(backend::Z2M_SERVICE_NAME, template)
+        .await?;
 
     // start named z2m instances, since templated services appear when started
     for name in appstate.config().z2m.servers.keys() {
-        mgr.start(ServiceId::instance("z2m", name)).await?;
+        mgr.start(ServiceId::instance(backend::Z2M_SERVICE_NAME, name))
+            .await?;
     }
 
     // finally, iterate over all services and start them
@@ -163,12 +177,17 @@ async fn run() -> ApiResult<()> {
     #[cfg(feature = "server-banner")]
     server::banner::print()?;
 
-    let config = config::parse("config.yaml".into())?;
+    let conf_file = Utf8PathBuf::from("config.yaml");
+    let config = config::parse(&conf_file)?;
     log::debug!("Configuration loaded successfully");
 
+    if let Some(device_db_file) = &config.bifrost.device_db_file {
+        config::load_device_db(device_db_file)?;
+    }
+
     let (client, future) = ServiceManager::spawn();
 
-    let appstate = AppState::from_config(config, client).await?;
+    let appstate = AppState::from_config(config, conf_file, client).await?;
 
     install_signal_handlers(&appstate)?;
 
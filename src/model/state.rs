@@ -15,6 +15,7 @@ use crate::error::{ApiError, ApiResult};
 pub struct AuxData {
     pub topic: Option<String>,
     pub index: Option<u32>,
+    pub backend: Option<String>,
 }
 
 impl AuxData {
@@ -38,6 +39,14 @@ impl AuxData {
             ..self
         }
     }
+
+    #[must_use]
+    pub fn with_backend(self, backend: &str) -> Self {
+        Self {
+            backend: Some(backend.to_string()),
+            ..self
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -212,6 +221,10 @@ impl State {
         self.aux.insert(id, aux);
     }
 
+    pub fn aux_iter(&self) -> impl Iterator<Item = (&Uuid, &AuxData)> {
+        self.aux.iter()
+    }
+
     #[must_use]
     pub fn try_get(&self, id: &Uuid) -> Option<&Resource> {
         self.res.get(id)
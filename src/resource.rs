@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::io::{Read, Write};
 use std::sync::Arc;
 
@@ -6,22 +6,24 @@ use itertools::Itertools;
 use maplit::btreeset;
 use serde::Serialize;
 use serde_json::json;
-use tokio::sync::Notify;
 use tokio::sync::broadcast::{Receiver, Sender};
+use tokio::sync::{Notify, mpsc};
 use uuid::Uuid;
 
-use bifrost_api::backend::BackendRequest;
+use bifrost_api::backend::{BackendRequest, BackendResponse};
 use hue::api::{
     Bridge, BridgeHome, Device, DeviceArchetype, DeviceProductData, DimmingUpdate, Entertainment,
-    EntertainmentConfiguration, GroupedLight, Light, Metadata, On, RType, Resource, ResourceLink,
-    ResourceRecord, Room, Stub, TimeZone, ZigbeeConnectivity, ZigbeeConnectivityStatus,
-    ZigbeeDeviceDiscovery, ZigbeeDeviceDiscoveryAction, ZigbeeDeviceDiscoveryStatus, Zone,
+    EntertainmentConfiguration, EntertainmentConfigurationStatus, GroupedLight, Light, Metadata,
+    On, RType, Resource, ResourceLink, ResourceRecord, Room, Stub, TimeZone, ZigbeeConnectivity,
+    ZigbeeConnectivityStatus, ZigbeeDeviceDiscovery, ZigbeeDeviceDiscoveryAction,
+    ZigbeeDeviceDiscoveryStatus, Zone,
 };
 use hue::error::{HueError, HueResult};
 use hue::event::EventBlock;
 use hue::version::SwVersion;
 
-use crate::error::ApiResult;
+use crate::backend::{BackendMessage, BackendReply};
+use crate::error::{ApiError, ApiResult};
 use crate::model::state::{AuxData, State};
 use crate::server::hueevents::HueEventStream;
 
@@ -30,7 +32,12 @@ pub struct Resources {
     state: State,
     version: SwVersion,
     state_updates: Arc<Notify>,
-    backend_updates: Sender<Arc<BackendRequest>>,
+    home_updates: Arc<Notify>,
+    /// Rooms containing each device, to find the grouped lights affected by
+    /// a light change
+    rooms_by_device: HashMap<Uuid, BTreeSet<Uuid>>,
+    backend_updates: Sender<Arc<BackendMessage>>,
+    online_backends: BTreeSet<String>,
     hue_event_stream: HueEventStream,
 }
 
@@ -41,13 +48,18 @@ impl Resources {
     #[allow(clippy::new_without_default)]
     #[must_use]
     pub fn new(version: SwVersion, state: State) -> Self {
-        Self {
+        let mut res = Self {
             state,
             version,
             state_updates: Arc::new(Notify::new()),
+            home_updates: Arc::new(Notify::new()),
+            rooms_by_device: HashMap::new(),
             backend_updates: Sender::new(32),
+            online_backends: BTreeSet::new(),
             hue_event_stream: HueEventStream::new(Self::HUE_EVENTS_BUFFER_SIZE),
-        }
+        };
+        res.index_rooms();
+        res
     }
 
     pub fn update_bridge_version(&mut self, version: SwVersion) {
@@ -78,6 +90,7 @@ impl Resources {
 
     pub fn read(&mut self, rdr: impl Read) -> ApiResult<()> {
         self.state = State::from_reader(rdr)?;
+        self.index_rooms();
         Ok(())
     }
 
@@ -101,6 +114,69 @@ impl Resources {
         self.state.aux_set(link.rid, aux);
     }
 
+    /// Return the ids of all resources created by the named backend
+    #[must_use]
+    pub fn backend_resources(&self, backend: &str) -> Vec<Uuid> {
+        self.state
+            .aux_iter()
+            .filter(|(_, aux)| aux.backend.as_deref() == Some(backend))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Name of the backend that owns the resource, if any
+    #[must_use]
+    pub fn backend_owner(&self, id: &Uuid) -> Option<&str> {
+        self.state
+            .aux_get(id)
+            .ok()
+            .and_then(|aux| aux.backend.as_deref())
+    }
+
+    /// Set the zigbee connectivity status of every device owned by a backend
+    pub fn set_backend_connectivity(
+        &mut self,
+        backend: &str,
+        status: ZigbeeConnectivityStatus,
+    ) -> ApiResult<()> {
+        if status == ZigbeeConnectivityStatus::Connected {
+            self.online_backends.insert(backend.to_string());
+        } else {
+            self.online_backends.remove(backend);
+        }
+
+        for id in self.backend_resources(backend) {
+            let Ok(dev) = self.get_id::<Device>(id) else {
+                continue;
+            };
+
+            let links = dev
+                .services
+                .iter()
+                .filter(|link| link.rtype == RType::ZigbeeConnectivity)
+                .copied()
+                .collect_vec();
+
+            for link in links {
+                self.update(&link.rid, |zbc: &mut ZigbeeConnectivity| {
+                    zbc.status = status;
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns true, unless the device is known to be disconnected
+    #[must_use]
+    pub fn is_reachable(&self, dev: &Device) -> bool {
+        !dev.services
+            .iter()
+            .filter(|link| link.rtype == RType::ZigbeeConnectivity)
+            .filter_map(|link| self.get::<ZigbeeConnectivity>(link).ok())
+            .any(|zbc| zbc.status == ZigbeeConnectivityStatus::Disconnected)
+    }
+
     pub fn try_update<T: Serialize>(
         &mut self,
         id: &Uuid,
@@ -130,11 +206,125 @@ impl Resources {
             )?);
 
             self.state_updates.notify_one();
+
+            match self.state.get(id)? {
+                Resource::Light(light) => {
+                    let owner = light.owner;
+                    self.aggregate_light_change(&owner)?;
+                }
+                Resource::Room(_) => {
+                    self.index_rooms();
+                    self.aggregate_room(id)?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rebuild the index of rooms by the devices they contain.
+    ///
+    /// Rooms change rarely, so the index is simply rebuilt on every change.
+    fn index_rooms(&mut self) {
+        self.rooms_by_device.clear();
+
+        for (id, obj) in &self.state.res {
+            if let Resource::Room(room) = obj {
+                for child in &room.children {
+                    self.rooms_by_device
+                        .entry(child.rid)
+                        .or_default()
+                        .insert(*id);
+                }
+            }
+        }
+    }
+
+    /// Recompute the grouped lights affected by a light owned by `owner`.
+    ///
+    /// Rooms containing the device are updated right away, while the bridge
+    /// home (which covers every light) is left to [`Self::aggregate_bridge_home`].
+    fn aggregate_light_change(&mut self, owner: &ResourceLink) -> ApiResult<()> {
+        let rooms = self
+            .rooms_by_device
+            .get(&owner.rid)
+            .cloned()
+            .unwrap_or_default();
+
+        for room in &rooms {
+            self.aggregate_room(room)?;
+        }
+
+        self.home_updates.notify_one();
+
+        Ok(())
+    }
+
+    fn aggregate_room(&mut self, id: &Uuid) -> ApiResult<()> {
+        let Ok(Resource::Room(room)) = self.state.get(id) else {
+            return Ok(());
+        };
+
+        let lights = room
+            .children
+            .iter()
+            .filter_map(|dev| self.get::<Device>(dev).ok())
+            .filter_map(Device::light_service)
+            .filter_map(|link| self.get::<Light>(link).ok());
+
+        let targets = self.aggregate_services(&room.services, lights);
+
+        for (id, glight) in targets {
+            self.update(&id, |obj: &mut GroupedLight| *obj = glight)?;
         }
 
         Ok(())
     }
 
+    /// Recompute the grouped light of the bridge home from all lights.
+    ///
+    /// This covers every light in the system, so it runs in the background
+    /// (see [`crate::server::home_aggregator`]) instead of on every change.
+    pub fn aggregate_bridge_home(&mut self) -> ApiResult<()> {
+        let mut targets = vec![];
+
+        for obj in self.state.res.values() {
+            if let Resource::BridgeHome(home) = obj {
+                let lights = self.state.res.values().filter_map(|obj| match obj {
+                    Resource::Light(light) => Some(light),
+                    _ => None,
+                });
+
+                targets.extend(self.aggregate_services(&home.services, lights));
+            }
+        }
+
+        for (id, glight) in targets {
+            self.update(&id, |obj: &mut GroupedLight| *obj = glight)?;
+        }
+
+        Ok(())
+    }
+
+    fn aggregate_services<'a>(
+        &self,
+        services: &BTreeSet<ResourceLink>,
+        lights: impl IntoIterator<Item = &'a Light>,
+    ) -> Vec<(Uuid, GroupedLight)> {
+        let lights: Vec<&Light> = lights.into_iter().collect();
+
+        services
+            .iter()
+            .filter(|s| s.rtype == RType::GroupedLight)
+            .filter_map(|link| {
+                let mut glight = self.get::<GroupedLight>(link).ok()?.clone();
+                glight.aggregate(lights.iter().copied());
+                Some((link.rid, glight))
+            })
+            .collect()
+    }
+
     pub fn update<T: Serialize>(&mut self, id: &Uuid, func: impl FnOnce(&mut T)) -> ApiResult<()>
     where
         for<'a> &'a mut T: TryFrom<&'a mut Resource, Error = HueError>,
@@ -192,6 +382,12 @@ impl Resources {
             return Ok(());
         }
 
+        let owner = match &obj {
+            Resource::Light(light) => Some(light.owner),
+            _ => None,
+        };
+        let is_room = obj.rtype() == RType::Room;
+
         self.state.insert(link.rid, obj);
 
         self.state_updates.notify_one();
@@ -202,6 +398,15 @@ impl Resources {
 
         self.hue_event_stream.hue_event(evt);
 
+        // new lights are included in their grouped lights right away
+        if let Some(owner) = owner {
+            self.aggregate_light_change(&owner)?;
+        }
+
+        if is_room {
+            self.index_rooms();
+        }
+
         Ok(())
     }
 
@@ -243,6 +448,10 @@ impl Resources {
         // Remove resource from state database
         self.state.remove(&link.rid)?;
 
+        if link.rtype == RType::Room {
+            self.index_rooms();
+        }
+
         // Find ids of all resources owned by the deleted node
         let owned_by = self
             .state
@@ -271,6 +480,40 @@ impl Resources {
         Ok(())
     }
 
+    /// Publish the zigbee network settings on the bridge itself
+    pub fn set_zigbee_network(
+        &mut self,
+        channel: u8,
+        extended_pan_id: Option<String>,
+    ) -> ApiResult<()> {
+        let Some(link) = self.bridge_zigbee_connectivity() else {
+            return Ok(());
+        };
+
+        // older state files might not have this resource
+        if self.get::<ZigbeeConnectivity>(&link).is_err() {
+            return Ok(());
+        }
+
+        self.try_update(&link.rid, |zbc: &mut ZigbeeConnectivity| {
+            zbc.channel = Some(ZigbeeConnectivity::channel_value(channel));
+            zbc.extended_pan_id = extended_pan_id;
+            Ok(())
+        })
+    }
+
+    /// The zigbee channel in use, as published on the bridge
+    #[must_use]
+    pub fn zigbee_channel(&self) -> Option<u8> {
+        let link = self.bridge_zigbee_connectivity()?;
+        self.get::<ZigbeeConnectivity>(&link).ok()?.channel_number()
+    }
+
+    fn bridge_zigbee_connectivity(&self) -> Option<ResourceLink> {
+        let bridge = *self.get_resource_ids_by_type(RType::Bridge).first()?;
+        Some(RType::ZigbeeConnectivity.deterministic(bridge))
+    }
+
     pub fn add_bridge(&mut self, bridge_id: String) -> ApiResult<()> {
         let link_bridge = RType::Bridge.deterministic(&bridge_id);
         let link_bridge_home = RType::BridgeHome.deterministic(format!("{bridge_id}HOME"));
@@ -315,8 +558,8 @@ impl Resources {
                 ]
             }),
             dimming: Some(DimmingUpdate { brightness: 8.7 }),
-            color: Some(Stub),
-            color_temperature: Some(Stub),
+            color: None,
+            color_temperature: None,
             color_temperature_delta: Some(Stub),
             dimming_delta: Stub,
             dynamics: Stub,
@@ -345,10 +588,7 @@ impl Resources {
             owner: link_bridge_dev,
             mac_address: String::from("11:22:33:44:55:66:77:88"),
             status: ZigbeeConnectivityStatus::Connected,
-            channel: Some(json!({
-                "status": "set",
-                "value": "channel_25",
-            })),
+            channel: Some(ZigbeeConnectivity::channel_value(25)),
             extended_pan_id: None,
         };
 
@@ -579,23 +819,453 @@ impl Resources {
         self.state_updates.clone()
     }
 
+    /// Notified when a light changes, and the bridge home needs updating
+    #[must_use]
+    pub fn home_channel(&self) -> Arc<Notify> {
+        self.home_updates.clone()
+    }
+
     #[must_use]
     pub const fn hue_event_stream(&self) -> &HueEventStream {
         &self.hue_event_stream
     }
 
     #[must_use]
-    pub fn backend_event_stream(&self) -> Receiver<Arc<BackendRequest>> {
+    pub fn backend_event_stream(&self) -> Receiver<Arc<BackendMessage>> {
         self.backend_updates.subscribe()
     }
 
+    /// Find the resource that decides which backend should handle a request
+    const fn request_target(req: &BackendRequest) -> Option<&ResourceLink> {
+        match req {
+            BackendRequest::LightUpdate(link, _)
+            | BackendRequest::SceneUpdate(link, _)
+            | BackendRequest::GroupedLightUpdate(link, _)
+            | BackendRequest::RoomUpdate(link, _)
+            | BackendRequest::Delete(link)
+            | BackendRequest::DeviceRename(link, _)
+            | BackendRequest::DeviceSoftwareUpdate(link, _)
+            | BackendRequest::DeviceSoftwareCheck(link)
+            | BackendRequest::ZclAttrRead(link, _)
+            | BackendRequest::ZclAttrWrite(link, _) => Some(link),
+            BackendRequest::SceneCreate(_, _, scene) => Some(&scene.group),
+            BackendRequest::EntertainmentStart(_)
+            | BackendRequest::EntertainmentFrame(_)
+            | BackendRequest::EntertainmentStop()
+            | BackendRequest::ZigbeeDeviceDiscovery(_, _)
+            | BackendRequest::BackendInfo(_) => None,
+        }
+    }
+
+    /// Find the backend that should handle a request, if it is not meant for
+    /// all of them
+    fn request_backend<'a>(&'a self, req: &'a BackendRequest) -> Option<&'a str> {
+        match req {
+            BackendRequest::BackendInfo(name) => Some(name),
+            _ => Self::request_target(req).and_then(|link| self.backend_owner(&link.rid)),
+        }
+    }
+
+    /// Find the backends that all need to be online to handle a request
+    fn required_backends<'a>(&'a self, req: &'a BackendRequest) -> BTreeSet<&'a str> {
+        match req {
+            // entertainment areas can span several backends, and they must
+            // all be ready, so the stream starts everywhere at once
+            BackendRequest::EntertainmentStart(area) => self
+                .get_id::<EntertainmentConfiguration>(*area)
+                .map(|ec| {
+                    ec.light_services
+                        .iter()
+                        .filter_map(|link| self.backend_owner(&link.rid))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => self.request_backend(req).into_iter().collect(),
+        }
+    }
+
+    /// Mark an entertainment area as streaming from `streamer`, and ask the
+    /// backends to start rendering it.
+    ///
+    /// If the backends cannot be reached, the area is left inactive.
+    pub fn start_entertainment(&mut self, area: Uuid, streamer: ResourceLink) -> ApiResult<()> {
+        let ec: &EntertainmentConfiguration = self.get_id(area)?;
+        let previous = (ec.active_streamer, ec.status);
+
+        self.update::<EntertainmentConfiguration>(&area, |ec| {
+            ec.active_streamer = Some(streamer);
+            ec.status = EntertainmentConfigurationStatus::Active;
+        })?;
+
+        let res = self.backend_request(BackendRequest::EntertainmentStart(area));
+        if res.is_err() {
+            let (active_streamer, status) = previous;
+            self.update::<EntertainmentConfiguration>(&area, |ec| {
+                ec.active_streamer = active_streamer;
+                ec.status = status;
+            })?;
+        }
+
+        res
+    }
+
+    /// Clear the streaming state of an entertainment area (and its lights),
+    /// and ask the backends to stop rendering it.
+    pub fn stop_entertainment(&mut self, area: Uuid) -> ApiResult<()> {
+        let ec: &EntertainmentConfiguration = self.get_id(area)?;
+        let lights: Vec<Uuid> = ec.light_services.iter().map(|ls| ls.rid).collect();
+        if ec.is_streaming() {
+            self.update(&area, EntertainmentConfiguration::stop_streaming)?;
+        }
+
+        for id in lights {
+            if self.get_id::<Light>(id).is_ok_and(Light::is_streaming) {
+                self.update(&id, Light::stop_streaming)?;
+            }
+        }
+
+        self.backend_request(BackendRequest::EntertainmentStop())
+    }
+
+    /// Stop any entertainment stream that includes lights from `backend`, so
+    /// that streams spanning several backends end everywhere at once.
+    pub fn stop_backend_streaming(&mut self, backend: &str) -> ApiResult<()> {
+        let affected = self
+            .get_resource_ids_by_type(RType::EntertainmentConfiguration)
+            .into_iter()
+            .filter(|id| {
+                self.get_id::<EntertainmentConfiguration>(*id)
+                    .is_ok_and(|ec| {
+                        ec.is_streaming()
+                            && ec
+                                .light_services
+                                .iter()
+                                .any(|link| self.backend_owner(&link.rid) == Some(backend))
+                    })
+            })
+            .collect_vec();
+
+        for area in affected {
+            log::warn!("Backend {backend} dropped out, stopping entertainment stream");
+            self.stop_entertainment(area)?;
+        }
+
+        Ok(())
+    }
+
     pub fn backend_request(&self, req: BackendRequest) -> ApiResult<()> {
+        self.send_backend_message(req, None)
+    }
+
+    /// Send a request that produces a result. The answer can be awaited with
+    /// [`crate::backend::backend_response`].
+    pub fn backend_query(
+        &self,
+        req: BackendRequest,
+    ) -> ApiResult<mpsc::UnboundedReceiver<ApiResult<BackendResponse>>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.send_backend_message(req, Some(tx))?;
+        Ok(rx)
+    }
+
+    fn send_backend_message(
+        &self,
+        req: BackendRequest,
+        reply: Option<BackendReply>,
+    ) -> ApiResult<()> {
         if !matches!(req, BackendRequest::EntertainmentFrame(_)) {
             log::debug!("Backend request: {req:#?}");
         }
 
-        self.backend_updates.send(Arc::new(req))?;
+        for backend in self.required_backends(&req) {
+            if !self.online_backends.contains(backend) {
+                return Err(ApiError::BackendUnavailable(backend.to_string()));
+            }
+        }
+
+        let target = self.request_backend(&req).map(ToString::to_string);
+
+        let mut msg = BackendMessage::new(target, req);
+        if let Some(reply) = reply {
+            msg = msg.with_reply(reply);
+        }
+
+        self.backend_updates.send(Arc::new(msg))?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bifrost_api::backend::BackendRequest;
+    use hue::api::{
+        Device, DeviceArchetype, DeviceProductData, DimmingUpdate, EntertainmentConfiguration,
+        EntertainmentConfigurationStatus, GroupedLight, Light, LightMetadata, LightUpdate,
+        Metadata, On, RType, Resource, ResourceLink, Room, RoomArchetype, RoomMetadata,
+        ZigbeeConnectivityStatus,
+    };
+    use hue::version::SwVersion;
+    use maplit::btreeset;
+    use serde_json::json;
+
+    use crate::error::ApiError;
+    use crate::model::state::{AuxData, State};
+    use crate::resource::Resources;
+
+    fn resources_with_light(backend: &str) -> (Resources, ResourceLink) {
+        let mut res = Resources::new(SwVersion::new(1, String::new()), State::new());
+
+        let link_device = RType::Device.deterministic("device");
+        let link_light = RType::Light.deterministic("light");
+        let metadata = LightMetadata::new(DeviceArchetype::ClassicBulb, "light");
+
+        res.add(
+            &link_light,
+            Resource::Light(Light::new(link_device, metadata)),
+        )
+        .unwrap();
+        res.aux_set(&link_light, AuxData::new().with_backend(backend));
+
+        (res, link_light)
+    }
+
+    #[test]
+    fn request_requires_online_backend() {
+        let (res, link) = resources_with_light("z2m");
+
+        let err = res
+            .backend_request(BackendRequest::LightUpdate(link, LightUpdate::new()))
+            .unwrap_err();
+
+        assert!(matches!(err, ApiError::BackendUnavailable(name) if name == "z2m"));
+    }
+
+    #[test]
+    fn request_sent_to_online_backend() {
+        let (mut res, link) = resources_with_light("z2m");
+        let mut rx = res.backend_event_stream();
+
+        res.set_backend_connectivity("z2m", ZigbeeConnectivityStatus::Connected)
+            .unwrap();
+        res.backend_request(BackendRequest::LightUpdate(link, LightUpdate::new()))
+            .unwrap();
+
+        let msg = rx.try_recv().unwrap();
+        assert_eq!(msg.target.as_deref(), Some("z2m"));
+    }
+
+    #[test]
+    fn request_blocked_after_backend_disconnects() {
+        let (mut res, link) = resources_with_light("z2m");
+        let _rx = res.backend_event_stream();
+
+        res.set_backend_connectivity("z2m", ZigbeeConnectivityStatus::Connected)
+            .unwrap();
+        res.set_backend_connectivity("z2m", ZigbeeConnectivityStatus::Disconnected)
+            .unwrap();
+
+        assert!(
+            res.backend_request(BackendRequest::LightUpdate(link, LightUpdate::new()))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn required_backends() {
+        let (res, link) = resources_with_light("z2m");
+
+        let req = BackendRequest::LightUpdate(link, LightUpdate::new());
+        assert_eq!(
+            res.required_backends(&req).into_iter().collect::<Vec<_>>(),
+            ["z2m"]
+        );
+
+        let req = BackendRequest::BackendInfo("other".to_string());
+        assert_eq!(
+            res.required_backends(&req).into_iter().collect::<Vec<_>>(),
+            ["other"]
+        );
+
+        // requests for resources without an owner go to every backend
+        let req = BackendRequest::Delete(RType::Scene.deterministic("scene"));
+        assert!(res.required_backends(&req).is_empty());
+    }
+
+    fn add_area(res: &mut Resources, light: ResourceLink, status: &str) -> ResourceLink {
+        let link_area = RType::EntertainmentConfiguration.deterministic("area");
+        let area: EntertainmentConfiguration = serde_json::from_value(json!({
+            "name": "area",
+            "configuration_type": "screen",
+            "metadata": {"name": "area"},
+            "status": status,
+            "stream_proxy": {
+                "mode": "auto",
+                "node": RType::Entertainment.deterministic("proxy"),
+            },
+            "locations": {"service_locations": []},
+            "light_services": [light],
+            "channels": [],
+        }))
+        .unwrap();
+        res.add(&link_area, Resource::EntertainmentConfiguration(area))
+            .unwrap();
+        link_area
+    }
+
+    #[test]
+    fn backend_dropout_stops_area() {
+        let (mut res, link) = resources_with_light("z2m");
+        let _rx = res.backend_event_stream();
+        let link_area = add_area(&mut res, link, "active");
+
+        res.stop_backend_streaming("other").unwrap();
+        let ec: &EntertainmentConfiguration = res.get(&link_area).unwrap();
+        assert_eq!(ec.status, EntertainmentConfigurationStatus::Active);
+
+        res.stop_backend_streaming("z2m").unwrap();
+        let ec: &EntertainmentConfiguration = res.get(&link_area).unwrap();
+        assert_eq!(ec.status, EntertainmentConfigurationStatus::Inactive);
+    }
+
+    #[test]
+    fn start_entertainment_offline_backend() {
+        let (mut res, link) = resources_with_light("z2m");
+        let _rx = res.backend_event_stream();
+        let link_area = add_area(&mut res, link, "inactive");
+        let streamer = RType::AuthV1.deterministic("app");
+
+        let err = res
+            .start_entertainment(link_area.rid, streamer)
+            .unwrap_err();
+        assert!(matches!(err, ApiError::BackendUnavailable(name) if name == "z2m"));
+
+        // the failed start must not leave the area claimed
+        let ec: &EntertainmentConfiguration = res.get(&link_area).unwrap();
+        assert_eq!(ec.status, EntertainmentConfigurationStatus::Inactive);
+        assert_eq!(ec.active_streamer, None);
+
+        res.set_backend_connectivity("z2m", ZigbeeConnectivityStatus::Connected)
+            .unwrap();
+        res.start_entertainment(link_area.rid, streamer).unwrap();
+        let ec: &EntertainmentConfiguration = res.get(&link_area).unwrap();
+        assert_eq!(ec.status, EntertainmentConfigurationStatus::Active);
+        assert_eq!(ec.active_streamer, Some(streamer));
+    }
+
+    /// Add a device with a single light, returning the device link
+    fn add_light(res: &mut Resources, name: &str, on: bool) -> ResourceLink {
+        let link_device = RType::Device.deterministic(name);
+        let link_light = RType::Light.deterministic(name);
+
+        let dev = Device {
+            product_data: DeviceProductData::hue_bridge_v2(&SwVersion::new(1, String::new())),
+            metadata: Metadata::new(DeviceArchetype::ClassicBulb, name),
+            services: btreeset![link_light],
+            identify: None,
+            usertest: None,
+        };
+        res.add(&link_device, Resource::Device(dev)).unwrap();
+
+        let mut light = Light::new(
+            link_device,
+            LightMetadata::new(DeviceArchetype::ClassicBulb, name),
+        );
+        light.on = On::new(on);
+        res.add(&link_light, Resource::Light(light)).unwrap();
+
+        link_device
+    }
+
+    /// Add a room with the given devices, returning its grouped light link
+    fn add_room(res: &mut Resources, name: &str, devices: &[ResourceLink]) -> ResourceLink {
+        let link_room = RType::Room.deterministic(name);
+        let link_glight = RType::GroupedLight.deterministic(name);
+
+        let room = Room {
+            children: devices.iter().copied().collect(),
+            metadata: RoomMetadata::new(RoomArchetype::LivingRoom, name),
+            services: btreeset![link_glight],
+        };
+        res.add(&link_room, Resource::Room(room)).unwrap();
+        res.add(
+            &link_glight,
+            Resource::GroupedLight(GroupedLight::new(link_room)),
+        )
+        .unwrap();
+
+        link_glight
+    }
+
+    fn grouped_on(res: &Resources, link: &ResourceLink) -> Option<bool> {
+        res.get::<GroupedLight>(link).unwrap().on.map(|on| on.on)
+    }
+
+    fn home_glight(res: &Resources) -> ResourceLink {
+        let home = res.get_resource_ids_by_type(RType::BridgeHome)[0];
+        RType::GroupedLight.deterministic(home)
+    }
+
+    #[test]
+    fn grouped_light_aggregates_added_light() {
+        let mut res = Resources::new(SwVersion::new(1, String::new()), State::new());
+        res.add_bridge("bridge".to_string()).unwrap();
+
+        let kitchen = add_room(&mut res, "kitchen", &[RType::Device.deterministic("lamp")]);
+        assert_eq!(grouped_on(&res, &kitchen), None);
+
+        add_light(&mut res, "lamp", false);
+
+        assert_eq!(grouped_on(&res, &kitchen), Some(false));
+
+        // the bridge home is only updated in the background
+        assert_eq!(grouped_on(&res, &home_glight(&res)), Some(true));
+        res.aggregate_bridge_home().unwrap();
+        assert_eq!(grouped_on(&res, &home_glight(&res)), Some(false));
+    }
+
+    #[test]
+    fn grouped_light_aggregates_affected_rooms() {
+        let mut res = Resources::new(SwVersion::new(1, String::new()), State::new());
+        res.add_bridge("bridge".to_string()).unwrap();
+
+        let lamp = add_light(&mut res, "lamp", false);
+        let spot = add_light(&mut res, "spot", false);
+        let kitchen = add_room(&mut res, "kitchen", &[lamp]);
+        let hallway = add_room(&mut res, "hallway", &[spot]);
+
+        // make the hallway state stale, to see if it is recomputed
+        res.update(&hallway.rid, |glight: &mut GroupedLight| {
+            glight.dimming = Some(DimmingUpdate::new(12.0));
+        })
+        .unwrap();
+
+        let link_lamp = RType::Light.deterministic("lamp");
+        res.update(&link_lamp.rid, |light: &mut Light| light.on = On::new(true))
+            .unwrap();
+
+        assert_eq!(grouped_on(&res, &kitchen), Some(true));
+        res.aggregate_bridge_home().unwrap();
+        assert_eq!(grouped_on(&res, &home_glight(&res)), Some(true));
+
+        let hallway = res.get::<GroupedLight>(&hallway).unwrap();
+        assert_eq!(hallway.dimming, Some(DimmingUpdate::new(12.0)));
+    }
+
+    #[test]
+    fn grouped_light_aggregates_changed_room() {
+        let mut res = Resources::new(SwVersion::new(1, String::new()), State::new());
+
+        let lamp = add_light(&mut res, "lamp", true);
+        let kitchen = add_room(&mut res, "kitchen", &[]);
+        assert_eq!(grouped_on(&res, &kitchen), None);
+
+        let link_room = RType::Room.deterministic("kitchen");
+        res.update(&link_room.rid, |room: &mut Room| {
+            room.children.insert(lamp);
+        })
+        .unwrap();
+
+        assert_eq!(grouped_on(&res, &kitchen), Some(true));
+    }
+}
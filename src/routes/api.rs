@@ -29,7 +29,7 @@ use hue::legacy_api::{
 
 use crate::error::{ApiError, ApiResult};
 use crate::resource::Resources;
-use crate::routes::auth::{STANDARD_APPLICATION_ID, STANDARD_CLIENT_KEY};
+use crate::routes::auth::{self, STANDARD_APPLICATION_ID, STANDARD_CLIENT_KEY};
 use crate::routes::clip::entertainment_configuration::{self, POSITIONS};
 use crate::routes::extractor::Json;
 use crate::routes::{ApiV1Error, ApiV1Result};
@@ -62,7 +62,7 @@ fn get_lights(res: &MutexGuard<Resources>) -> ApiResult<HashMap<String, ApiLight
         let dev = res.get::<Device>(&light.owner)?;
         lights.insert(
             res.get_id_v1(rr.id)?,
-            ApiLight::from_dev_and_light(&rr.id, dev, &light),
+            ApiLight::from_dev_and_light(&rr.id, dev, &light).with_reachable(res.is_reachable(dev)),
         );
     }
 
@@ -85,18 +85,25 @@ fn get_groups(res: &MutexGuard<Resources>, group_0: bool) -> ApiResult<HashMap<S
             .ok_or(HueError::NotFound(rr.id))?;
 
         let glight = res.get::<GroupedLight>(uuid)?;
-        let lights: Vec<String> = room
+        let light_links: Vec<&ResourceLink> = room
             .children
             .iter()
             .filter_map(|rl| res.get(rl).ok())
             .filter_map(Device::light_service)
+            .collect();
+
+        let lights: Vec<String> = light_links
+            .iter()
             .filter_map(|rl| res.get_id_v1(rl.rid).ok())
             .collect();
 
-        rooms.insert(
-            res.get_id_v1(rr.id)?,
-            ApiGroup::from_lights_and_room(glight, lights, room),
-        );
+        let state =
+            ApiGroupState::from_lights(light_links.iter().filter_map(|rl| res.get(rl).ok()));
+
+        let mut group = ApiGroup::from_lights_and_room(glight, lights, room);
+        group.state = state;
+
+        rooms.insert(res.get_id_v1(rr.id)?, group);
     }
 
     for rr in res.get_resources_by_type(RType::EntertainmentConfiguration) {
@@ -353,7 +360,10 @@ async fn get_api_user_resource_id(
             let light = lock.get::<Light>(&link)?;
             let dev = lock.get::<Device>(&light.owner)?;
 
-            json!(ApiLight::from_dev_and_light(&uuid, dev, light))
+            json!(
+                ApiLight::from_dev_and_light(&uuid, dev, light)
+                    .with_reachable(lock.is_reachable(dev))
+            )
         }
         ApiResourceType::Scenes => {
             let lock = state.res.lock().await;
@@ -417,6 +427,7 @@ async fn put_api_user_resource_id(
             let resp = entertainment_configuration::put_resource_id(
                 &state,
                 rlink,
+                auth::application_for_key(&username),
                 serde_json::to_value(&ecupd)?,
             )
             .await?;
@@ -5,7 +5,9 @@ use axum::routing::get;
 use hyper::HeaderMap;
 use serde_json::json;
 
-use hue::api::HueStreamKey;
+use uuid::{Uuid, uuid};
+
+use hue::api::{HueStreamKey, RType, ResourceLink};
 
 use crate::routes::extractor::Json;
 use crate::server::appstate::AppState;
@@ -15,8 +17,35 @@ pub const STANDARD_APPLICATION_ID: &str = "01010101-0202-0303-0404-050505050505"
 /// This 16-byte key is used for all DTLS entertainment streams
 pub const STANDARD_CLIENT_KEY: HueStreamKey = HueStreamKey::new(*b"BifrostHueTlsKey");
 
-pub async fn auth_v1() -> impl IntoResponse {
-    let value = HeaderValue::from_static(STANDARD_APPLICATION_ID);
+/// Find the application using an application key (the v1 username).
+///
+/// Keys issued by Bifrost are application ids themselves. Any other key
+/// always maps to the same (derived) application id. This is the id reported
+/// by [`auth_v1`], which applications use to identify their entertainment
+/// streams.
+#[must_use]
+pub fn application_for_key(key: &str) -> ResourceLink {
+    Uuid::try_parse(key).map_or_else(
+        |_| RType::AuthV1.deterministic(key),
+        |id| RType::AuthV1.link_to(id),
+    )
+}
+
+/// Find the application making a request, from its `hue-application-key`
+#[must_use]
+pub fn application(headers: &HeaderMap) -> ResourceLink {
+    headers
+        .get("hue-application-key")
+        .and_then(|key| key.to_str().ok())
+        .map_or_else(
+            || RType::AuthV1.link_to(uuid!(STANDARD_APPLICATION_ID)),
+            application_for_key,
+        )
+}
+
+pub async fn auth_v1(req_headers: HeaderMap) -> impl IntoResponse {
+    let app = application(&req_headers);
+    let value = HeaderValue::from_str(&app.rid.to_string()).unwrap();
 
     let mut headers = HeaderMap::new();
     headers.append("hue-application-id", value);
@@ -27,3 +56,35 @@ pub async fn auth_v1() -> impl IntoResponse {
 pub fn router() -> Router<AppState> {
     Router::new().route("/v1", get(auth_v1))
 }
+
+#[cfg(test)]
+mod tests {
+    use hue::api::RType;
+    use hyper::HeaderMap;
+    use uuid::uuid;
+
+    use crate::routes::auth::{STANDARD_APPLICATION_ID, application, application_for_key};
+
+    #[test]
+    fn application_keys() {
+        let standard = RType::AuthV1.link_to(uuid!(STANDARD_APPLICATION_ID));
+
+        // keys issued by bifrost are application ids
+        assert_eq!(application_for_key(STANDARD_APPLICATION_ID), standard);
+
+        // other keys map to a stable id of their own
+        let app = application_for_key("some-app-key");
+        assert_eq!(app.rtype, RType::AuthV1);
+        assert_ne!(app, standard);
+        assert_eq!(application_for_key("some-app-key"), app);
+    }
+
+    #[test]
+    fn application_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(application(&headers).rid, uuid!(STANDARD_APPLICATION_ID));
+
+        headers.insert("hue-application-key", "some-app-key".parse().unwrap());
+        assert_eq!(application(&headers), application_for_key("some-app-key"));
+    }
+}
@@ -1,14 +1,100 @@
+use std::collections::BTreeMap;
+
 use axum::Router;
 use axum::extract::{Path, State};
-use axum::routing::post;
+use axum::routing::get;
 
+use bifrost_api::backend::{BackendInfo, BackendRequest, BackendResponse};
 use bifrost_api::config::Z2mServer;
+use svc::serviceid::ServiceId;
+use svc::traits::ServiceState;
 
-use crate::backend::z2m::Z2mBackend;
+use crate::backend::{self, Z2M_SERVICE_NAME};
+use crate::error::{ApiError, ApiResult};
 use crate::routes::bifrost::BifrostApiResult;
 use crate::routes::extractor::Json;
 use crate::server::appstate::AppState;
 
+/// Stop and unregister the service for a z2m backend, if it exists
+async fn remove_z2m_service(state: &AppState, name: &str) -> ApiResult<()> {
+    let mut mgr = state.manager();
+    let id = ServiceId::instance(Z2M_SERVICE_NAME, name);
+
+    let Ok(svc_state) = mgr.status(id.clone()).await else {
+        return Ok(());
+    };
+
+    if !matches!(svc_state, ServiceState::Stopped | ServiceState::Failed) {
+        mgr.stop(&id).await?;
+        mgr.wait_for_stop(id.clone()).await?;
+    }
+
+    mgr.remove(&id).await?;
+
+    Ok(())
+}
+
+/// Delete all resources created by a backend
+async fn remove_z2m_resources(state: &AppState, name: &str) -> ApiResult<()> {
+    let mut lock = state.res.lock().await;
+
+    for id in lock.backend_resources(name) {
+        // deleting a resource also deletes everything it owns, so some of
+        // these might be gone by now.
+        if let Ok(rr) = lock.get_resource_by_id(&id) {
+            lock.delete(&rr.obj.rtype().link_to(id))?;
+        }
+    }
+    drop(lock);
+
+    Ok(())
+}
+
+async fn get_backends(State(state): State<AppState>) -> Json<BTreeMap<String, Z2mServer>> {
+    let servers = &state.config().z2m.servers;
+    Json(
+        servers
+            .iter()
+            .map(|(name, server)| (name.clone(), server.redacted()))
+            .collect(),
+    )
+}
+
+async fn get_backend_z2m(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> BifrostApiResult<Json<Z2mServer>> {
+    let config = state.config();
+    let server = config
+        .z2m
+        .servers
+        .get(&name)
+        .ok_or(ApiError::BackendNotFound(name))?;
+
+    Ok(Json(server.redacted()))
+}
+
+async fn get_backend_info(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> BifrostApiResult<Json<BackendInfo>> {
+    if !state.config().z2m.servers.contains_key(&name) {
+        return Err(ApiError::BackendNotFound(name).into());
+    }
+
+    let rx = state
+        .res
+        .lock()
+        .await
+        .backend_query(BackendRequest::BackendInfo(name))?;
+
+    let BackendResponse::BackendInfo(info) = backend::backend_response(rx).await? else {
+        return Err(ApiError::BackendUnexpectedReply.into());
+    };
+
+    Ok(Json(*info))
+}
+
 #[axum::debug_handler]
 async fn post_backend_z2m(
     State(state): State<AppState>,
@@ -17,17 +103,83 @@ async fn post_backend_z2m(
 ) -> BifrostApiResult<Json<()>> {
     log::info!("Adding new z2m backend: {name:?}");
 
-    let mut mgr = state.manager();
+    state
+        .update_z2m_config(|z2m| {
+            if z2m.servers.contains_key(&name) {
+                return Err(ApiError::BackendExists(name.clone()));
+            }
+            z2m.servers.insert(name.clone(), server);
+            Ok(())
+        })
+        .await?;
+
+    // the service template picks up the new configuration
+    state
+        .manager()
+        .start(ServiceId::instance(Z2M_SERVICE_NAME, name))
+        .await?;
+
+    Ok(Json(()))
+}
+
+async fn put_backend_z2m(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(server): Json<Z2mServer>,
+) -> BifrostApiResult<Json<()>> {
+    log::info!("Updating z2m backend: {name:?}");
+
+    state
+        .update_z2m_config(|z2m| {
+            let conf = z2m
+                .servers
+                .get_mut(&name)
+                .ok_or_else(|| ApiError::BackendNotFound(name.clone()))?;
+            *conf = server.unredacted(conf);
+            Ok(())
+        })
+        .await?;
+
+    // restart the service, to regenerate it with the new configuration
+    remove_z2m_service(&state, &name).await?;
+    state
+        .manager()
+        .start(ServiceId::instance(Z2M_SERVICE_NAME, name))
+        .await?;
+
+    Ok(Json(()))
+}
+
+async fn delete_backend_z2m(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> BifrostApiResult<Json<()>> {
+    log::info!("Removing z2m backend: {name:?}");
 
-    let svc = Z2mBackend::new(name.clone(), server, state.config(), state.res.clone())?;
-    let name = format!("z2m-{name}");
+    state
+        .update_z2m_config(|z2m| {
+            z2m.servers
+                .remove(&name)
+                .ok_or_else(|| ApiError::BackendNotFound(name.clone()))?;
+            Ok(())
+        })
+        .await?;
 
-    mgr.register_service(&name, svc).await?;
-    mgr.start(&name).await?;
+    remove_z2m_service(&state, &name).await?;
+    remove_z2m_resources(&state, &name).await?;
 
     Ok(Json(()))
 }
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/z2m/{name}", post(post_backend_z2m))
+    Router::new()
+        .route("/", get(get_backends))
+        .route("/{name}/info", get(get_backend_info))
+        .route(
+            "/z2m/{name}",
+            get(get_backend_z2m)
+                .post(post_backend_z2m)
+                .put(put_backend_z2m)
+                .delete(delete_backend_z2m),
+        )
 }
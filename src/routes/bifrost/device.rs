@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use axum::Router;
+use axum::extract::{Path, State};
+use axum::routing::post;
+use uuid::Uuid;
+
+use bifrost_api::backend::{BackendRequest, BackendResponse};
+use bifrost_api::device::{ZclAttrRead, ZclAttrResult, ZclAttrWrite};
+use hue::api::{Device, RType};
+
+use crate::backend;
+use crate::error::ApiError;
+use crate::routes::bifrost::BifrostApiResult;
+use crate::routes::extractor::Json;
+use crate::server::appstate::AppState;
+
+async fn post_zcl_read(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ZclAttrRead>,
+) -> BifrostApiResult<Json<BTreeMap<String, ZclAttrResult>>> {
+    let lock = state.res.lock().await;
+    lock.get_id::<Device>(id)?;
+    let rx = lock.backend_query(BackendRequest::ZclAttrRead(RType::Device.link_to(id), req))?;
+    drop(lock);
+
+    let BackendResponse::ZclAttributes(attrs) = backend::backend_response(rx).await? else {
+        return Err(ApiError::BackendUnexpectedReply.into());
+    };
+
+    Ok(Json(attrs))
+}
+
+async fn post_zcl_write(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ZclAttrWrite>,
+) -> BifrostApiResult<Json<()>> {
+    let lock = state.res.lock().await;
+    lock.get_id::<Device>(id)?;
+    lock.backend_request(BackendRequest::ZclAttrWrite(RType::Device.link_to(id), req))?;
+    drop(lock);
+
+    Ok(Json(()))
+}
+
+async fn post_software_check(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> BifrostApiResult<Json<()>> {
+    let lock = state.res.lock().await;
+    lock.get_id::<Device>(id)?;
+    lock.backend_request(BackendRequest::DeviceSoftwareCheck(
+        RType::Device.link_to(id),
+    ))?;
+    drop(lock);
+
+    Ok(Json(()))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/{id}/software/check", post(post_software_check))
+        .route("/{id}/zcl/read", post(post_zcl_read))
+        .route("/{id}/zcl/write", post(post_zcl_write))
+}
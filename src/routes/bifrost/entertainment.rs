@@ -0,0 +1,140 @@
+use std::fs;
+
+use axum::Router;
+use axum::extract::{Path, State};
+use axum::routing::post;
+use camino::Utf8Path;
+use uuid::{Uuid, uuid};
+
+use bifrost_api::entertainment::{EntertainmentEffectRequest, EntertainmentReplay};
+use hue::api::{EntertainmentConfiguration, RType};
+use svc::serviceid::ServiceId;
+use svc::traits::ServiceState;
+
+use crate::error::{ApiError, ApiResult};
+use crate::routes::auth::STANDARD_APPLICATION_ID;
+use crate::routes::bifrost::BifrostApiResult;
+use crate::routes::extractor::Json;
+use crate::server::appstate::AppState;
+use crate::server::effects::{self, EFFECT_SERVICE_NAME, EffectGenerator};
+use crate::server::entertainment::{EntertainmentService, Session, SessionOwner};
+use crate::server::recording::{self, EntRecorder};
+
+/// Stop and unregister the effect service for an area, if it exists, and
+/// release the entertainment session held by the effect.
+async fn remove_effect_service(state: &AppState, area: Uuid) -> ApiResult<()> {
+    let mut mgr = state.manager();
+    let id = ServiceId::instance(EFFECT_SERVICE_NAME, area.to_string());
+
+    if let Ok(svc_state) = mgr.status(id.clone()).await {
+        if !matches!(svc_state, ServiceState::Stopped | ServiceState::Failed) {
+            mgr.stop(&id).await?;
+            mgr.wait_for_stop(id.clone()).await?;
+        }
+
+        mgr.remove(&id).await?;
+    }
+
+    // a stopped effect loop does not get to release its session itself
+    EntertainmentService::stop_session(&state.res, &state.ent_session, area, SessionOwner::Effect)
+        .await
+}
+
+async fn post_effect(
+    State(state): State<AppState>,
+    Path(area): Path<Uuid>,
+    Json(req): Json<EntertainmentEffectRequest>,
+) -> BifrostApiResult<Json<()>> {
+    log::info!(
+        "Starting entertainment effect {:?} in area {area}",
+        req.effect
+    );
+
+    // clean up after any previous effect, that has since been stopped
+    remove_effect_service(&state, area).await?;
+
+    let lock = state.res.lock().await;
+    let ec: &EntertainmentConfiguration = lock.get_id(area)?;
+    let generator = EffectGenerator::new(ec, req.effect, req.speed.unwrap_or(1.0))?;
+    drop(lock);
+
+    // claim the session, so effects never share an area with a sync app
+    let new = Session {
+        area,
+        owner: SessionOwner::Effect,
+    };
+    let streamer = RType::AuthV1.link_to(uuid!(STANDARD_APPLICATION_ID));
+    EntertainmentService::start_session(&state.res, &state.ent_session, new, streamer).await?;
+
+    let name = ServiceId::instance(EFFECT_SERVICE_NAME, area.to_string());
+    let mut mgr = state.manager();
+    let svc = effects::effect_loop(
+        state.res.clone(),
+        state.ent_session.clone(),
+        area,
+        generator,
+    );
+    let started = async {
+        mgr.register_function(name.to_string(), svc).await?;
+        mgr.start(name).await
+    };
+
+    if let Err(err) = started.await {
+        remove_effect_service(&state, area).await?;
+        return Err(ApiError::from(err).into());
+    }
+
+    Ok(Json(()))
+}
+
+async fn delete_effect(
+    State(state): State<AppState>,
+    Path(area): Path<Uuid>,
+) -> BifrostApiResult<Json<()>> {
+    log::info!("Stopping entertainment effect in area {area}");
+
+    remove_effect_service(&state, area).await?;
+
+    Ok(Json(()))
+}
+
+async fn post_replay(
+    State(state): State<AppState>,
+    Json(req): Json<EntertainmentReplay>,
+) -> BifrostApiResult<Json<Uuid>> {
+    let Some(dir) = state.config().bifrost.recording_dir.clone() else {
+        return Err(ApiError::EntRecordingDisabled.into());
+    };
+
+    // only plain file names are allowed, to stay inside the recording dir
+    let name = Utf8Path::new(&req.name);
+    if name.file_name() != Some(req.name.as_str()) {
+        return Err(ApiError::EntRecordingName(req.name).into());
+    }
+
+    let frames = EntRecorder::parse(&fs::read(dir.join(name))?)?;
+    let Some(area) = frames.first().map(|frame| frame.packet.area) else {
+        return Err(ApiError::EntRecordingInvalid.into());
+    };
+
+    let active = *state.ent_session.lock().await;
+    if let Some(busy) = active {
+        return Err(ApiError::EntStreamBusy(busy.area).into());
+    }
+
+    let res = state.res.clone();
+    let session = state.ent_session.clone();
+    tokio::spawn(async move {
+        if let Err(err) = recording::replay(&res, &session, frames).await {
+            log::error!("Entertainment replay failed: {err}");
+        }
+    });
+
+    Ok(Json(area))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/replay", post(post_replay))
+        .route("/effect/{area}", post(post_effect).delete(delete_effect))
+}
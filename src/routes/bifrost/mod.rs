@@ -1,4 +1,6 @@
 pub mod backend;
+pub mod device;
+pub mod entertainment;
 pub mod service;
 pub mod websocket;
 
@@ -46,13 +48,19 @@ impl IntoResponse for BifrostApiError {
 }
 
 async fn get_config(State(state): State<AppState>) -> BifrostApiResult<Json<AppConfig>> {
-    Ok(Json((*state.config()).clone()))
+    let mut config = (*state.config()).clone();
+    for server in config.z2m.servers.values_mut() {
+        *server = server.redacted();
+    }
+    Ok(Json(config))
 }
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .nest("/service", service::router())
         .nest("/backend", backend::router())
+        .nest("/device", device::router())
+        .nest("/entertainment", entertainment::router())
         .route("/config", get(get_config))
         .route("/ws", any(websocket))
 }
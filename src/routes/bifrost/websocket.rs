@@ -5,12 +5,12 @@ use axum::extract::{State, WebSocketUpgrade};
 use axum::response::Response;
 use tokio::select;
 
-use bifrost_api::backend::BackendRequest;
 use bifrost_api::service::Service;
 use bifrost_api::websocket::Update;
 use hue::event::EventBlock;
 use svc::manager::{ServiceEvent, SvmClient};
 
+use crate::backend::BackendMessage;
 use crate::routes::bifrost::BifrostApiResult;
 use crate::server::appstate::AppState;
 use crate::server::hueevents::HueEventRecord;
@@ -44,10 +44,10 @@ impl WebSocketTask {
 
     fn handle_backend_event(
         &self,
-        backend_event: &Arc<BackendRequest>,
+        backend_event: &Arc<BackendMessage>,
     ) -> BifrostApiResult<Option<Update>> {
         log::info!("Backend event: {backend_event:?}");
-        Ok(Some(Update::BackendRequest((**backend_event).clone())))
+        Ok(Some(Update::BackendRequest(backend_event.request.clone())))
     }
 
     fn handle_hue_event(&self, hue_event: HueEventRecord) -> BifrostApiResult<Option<Update>> {
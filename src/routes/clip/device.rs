@@ -12,6 +12,14 @@ pub async fn put_device(state: &AppState, rlink: ResourceLink, put: Value) -> Ap
 
     let mut lock = state.res.lock().await;
 
+    // each backend decides whether to pass the new name on
+    if let Some(name) = upd.metadata.as_ref().and_then(|md| md.name.as_ref()) {
+        let dev: &Device = lock.get(&rlink)?;
+        if &dev.metadata.name != name {
+            lock.backend_request(BackendRequest::DeviceRename(rlink, name.clone()))?;
+        }
+    }
+
     if let Some(identify) = &upd.identify {
         let dev: &Device = lock.get(&rlink)?;
         if let Some(light) = dev.light_service() {
@@ -0,0 +1,24 @@
+use serde_json::Value;
+
+use bifrost_api::backend::BackendRequest;
+use hue::api::{DeviceSoftwareUpdate, DeviceSoftwareUpdateUpdate, ResourceLink};
+
+use crate::routes::clip::{ApiV2Result, V2Reply};
+use crate::server::appstate::AppState;
+
+pub async fn put_device_software_update(
+    state: &AppState,
+    rlink: ResourceLink,
+    put: Value,
+) -> ApiV2Result {
+    let lock = state.res.lock().await;
+    lock.get::<DeviceSoftwareUpdate>(&rlink)?;
+
+    let upd: DeviceSoftwareUpdateUpdate = serde_json::from_value(put)?;
+
+    lock.backend_request(BackendRequest::DeviceSoftwareUpdate(rlink, upd))?;
+
+    drop(lock);
+
+    V2Reply::ok(rlink)
+}
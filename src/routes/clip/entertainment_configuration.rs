@@ -1,6 +1,6 @@
 use hue::error::HueError;
 use serde_json::Value;
-use uuid::{Uuid, uuid};
+use uuid::Uuid;
 
 use hue::api::{
     Bridge, Device, Entertainment, EntertainmentConfiguration, EntertainmentConfigurationAction,
@@ -12,9 +12,8 @@ use hue::api::{
     LightMode, Position, RType, Resource, ResourceLink,
 };
 
-use crate::error::ApiResult;
+use crate::error::{ApiError, ApiResult};
 use crate::resource::Resources;
-use crate::routes::auth::STANDARD_APPLICATION_ID;
 use crate::routes::clip::{ApiV2Result, V2Reply};
 use crate::server::appstate::AppState;
 
@@ -76,6 +75,10 @@ pub async fn post_resource(state: &AppState, req: Value) -> ApiV2Result {
 
     let auto_node = find_bridge_entertainment(&lock)?;
 
+    if let Some(EntertainmentConfigurationStreamProxyUpdate::Manual { node }) = &new.stream_proxy {
+        check_proxy_node(&lock, node)?;
+    }
+
     let obj = Resource::EntertainmentConfiguration(EntertainmentConfiguration {
         name: new.metadata.name.clone(),
         configuration_type: new.configuration_type,
@@ -128,10 +131,12 @@ fn make_channels(
         let ent: &Entertainment = lock.get(&location.service)?;
 
         if let Some(segs) = &ent.segments {
-            for index in 0..segs.segments.len() {
+            // spread the segments along the positions configured for this light
+            let positions = Position::spread(&location.positions, segs.segments.len());
+            for (index, position) in positions.into_iter().enumerate() {
                 channels.push(EntertainmentConfigurationChannels {
                     channel_id,
-                    position: POSITIONS[index % POSITIONS.len()].clone(),
+                    position,
                     members: vec![EntertainmentConfigurationStreamMembers {
                         service: location.service,
                         index: u16::try_from(index)?,
@@ -171,6 +176,16 @@ fn make_services(
     Ok(res)
 }
 
+/// Make sure a manually chosen proxy node is able to relay streams
+fn check_proxy_node(lock: &Resources, node: &ResourceLink) -> ApiResult<()> {
+    let ent: &Entertainment = lock.get(node)?;
+    if !ent.proxy {
+        return Err(ApiError::EntStreamInvalidProxy(*node));
+    }
+
+    Ok(())
+}
+
 fn find_bridge_entertainment(lock: &Resources) -> ApiResult<ResourceLink> {
     let bridge_id = lock.get_resource_ids_by_type(RType::Bridge)[0];
 
@@ -188,7 +203,14 @@ fn find_bridge_entertainment(lock: &Resources) -> ApiResult<ResourceLink> {
     Ok(bridge_ent)
 }
 
-pub async fn put_resource_id(state: &AppState, rlink: ResourceLink, put: Value) -> ApiV2Result {
+/// Update an entertainment area. Streaming is started on behalf of `app`, the
+/// application making the request.
+pub async fn put_resource_id(
+    state: &AppState,
+    rlink: ResourceLink,
+    app: ResourceLink,
+    put: Value,
+) -> ApiV2Result {
     let upd: EntertainmentConfigurationUpdate = serde_json::from_value(put)?;
 
     let mut lock = state.res.lock().await;
@@ -212,6 +234,16 @@ pub async fn put_resource_id(state: &AppState, rlink: ResourceLink, put: Value)
     }
 
     if let Some(action) = &upd.action {
+        // only one area can stream at a time, so others must be stopped first
+        if matches!(action, EntertainmentConfigurationAction::Start) {
+            for id in lock.get_resource_ids_by_type(RType::EntertainmentConfiguration) {
+                let ec: &EntertainmentConfiguration = lock.get_id(id)?;
+                if id != rlink.rid && ec.is_streaming() {
+                    return Err(ApiError::EntStreamBusy(id));
+                }
+            }
+        }
+
         let ent: &EntertainmentConfiguration = lock.get(&rlink)?;
         let svc = ent.light_services.clone();
 
@@ -225,6 +257,10 @@ pub async fn put_resource_id(state: &AppState, rlink: ResourceLink, put: Value)
 
     let bridge_ent = find_bridge_entertainment(&lock)?;
 
+    if let Some(EntertainmentConfigurationStreamProxyUpdate::Manual { node }) = &upd.stream_proxy {
+        check_proxy_node(&lock, node)?;
+    }
+
     lock.update::<EntertainmentConfiguration>(&rlink.rid, |ec| {
         if let Some(_locations) = upd.locations {
             ec.locations = locations.unwrap();
@@ -256,8 +292,7 @@ pub async fn put_resource_id(state: &AppState, rlink: ResourceLink, put: Value)
         if let Some(action) = upd.action {
             match action {
                 EntertainmentConfigurationAction::Start => {
-                    ec.active_streamer =
-                        Some(RType::AuthV1.link_to(uuid!(STANDARD_APPLICATION_ID)));
+                    ec.active_streamer = Some(app);
                     ec.status = EntertainmentConfigurationStatus::Active;
                 }
                 EntertainmentConfigurationAction::Stop => {
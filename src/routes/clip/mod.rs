@@ -1,4 +1,5 @@
 pub mod device;
+pub mod device_software_update;
 pub mod entertainment_configuration;
 pub mod grouped_light;
 pub mod light;
@@ -11,12 +12,14 @@ use entertainment_configuration as ent_conf;
 
 use axum::Router;
 use axum::extract::{Path, State};
+use axum::http::HeaderMap;
 use axum::routing::{delete, get, post, put};
 use hue::api::{RType, ResourceLink};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::error::{ApiError, ApiResult};
+use crate::routes::auth;
 use crate::routes::extractor::Json;
 use crate::server::appstate::AppState;
 
@@ -138,6 +141,7 @@ pub async fn get_resource_id(
 async fn put_resource_id(
     State(state): State<AppState>,
     Path(rlink): Path<ResourceLink>,
+    headers: HeaderMap,
     Json(put): Json<Value>,
 ) -> ApiV2Result {
     log::info!("PUT {rlink:?}");
@@ -146,7 +150,13 @@ async fn put_resource_id(
     match rlink.rtype {
         /* Allowed + supported */
         RType::Device => device::put_device(&state, rlink, put).await,
-        RType::EntertainmentConfiguration => ent_conf::put_resource_id(&state, rlink, put).await,
+        RType::DeviceSoftwareUpdate => {
+            device_software_update::put_device_software_update(&state, rlink, put).await
+        }
+        RType::EntertainmentConfiguration => {
+            let app = auth::application(&headers);
+            ent_conf::put_resource_id(&state, rlink, app, put).await
+        }
         RType::GroupedLight => grouped_light::put_grouped_light(&state, rlink, put).await,
         RType::Light => light::put_light(&state, rlink, put).await,
         RType::Scene => scene::put_scene(&state, rlink, put).await,
@@ -162,7 +172,6 @@ async fn put_resource_id(
         | RType::CameraMotion
         | RType::Contact
         | RType::DevicePower
-        | RType::DeviceSoftwareUpdate
         | RType::Entertainment
         | RType::GeofenceClient
         | RType::Geolocation
@@ -3,6 +3,8 @@ use serde_json::Value;
 use bifrost_api::backend::BackendRequest;
 use hue::api::{ResourceLink, ZigbeeDeviceDiscovery, ZigbeeDeviceDiscoveryUpdate};
 
+use crate::backend::z2m::discovery::Discovery;
+use crate::error::ApiError;
 use crate::routes::clip::{ApiV2Result, V2Reply};
 use crate::server::appstate::AppState;
 
@@ -16,6 +18,13 @@ pub async fn put_zigbee_device_discovery(
 
     let upd: ZigbeeDeviceDiscoveryUpdate = serde_json::from_value(put)?;
 
+    // touchlink devices can only be selected by their ieee address
+    for code in upd.action.search_codes.iter().flatten() {
+        if Discovery::parse_search_code(code).is_none() {
+            return Err(ApiError::InvalidSearchCode(code.clone()));
+        }
+    }
+
     lock.backend_request(BackendRequest::ZigbeeDeviceDiscovery(rlink, upd))?;
 
     drop(lock);
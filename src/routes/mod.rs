@@ -152,6 +152,12 @@ impl IntoResponse for ApiError {
             | Self::UpdateNotYetSupported(_)
             | Self::DeleteNotYetSupported(_) => StatusCode::FORBIDDEN,
 
+            Self::BackendUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+
+            Self::EntStreamBusy(_) => StatusCode::CONFLICT,
+
+            Self::EntStreamInvalidProxy(_) | Self::InvalidSearchCode(_) => StatusCode::BAD_REQUEST,
+
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
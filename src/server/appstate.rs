@@ -1,31 +1,39 @@
 use std::collections::HashMap;
 use std::fs::{self, File};
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock};
 
-use camino::Utf8Path;
+use camino::{Utf8Path, Utf8PathBuf};
 use chrono::Utc;
 use tokio::sync::Mutex;
 
 use hue::legacy_api::{ApiConfig, ApiShortConfig, Whitelist};
 use svc::manager::SvmClient;
 
-use crate::config::AppConfig;
+use crate::config::{self, AppConfig, Z2mConfig};
 use crate::error::ApiResult;
 use crate::model::state::{State, StateVersion};
 use crate::resource::Resources;
 use crate::server::certificate;
+use crate::server::entertainment::SessionSlot;
 use crate::server::updater::VersionUpdater;
 
 #[derive(Clone)]
 pub struct AppState {
-    conf: Arc<AppConfig>,
+    conf: Arc<RwLock<Arc<AppConfig>>>,
+    conf_file: Utf8PathBuf,
+    conf_update: Arc<Mutex<()>>,
     upd: Arc<Mutex<VersionUpdater>>,
     svm: SvmClient,
     pub res: Arc<Mutex<Resources>>,
+    pub ent_session: SessionSlot,
 }
 
 impl AppState {
-    pub async fn from_config(config: AppConfig, svm: SvmClient) -> ApiResult<Self> {
+    pub async fn from_config(
+        config: AppConfig,
+        conf_file: Utf8PathBuf,
+        svm: SvmClient,
+    ) -> ApiResult<Self> {
         let certfile = &config.bifrost.cert_file;
 
         let certpath = Utf8Path::new(certfile);
@@ -65,20 +73,46 @@ impl AppState {
 
         res.reset_all_streaming()?;
 
-        let conf = Arc::new(config);
+        let conf = Arc::new(RwLock::new(Arc::new(config)));
         let res = Arc::new(Mutex::new(res));
 
         Ok(Self {
             conf,
+            conf_file,
+            conf_update: Arc::new(Mutex::new(())),
             upd,
             svm,
             res,
+            ent_session: SessionSlot::default(),
         })
     }
 
     #[must_use]
     pub fn config(&self) -> Arc<AppConfig> {
-        self.conf.clone()
+        self.conf
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Modify the z2m server configuration, and save it to the config file
+    pub async fn update_z2m_config<T>(
+        &self,
+        func: impl FnOnce(&mut Z2mConfig) -> ApiResult<T>,
+    ) -> ApiResult<T> {
+        // serialize updates, so concurrent changes are not lost
+        let _guard = self.conf_update.lock().await;
+
+        let mut conf = (*self.config()).clone();
+        let res = func(&mut conf.z2m)?;
+
+        let conf_file = self.conf_file.clone();
+        let z2m = conf.z2m.clone();
+        tokio::task::spawn_blocking(move || config::save_z2m(&conf_file, &z2m)).await??;
+
+        *self.conf.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(conf);
+
+        Ok(res)
     }
 
     #[must_use]
@@ -93,20 +127,22 @@ impl AppState {
 
     #[must_use]
     pub async fn api_short_config(&self) -> ApiShortConfig {
-        let mac = self.conf.bridge.mac;
+        let mac = self.config().bridge.mac;
         ApiShortConfig::from_mac_and_version(mac, self.upd.lock().await.get().await)
     }
 
     pub async fn api_config(&self, username: String) -> ApiResult<ApiConfig> {
-        let tz = tzfile::Tz::named(&self.conf.bridge.timezone)?;
+        let conf = self.config();
+        let tz = tzfile::Tz::named(&conf.bridge.timezone)?;
         let localtime = Utc::now().with_timezone(&&tz).naive_local();
+        let zigbeechannel = self.res.lock().await.zigbee_channel();
 
-        let res = ApiConfig {
+        let mut res = ApiConfig {
             short_config: self.api_short_config().await,
-            ipaddress: self.conf.bridge.ipaddress,
-            netmask: self.conf.bridge.netmask,
-            gateway: self.conf.bridge.gateway,
-            timezone: self.conf.bridge.timezone.clone(),
+            ipaddress: conf.bridge.ipaddress,
+            netmask: conf.bridge.netmask,
+            gateway: conf.bridge.gateway,
+            timezone: conf.bridge.timezone.clone(),
             whitelist: HashMap::from([(
                 username,
                 Whitelist {
@@ -119,6 +155,10 @@ impl AppState {
             ..ApiConfig::default()
         };
 
+        if let Some(channel) = zigbeechannel {
+            res.zigbeechannel = channel;
+        }
+
         Ok(res)
     }
 }
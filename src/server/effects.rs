@@ -0,0 +1,363 @@
+use std::f64::consts::TAU;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::sync::Mutex;
+use tokio::time::{Instant, MissedTickBehavior, interval};
+use uuid::Uuid;
+
+use bifrost_api::backend::BackendRequest;
+use bifrost_api::entertainment::EntertainmentEffect;
+use hue::api::{EntertainmentConfiguration, Position};
+use hue::hs::HS;
+use hue::stream::{HueStreamLightsV2, Rgb16, Rgb16V2};
+use hue::xy::XY;
+
+use crate::error::ApiResult;
+use crate::resource::Resources;
+use crate::server::entertainment::{EntertainmentService, SessionOwner, SessionSlot};
+
+/// Service name for effect generators (one instance per entertainment area)
+pub const EFFECT_SERVICE_NAME: &str = "entertainment-effect";
+
+/// Frame rate of generated effects. The backends throttle this further, to
+/// their configured streaming fps.
+const EFFECT_FPS: u64 = 25;
+
+/// Generator for entertainment frames, without the need for a sync app
+pub struct EffectGenerator {
+    effect: EntertainmentEffect,
+    speed: f64,
+    channels: Vec<(u8, Position)>,
+    levels: Vec<f64>,
+    hues: Vec<f64>,
+    beat: Option<u64>,
+    rng: StdRng,
+}
+
+impl EffectGenerator {
+    pub fn new(
+        ec: &EntertainmentConfiguration,
+        effect: EntertainmentEffect,
+        speed: f64,
+    ) -> ApiResult<Self> {
+        let mut channels = vec![];
+        for chan in &ec.channels {
+            channels.push((u8::try_from(chan.channel_id)?, chan.position.clone()));
+        }
+
+        Ok(Self {
+            effect,
+            speed: speed.clamp(0.05, 20.0),
+            levels: vec![1.0; channels.len()],
+            hues: vec![0.0; channels.len()],
+            channels,
+            beat: None,
+            rng: StdRng::from_os_rng(),
+        })
+    }
+
+    fn hue_rgb(hue: f64) -> [f64; 3] {
+        XY::rgb_from_hsl(
+            HS {
+                hue: hue.rem_euclid(1.0),
+                sat: 1.0,
+            },
+            0.5,
+        )
+    }
+
+    /// Move the flicker level of each channel towards a random target
+    fn flicker(&mut self, min: f64, rate: f64) {
+        let rate = (rate * self.speed).min(1.0);
+        for level in &mut self.levels {
+            let target = self.rng.random_range(min..=1.0);
+            *level += (target - *level) * rate;
+        }
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn colors(&mut self, t: f64) -> Vec<[f64; 3]> {
+        let count = self.channels.len() as f64;
+
+        match self.effect {
+            EntertainmentEffect::ColorWave => self
+                .channels
+                .iter()
+                .map(|(_, pos)| {
+                    let phase = TAU * (t * 0.2).mul_add(self.speed, pos.x / 2.0);
+                    let [r, g, b] = Self::hue_rgb(0.15f64.mul_add(phase.sin(), 0.65));
+                    let level = 0.35f64.mul_add((phase * 1.5).cos(), 0.65);
+                    [r * level, g * level, b * level]
+                })
+                .collect(),
+
+            EntertainmentEffect::Fire => {
+                self.flicker(0.3, 0.3);
+                self.levels
+                    .iter()
+                    .map(|level| [*level, 0.25 * level * level, 0.0])
+                    .collect()
+            }
+
+            EntertainmentEffect::Candle => {
+                self.flicker(0.75, 0.1);
+                self.levels
+                    .iter()
+                    .map(|level| [*level, 0.55 * level, 0.15 * level])
+                    .collect()
+            }
+
+            EntertainmentEffect::Rainbow => (0..self.channels.len())
+                .map(|index| Self::hue_rgb((t * 0.1).mul_add(self.speed, index as f64 / count)))
+                .collect(),
+
+            EntertainmentEffect::Party => {
+                let beat = (t * 2.0 * self.speed) as u64;
+                if self.beat != Some(beat) {
+                    self.beat = Some(beat);
+                    for hue in &mut self.hues {
+                        *hue = self.rng.random();
+                    }
+                }
+                self.hues.iter().map(|hue| Self::hue_rgb(*hue)).collect()
+            }
+        }
+    }
+
+    /// Generate the frame for time `t` (in seconds since the effect started)
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn frame(&mut self, t: f64) -> HueStreamLightsV2 {
+        let to_u16 = |value: f64| (value.clamp(0.0, 1.0) * f64::from(u16::MAX)) as u16;
+
+        let lights = self
+            .colors(t)
+            .into_iter()
+            .zip(&self.channels)
+            .map(|([r, g, b], (channel, _))| Rgb16V2 {
+                channel: *channel,
+                rgb: Rgb16 {
+                    r: to_u16(r),
+                    g: to_u16(g),
+                    b: to_u16(b),
+                },
+            })
+            .collect();
+
+        HueStreamLightsV2::Rgb(lights)
+    }
+}
+
+/// Render generated frames to an entertainment area, until it is stopped
+///
+/// The area must already be claimed in `session` by [`SessionOwner::Effect`],
+/// and is released again on every exit path.
+pub async fn effect_loop(
+    res: Arc<Mutex<Resources>>,
+    session: SessionSlot,
+    area: Uuid,
+    generator: EffectGenerator,
+) -> ApiResult<()> {
+    let result = render_effect(&res, area, generator).await;
+
+    EntertainmentService::stop_session(&res, &session, area, SessionOwner::Effect).await?;
+
+    result
+}
+
+async fn render_effect(
+    res: &Mutex<Resources>,
+    area: Uuid,
+    mut generator: EffectGenerator,
+) -> ApiResult<()> {
+    let start = Instant::now();
+    let mut ticker = interval(Duration::from_millis(1000 / EFFECT_FPS));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    loop {
+        ticker.tick().await;
+
+        let lock = res.lock().await;
+
+        // the area can be stopped through the api, which ends the effect
+        if !lock
+            .get_id::<EntertainmentConfiguration>(area)?
+            .is_streaming()
+        {
+            log::info!("Entertainment area stopped, ending effect");
+            break;
+        }
+
+        let frame = generator.frame(start.elapsed().as_secs_f64());
+        lock.backend_request(BackendRequest::EntertainmentFrame(frame))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bifrost_api::entertainment::EntertainmentEffect;
+    use hue::api::{EntertainmentConfiguration, RType};
+    use hue::stream::HueStreamLightsV2;
+    use serde_json::json;
+
+    use crate::server::effects::EffectGenerator;
+
+    const EFFECTS: [EntertainmentEffect; 5] = [
+        EntertainmentEffect::ColorWave,
+        EntertainmentEffect::Fire,
+        EntertainmentEffect::Candle,
+        EntertainmentEffect::Rainbow,
+        EntertainmentEffect::Party,
+    ];
+
+    fn area() -> EntertainmentConfiguration {
+        let channels: Vec<_> = [(0, -1.0), (1, 0.0), (5, 1.0)]
+            .into_iter()
+            .map(|(id, x)| {
+                json!({
+                    "channel_id": id,
+                    "position": {"x": x, "y": 0.0, "z": 0.0},
+                    "members": [],
+                })
+            })
+            .collect();
+
+        serde_json::from_value(json!({
+            "name": "area",
+            "configuration_type": "screen",
+            "metadata": {"name": "area"},
+            "status": "active",
+            "stream_proxy": {
+                "mode": "auto",
+                "node": RType::Entertainment.deterministic("proxy"),
+            },
+            "locations": {"service_locations": []},
+            "light_services": [],
+            "channels": channels,
+        }))
+        .unwrap()
+    }
+
+    fn generator(effect: EntertainmentEffect) -> EffectGenerator {
+        EffectGenerator::new(&area(), effect, 1.0).unwrap()
+    }
+
+    /// Render a frame as (channel, [r, g, b]) tuples
+    fn frame(generator: &mut EffectGenerator, t: f64) -> Vec<(u8, [u16; 3])> {
+        let HueStreamLightsV2::Rgb(lights) = generator.frame(t) else {
+            panic!("effects must generate rgb frames");
+        };
+
+        lights
+            .iter()
+            .map(|light| (light.channel, [light.rgb.r, light.rgb.g, light.rgb.b]))
+            .collect()
+    }
+
+    fn colors(generator: &mut EffectGenerator, t: f64) -> Vec<[u16; 3]> {
+        frame(generator, t)
+            .into_iter()
+            .map(|(_, rgb)| rgb)
+            .collect()
+    }
+
+    #[test]
+    fn frames_cover_all_channels() {
+        for effect in EFFECTS {
+            let mut generator = generator(effect);
+            for step in 0..50 {
+                let channels: Vec<u8> = frame(&mut generator, f64::from(step) * 0.04)
+                    .into_iter()
+                    .map(|(channel, _)| channel)
+                    .collect();
+                assert_eq!(channels, [0, 1, 5], "{effect:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn speed_is_clamped() {
+        let ec = area();
+        let slow = EffectGenerator::new(&ec, EntertainmentEffect::Rainbow, 0.0).unwrap();
+        let fast = EffectGenerator::new(&ec, EntertainmentEffect::Rainbow, 1000.0).unwrap();
+
+        assert!((slow.speed - 0.05).abs() < f64::EPSILON);
+        assert!((fast.speed - 20.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn colorwave_depends_on_time_and_position() {
+        let mut first = generator(EntertainmentEffect::ColorWave);
+        let mut second = generator(EntertainmentEffect::ColorWave);
+
+        let now = colors(&mut first, 1.0);
+        assert_eq!(now, colors(&mut second, 1.0));
+        assert_ne!(now, colors(&mut first, 2.0));
+
+        // channels at different positions are in a different phase
+        assert_ne!(now[0], now[2]);
+
+        // the wave stays in the blue/purple range
+        for [r, _, b] in now {
+            assert!(b >= r);
+        }
+    }
+
+    #[test]
+    fn fire_is_red_and_orange() {
+        let mut generator = generator(EntertainmentEffect::Fire);
+        for step in 0..100 {
+            for [r, g, b] in colors(&mut generator, f64::from(step) * 0.04) {
+                assert!(r > 0);
+                assert!(g <= r / 4 + 1);
+                assert_eq!(b, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn candle_is_warm_and_stays_bright() {
+        let mut generator = generator(EntertainmentEffect::Candle);
+        for step in 0..100 {
+            for [r, g, b] in colors(&mut generator, f64::from(step) * 0.04) {
+                assert!(r >= u16::MAX / 4 * 3);
+                assert!(r > g && g > b);
+            }
+        }
+    }
+
+    #[test]
+    fn rainbow_spreads_and_cycles() {
+        let mut generator = generator(EntertainmentEffect::Rainbow);
+
+        let start = colors(&mut generator, 0.0);
+        assert_ne!(start[0], start[1]);
+        assert_ne!(start[1], start[2]);
+
+        // at speed 1.0, the rainbow completes a cycle every 10 seconds
+        let cycled = colors(&mut generator, 10.0);
+        for (a, b) in start.iter().zip(&cycled) {
+            for (x, y) in a.iter().zip(b) {
+                assert!(x.abs_diff(*y) <= 1, "{start:?} != {cycled:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn party_changes_on_beat() {
+        let mut generator = generator(EntertainmentEffect::Party);
+
+        // two beats per second at speed 1.0
+        let beat = colors(&mut generator, 0.1);
+        assert_eq!(beat, colors(&mut generator, 0.4));
+        assert_ne!(beat, colors(&mut generator, 0.6));
+    }
+}
@@ -7,20 +7,23 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use camino::{Utf8Path, Utf8PathBuf};
 use chrono::Utc;
 use nix::sys::socket;
 use nix::sys::socket::sockopt::RcvBuf;
+use openssl::ex_data::Index;
 use openssl::ssl::{Ssl, SslContext, SslMethod};
 use tokio::io::AsyncReadExt;
 use tokio::net::UdpSocket;
 use tokio::sync::Mutex;
+use tokio::task::JoinSet;
 use tokio::time::timeout;
 use tokio_openssl::SslStream;
 use udp_stream::{UdpListenBuilder, UdpListener, UdpStream};
-use uuid::Uuid;
+use uuid::{Uuid, uuid};
 
 use bifrost_api::backend::BackendRequest;
-use hue::api::{Device, EntertainmentConfiguration, Light, RType};
+use hue::api::{Device, EntertainmentConfiguration, Light, RType, ResourceLink};
 use hue::error::HueError;
 use hue::stream::{
     HueStreamLightsV1, HueStreamLightsV2, HueStreamPacket, HueStreamPacketV1, HueStreamPacketV2,
@@ -30,21 +33,58 @@ use svc::traits::Service;
 
 use crate::error::{ApiError, ApiResult};
 use crate::resource::Resources;
-use crate::routes::auth::STANDARD_CLIENT_KEY;
+use crate::routes::auth::{self, STANDARD_APPLICATION_ID, STANDARD_CLIENT_KEY};
+use crate::server::recording::EntRecorder;
+
+/// Entertainment sessions are stopped, if no frames arrive for this long
+const SESSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Source of the frames for an entertainment session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionOwner {
+    /// A sync app, streaming over DTLS
+    Peer(SocketAddr),
+    /// A generated effect
+    Effect,
+    /// A replayed recording
+    Replay,
+}
+
+/// The entertainment session currently streaming
+#[derive(Debug, Clone, Copy)]
+pub struct Session {
+    pub area: Uuid,
+    pub owner: SessionOwner,
+}
+
+/// The single entertainment session slot, shared by all frame sources
+pub type SessionSlot = Arc<Mutex<Option<Session>>>;
 
 pub struct EntertainmentService {
     addr: SocketAddr,
     udp: Option<Arc<UdpListener>>,
     ctx: Option<SslContext>,
+    identity: Option<Index<Ssl, String>>,
+    session: SessionSlot,
+    recording_dir: Option<Utf8PathBuf>,
     res: Arc<Mutex<Resources>>,
 }
 
 impl EntertainmentService {
-    pub fn new(addr: Ipv4Addr, port: u16, res: Arc<Mutex<Resources>>) -> ApiResult<Self> {
+    pub fn new(
+        addr: Ipv4Addr,
+        port: u16,
+        recording_dir: Option<Utf8PathBuf>,
+        res: Arc<Mutex<Resources>>,
+        session: SessionSlot,
+    ) -> ApiResult<Self> {
         let res = Self {
             addr: SocketAddr::new(addr.into(), port),
             udp: None,
             ctx: None,
+            identity: None,
+            session,
+            recording_dir,
             res,
         };
 
@@ -52,9 +92,7 @@ impl EntertainmentService {
     }
 
     async fn read_frame(sess: &mut SslStream<UdpStream>, buf: &mut [u8]) -> ApiResult<usize> {
-        const TIMEOUT: Duration = Duration::from_secs(10);
-
-        match timeout(TIMEOUT, sess.read(buf)).await {
+        match timeout(SESSION_TIMEOUT, sess.read(buf)).await {
             Ok(Err(err)) if err.kind() == ErrorKind::UnexpectedEof => {
                 log::debug!("Sync stream stopped by sender");
                 Ok(0)
@@ -191,7 +229,72 @@ impl EntertainmentService {
         }
     }
 
-    pub async fn run_loop(&self, mut sess: SslStream<UdpStream>) -> ApiResult<()> {
+    /// Find the application streaming on a session, from its PSK identity
+    fn streamer(sess: &SslStream<UdpStream>, identity: Option<Index<Ssl, String>>) -> ResourceLink {
+        identity
+            .and_then(|index| sess.ssl().ex_data(index))
+            .map_or_else(
+                || RType::AuthV1.link_to(uuid!(STANDARD_APPLICATION_ID)),
+                |client_id| auth::application_for_key(client_id),
+            )
+    }
+
+    /// Claim the entertainment area for a new session, unless another session
+    /// is already streaming.
+    pub async fn start_session(
+        res: &Mutex<Resources>,
+        session: &Mutex<Option<Session>>,
+        new: Session,
+        streamer: ResourceLink,
+    ) -> ApiResult<()> {
+        let mut active = session.lock().await;
+
+        if let Some(current) = *active {
+            log::warn!(
+                "Rejecting entertainment stream from {:?}: area {} is busy",
+                new.owner,
+                current.area
+            );
+            return Err(ApiError::EntStreamBusy(current.area));
+        }
+
+        res.lock().await.start_entertainment(new.area, streamer)?;
+
+        log::info!("Entertainment session started by {:?}", new.owner);
+        *active = Some(new);
+        drop(active);
+
+        Ok(())
+    }
+
+    /// Release the entertainment area held by a session, if the session is
+    /// still the one streaming to `area`
+    pub async fn stop_session(
+        res: &Mutex<Resources>,
+        session: &Mutex<Option<Session>>,
+        area: Uuid,
+        owner: SessionOwner,
+    ) -> ApiResult<()> {
+        let mut active = session.lock().await;
+
+        let Some(current) = active.take_if(|sess| sess.owner == owner && sess.area == area) else {
+            return Ok(());
+        };
+        drop(active);
+
+        log::info!("Entertainment session stopped by {owner:?}");
+
+        res.lock().await.stop_entertainment(current.area)
+    }
+
+    async fn run_session(
+        res: Arc<Mutex<Resources>>,
+        session: SessionSlot,
+        identity: Option<Index<Ssl, String>>,
+        recording_dir: Option<Utf8PathBuf>,
+        mut sess: SslStream<UdpStream>,
+        peer: SocketAddr,
+    ) -> ApiResult<()> {
         let mut buf = [0u8; 1024];
 
         timeout(Duration::from_secs(5), Pin::new(&mut sess).accept())
@@ -201,22 +304,61 @@ impl EntertainmentService {
         // read the first frame, and use it to look up area, color mode, etc.
         // this means we discard the first frame, but since we expect at least
         // 10 frames *per second*, this is acceptable.
-        let mut sz = Self::read_frame(&mut sess, &mut buf).await?;
+        let sz = Self::read_frame(&mut sess, &mut buf).await?;
         log::trace!("First entertainment frame: {}", hex::encode(&buf[..sz]));
         let raw = HueStreamPacket::parse(&buf[..sz])?;
 
-        let lock = self.res.lock().await;
+        let lock = res.lock().await;
 
         let header = Self::translate_frame(&lock, raw)?;
 
         // look up entertainment area, to make sure it exists
         let _ent: &EntertainmentConfiguration = lock.get_id(header.area)?;
 
-        // request entertainment mode start
-        lock.backend_request(BackendRequest::EntertainmentStart(header.area))?;
-
         drop(lock);
 
+        let streamer = Self::streamer(&sess, identity);
+        let new = Session {
+            area: header.area,
+            owner: SessionOwner::Peer(peer),
+        };
+        Self::start_session(&res, &session, new, streamer).await?;
+
+        // only record sessions that actually got to stream
+        let result = match Self::create_recorder(recording_dir.as_deref(), header.area) {
+            Ok(mut recorder) => {
+                let result =
+                    Self::run_loop(&res, &mut sess, &mut buf, sz, &header, &mut recorder).await;
+                let finished = recorder.map_or(Ok(()), EntRecorder::finish);
+                result.and(finished)
+            }
+            Err(err) => Err(err),
+        };
+
+        Self::stop_session(&res, &session, header.area, SessionOwner::Peer(peer)).await?;
+
+        result
+    }
+
+    fn create_recorder(dir: Option<&Utf8Path>, area: Uuid) -> ApiResult<Option<EntRecorder>> {
+        let Some(dir) = dir else {
+            return Ok(None);
+        };
+
+        let (path, rec) = EntRecorder::create_in(dir, area)?;
+        log::info!("Recording entertainment stream to {path}");
+
+        Ok(Some(rec))
+    }
+
+    async fn run_loop(
+        res: &Mutex<Resources>,
+        sess: &mut SslStream<UdpStream>,
+        buf: &mut [u8],
+        mut sz: usize,
+        header: &HueStreamPacketV2,
+        recorder: &mut Option<EntRecorder>,
+    ) -> ApiResult<()> {
         let mut fps = 0;
         let mut period = Utc::now().timestamp();
 
@@ -225,7 +367,19 @@ impl EntertainmentService {
             log::trace!("Packet buffer: {}", view.escape_ascii());
 
             let raw = HueStreamPacket::parse(view)?;
-            let pkt = Self::translate_frame(&*self.res.lock().await, raw)?;
+
+            let lock = res.lock().await;
+
+            // the area can be stopped through the api, which ends the session
+            if !lock
+                .get_id::<EntertainmentConfiguration>(header.area)?
+                .is_streaming()
+            {
+                log::info!("Entertainment area stopped, ending session");
+                break;
+            }
+
+            let pkt = Self::translate_frame(&lock, raw)?;
 
             if pkt.color_mode() != header.color_mode() {
                 log::error!("Entertainment Mode color_mode changed mid-stream.");
@@ -245,10 +399,20 @@ impl EntertainmentService {
             }
 
             fps += 1;
+
+            // a failing recording must not take the live session down with it
+            if let Some(rec) = recorder {
+                if let Err(err) = rec.record(&pkt) {
+                    log::error!("Failed to record entertainment frame, stopping recording: {err}");
+                    *recorder = None;
+                }
+            }
+
             let req = BackendRequest::EntertainmentFrame(pkt.lights);
-            self.res.lock().await.backend_request(req)?;
+            lock.backend_request(req)?;
+            drop(lock);
 
-            sz = Self::read_frame(&mut sess, &mut buf).await?;
+            sz = Self::read_frame(sess, buf).await?;
             if sz == 0 {
                 break;
             }
@@ -265,9 +429,13 @@ impl Service for EntertainmentService {
     async fn configure(&mut self) -> Result<(), Self::Error> {
         let mut bldr = SslContext::builder(SslMethod::dtls_server())?;
 
-        bldr.set_psk_server_callback(|_sslref, cid, psk| {
+        let identity = Ssl::new_ex_index()?;
+        self.identity = Some(identity);
+
+        bldr.set_psk_server_callback(move |sslref, cid, psk| {
             let client_id = String::from_utf8_lossy(cid.unwrap_or_default());
             log::debug!("Setting PSK for {client_id}",);
+            sslref.set_ex_data(identity, client_id.to_string());
             STANDARD_CLIENT_KEY.write_to_slice(psk).unwrap();
 
             log::trace!("psk: {}", hex::encode(&psk[..16]));
@@ -301,18 +469,33 @@ impl Service for EntertainmentService {
             return Err(ApiError::service_error("Ctx not initialized"));
         };
 
+        let mut sessions = JoinSet::new();
+
         loop {
-            let (socket, _addr) = udp.accept().await?;
-            let ssl = Ssl::new(ctx)?;
-            let stream = SslStream::new(ssl, socket)?;
+            tokio::select! {
+                conn = udp.accept() => {
+                    let (socket, peer) = conn?;
+                    let ssl = Ssl::new(ctx)?;
+                    let stream = SslStream::new(ssl, socket)?;
+
+                    sessions.spawn(Self::run_session(
+                        self.res.clone(),
+                        self.session.clone(),
+                        self.identity,
+                        self.recording_dir.clone(),
+                        stream,
+                        peer,
+                    ));
+                }
 
-            match self.run_loop(stream).await {
-                Ok(()) => log::info!("Entertainment stream finished"),
-                Err(err) => log::error!("Entertainment stream error: {err}"),
+                Some(done) = sessions.join_next() => {
+                    match done {
+                        Ok(Ok(())) => log::info!("Entertainment stream finished"),
+                        Ok(Err(err)) => log::error!("Entertainment stream error: {err}"),
+                        Err(err) => log::error!("Entertainment stream failed: {err}"),
+                    }
+                }
             }
-
-            let req = BackendRequest::EntertainmentStop();
-            self.res.lock().await.backend_request(req)?;
         }
     }
 
@@ -321,3 +504,81 @@ impl Service for EntertainmentService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hue::api::{EntertainmentConfiguration, RType, Resource, ResourceLink};
+    use hue::version::SwVersion;
+    use serde_json::json;
+    use tokio::sync::Mutex;
+    use uuid::Uuid;
+
+    use crate::model::state::State;
+    use crate::resource::Resources;
+    use crate::server::entertainment::{EntertainmentService, Session, SessionOwner};
+
+    fn add_area(res: &mut Resources, name: &str) -> Uuid {
+        let link = RType::EntertainmentConfiguration.deterministic(name);
+        let area: EntertainmentConfiguration = serde_json::from_value(json!({
+            "name": name,
+            "configuration_type": "screen",
+            "metadata": {"name": name},
+            "status": "inactive",
+            "stream_proxy": {
+                "mode": "auto",
+                "node": RType::Entertainment.deterministic("proxy"),
+            },
+            "locations": {"service_locations": []},
+            "light_services": [],
+            "channels": [],
+        }))
+        .unwrap();
+        res.add(&link, Resource::EntertainmentConfiguration(area))
+            .unwrap();
+        link.rid
+    }
+
+    fn is_streaming(res: &Resources, area: Uuid) -> bool {
+        res.get_id::<EntertainmentConfiguration>(area)
+            .unwrap()
+            .is_streaming()
+    }
+
+    #[test]
+    fn stop_session_requires_matching_area() {
+        let mut res = Resources::new(SwVersion::new(1, String::new()), State::new());
+        let _rx = res.backend_event_stream();
+        let area_a = add_area(&mut res, "a");
+        let area_b = add_area(&mut res, "b");
+
+        let res = Mutex::new(res);
+        let session = Mutex::new(None);
+        let streamer = ResourceLink::new(Uuid::nil(), RType::AuthV1);
+
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let new = Session {
+                    area: area_a,
+                    owner: SessionOwner::Effect,
+                };
+                EntertainmentService::start_session(&res, &session, new, streamer)
+                    .await
+                    .unwrap();
+
+                // releasing another area must leave the running session alone
+                EntertainmentService::stop_session(&res, &session, area_b, SessionOwner::Effect)
+                    .await
+                    .unwrap();
+                assert!(session.lock().await.is_some());
+                assert!(is_streaming(&*res.lock().await, area_a));
+
+                EntertainmentService::stop_session(&res, &session, area_a, SessionOwner::Effect)
+                    .await
+                    .unwrap();
+                assert!(session.lock().await.is_none());
+                assert!(!is_streaming(&*res.lock().await, area_a));
+            });
+    }
+}
@@ -3,10 +3,12 @@ pub mod banner;
 
 pub mod appstate;
 pub mod certificate;
+pub mod effects;
 pub mod entertainment;
 pub mod http;
 pub mod hueevents;
 pub mod mdns;
+pub mod recording;
 pub mod ssdp;
 pub mod updater;
 
@@ -99,6 +101,28 @@ pub fn build_service(
     ServiceExt::<Request>::into_make_service_with_connect_info(normalized)
 }
 
+pub async fn home_aggregator(res: Arc<Mutex<Resources>>) -> ApiResult<()> {
+    const STABILIZE_TIME: Duration = Duration::from_millis(200);
+
+    let rx = res.lock().await.home_channel();
+
+    loop {
+        rx.notified().await;
+
+        /* Lights tend to change in bursts (e.g. when a room or scene is
+         * recalled), so only recompute once they have settled */
+        let deadline = tokio::time::Instant::now() + STABILIZE_TIME;
+        loop {
+            select! {
+                () = rx.notified() => {},
+                () = sleep_until(deadline) => break,
+            }
+        }
+
+        res.lock().await.aggregate_bridge_home()?;
+    }
+}
+
 pub async fn config_writer(res: Arc<Mutex<Resources>>, filename: Utf8PathBuf) -> ApiResult<()> {
     const STABILIZE_TIME: Duration = Duration::from_secs(1);
 
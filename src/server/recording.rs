@@ -0,0 +1,276 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::{Duration, Instant};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::Utc;
+use tokio::sync::Mutex;
+use tokio::time::sleep_until;
+use uuid::{Uuid, uuid};
+
+use bifrost_api::backend::BackendRequest;
+use hue::api::{EntertainmentConfiguration, RType};
+use hue::stream::{HueStreamPacket, HueStreamPacketV2};
+
+use crate::error::{ApiError, ApiResult};
+use crate::resource::Resources;
+use crate::routes::auth::STANDARD_APPLICATION_ID;
+use crate::server::entertainment::{EntertainmentService, Session, SessionOwner, SessionSlot};
+
+/// Recorder for (decrypted) entertainment streams
+///
+/// The file format is a short magic header, followed by a record for each
+/// frame: a 4-byte timestamp (milliseconds since recording start), a 2-byte
+/// length, and the frame itself, encoded as a [`HueStreamPacketV2`]. All
+/// integers are big-endian.
+pub struct EntRecorder {
+    file: BufWriter<File>,
+    start: Instant,
+}
+
+pub struct EntRecordingFrame {
+    pub offset: Duration,
+    pub packet: HueStreamPacketV2,
+}
+
+impl EntRecorder {
+    pub const MAGIC: &[u8] = b"HueStreamRec\x01";
+    pub const EXTENSION: &str = "hsr";
+
+    pub fn create(path: &Utf8Path) -> ApiResult<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(Self::MAGIC)?;
+
+        Ok(Self {
+            file,
+            start: Instant::now(),
+        })
+    }
+
+    /// Start a new recording for `area` in `dir`, named after the current time
+    pub fn create_in(dir: &Utf8Path, area: Uuid) -> ApiResult<(Utf8PathBuf, Self)> {
+        let ts = Utc::now().format("%Y%m%d-%H%M%S");
+        let path = dir.join(format!("{ts}-{area}.{}", Self::EXTENSION));
+
+        let rec = Self::create(&path)?;
+
+        Ok((path, rec))
+    }
+
+    pub fn record(&mut self, pkt: &HueStreamPacketV2) -> ApiResult<()> {
+        let offset = u32::try_from(self.start.elapsed().as_millis())?;
+        let data = pkt.pack()?;
+        let len = u16::try_from(data.len())?;
+
+        self.file.write_all(&offset.to_be_bytes())?;
+        self.file.write_all(&len.to_be_bytes())?;
+        self.file.write_all(&data)?;
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> ApiResult<()> {
+        Ok(self.file.flush()?)
+    }
+
+    /// Parse a recording made by [`EntRecorder`]
+    pub fn parse(data: &[u8]) -> ApiResult<Vec<EntRecordingFrame>> {
+        let mut data = data
+            .strip_prefix(Self::MAGIC)
+            .ok_or(ApiError::EntRecordingInvalid)?;
+
+        let mut frames = vec![];
+
+        while !data.is_empty() {
+            let (hdr, rest) = data
+                .split_first_chunk::<6>()
+                .ok_or(ApiError::EntRecordingInvalid)?;
+
+            let offset = u32::from_be_bytes([hdr[0], hdr[1], hdr[2], hdr[3]]);
+            let len = usize::from(u16::from_be_bytes([hdr[4], hdr[5]]));
+
+            if rest.len() < len {
+                return Err(ApiError::EntRecordingInvalid);
+            }
+            let (raw, rest) = rest.split_at(len);
+
+            let HueStreamPacket::V2(packet) = HueStreamPacket::parse(raw)? else {
+                return Err(ApiError::EntRecordingInvalid);
+            };
+
+            frames.push(EntRecordingFrame {
+                offset: Duration::from_millis(offset.into()),
+                packet,
+            });
+
+            data = rest;
+        }
+
+        Ok(frames)
+    }
+}
+
+/// Feed a recording to the backends, with the original frame timing
+pub async fn replay(
+    res: &Mutex<Resources>,
+    session: &SessionSlot,
+    frames: Vec<EntRecordingFrame>,
+) -> ApiResult<()> {
+    let Some(area) = frames.first().map(|frame| frame.packet.area) else {
+        return Ok(());
+    };
+
+    let new = Session {
+        area,
+        owner: SessionOwner::Replay,
+    };
+    let streamer = RType::AuthV1.link_to(uuid!(STANDARD_APPLICATION_ID));
+    EntertainmentService::start_session(res, session, new, streamer).await?;
+
+    log::info!(
+        "Replaying {} entertainment frames to area {area}",
+        frames.len()
+    );
+
+    let result = replay_frames(res, area, frames).await;
+
+    // always release the area, even if the replay failed halfway through
+    EntertainmentService::stop_session(res, session, area, SessionOwner::Replay).await?;
+
+    result
+}
+
+async fn replay_frames(
+    res: &Mutex<Resources>,
+    area: Uuid,
+    frames: Vec<EntRecordingFrame>,
+) -> ApiResult<()> {
+    let start = Instant::now();
+    for frame in frames {
+        sleep_until((start + frame.offset).into()).await;
+
+        let lock = res.lock().await;
+
+        // the area can be stopped through the api, which ends the replay
+        if !lock
+            .get_id::<EntertainmentConfiguration>(area)?
+            .is_streaming()
+        {
+            log::info!("Entertainment area stopped, ending replay");
+            break;
+        }
+
+        lock.backend_request(BackendRequest::EntertainmentFrame(frame.packet.lights))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8PathBuf;
+    use hue::stream::{HueStreamLightsV2, HueStreamPacketV2, Rgb16, Rgb16V2, Xy16, Xy16V2};
+    use uuid::{Uuid, uuid};
+
+    use crate::error::ApiError;
+    use crate::server::recording::EntRecorder;
+
+    const AREA: Uuid = uuid!("01234567-89ab-cdef-0123-456789abcdef");
+
+    fn packets() -> Vec<HueStreamPacketV2> {
+        let xy = HueStreamLightsV2::Xy(vec![Xy16V2 {
+            channel: 1,
+            xy: Xy16 {
+                x: 0x1234,
+                y: 0x5678,
+                b: 0x9ABC,
+            },
+        }]);
+        let rgb = HueStreamLightsV2::Rgb(vec![
+            Rgb16V2 {
+                channel: 0,
+                rgb: Rgb16 {
+                    r: 0xFFFF,
+                    g: 0,
+                    b: 0x8000,
+                },
+            },
+            Rgb16V2 {
+                channel: 2,
+                rgb: Rgb16 { r: 1, g: 2, b: 3 },
+            },
+        ]);
+
+        vec![
+            HueStreamPacketV2 {
+                area: AREA,
+                lights: xy,
+            },
+            HueStreamPacketV2 {
+                area: AREA,
+                lights: rgb,
+            },
+        ]
+    }
+
+    fn record(name: &str, pkts: &[HueStreamPacketV2]) -> Vec<u8> {
+        let dir = Utf8PathBuf::try_from(std::env::temp_dir()).unwrap();
+        let path = dir.join(format!("bifrost-test-{name}-{}.hsr", std::process::id()));
+
+        let mut rec = EntRecorder::create(&path).unwrap();
+        for pkt in pkts {
+            rec.record(pkt).unwrap();
+        }
+        rec.finish().unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        data
+    }
+
+    #[test]
+    fn record_parse_roundtrip() {
+        let pkts = packets();
+        let data = record("roundtrip", &pkts);
+
+        let frames = EntRecorder::parse(&data).unwrap();
+
+        assert_eq!(frames.len(), pkts.len());
+        for (frame, pkt) in frames.iter().zip(&pkts) {
+            assert_eq!(frame.packet.area, AREA);
+            assert_eq!(frame.packet.pack().unwrap(), pkt.pack().unwrap());
+        }
+        assert!(frames[0].offset <= frames[1].offset);
+    }
+
+    #[test]
+    fn parse_empty_recording() {
+        let data = record("empty", &[]);
+
+        assert_eq!(data, EntRecorder::MAGIC);
+        assert!(EntRecorder::parse(&data).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_bad_magic() {
+        let mut data = record("magic", &packets());
+        data[0] ^= 0xFF;
+
+        assert!(matches!(
+            EntRecorder::parse(&data),
+            Err(ApiError::EntRecordingInvalid)
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_truncated_frame() {
+        let data = record("truncated", &packets());
+
+        for len in [data.len() - 1, EntRecorder::MAGIC.len() + 3] {
+            assert!(matches!(
+                EntRecorder::parse(&data[..len]),
+                Err(ApiError::EntRecordingInvalid)
+            ));
+        }
+    }
+}